@@ -0,0 +1,210 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use super::RandomSource;
+
+// The default timeout for receiving all decision acks before continuing to the next epoch.
+//
+// This value is not important for the overall correctness fo the algorithm. A timeout here is
+// processed the same way as receiving all acks. If participants aren't ready for the next epoch,
+// they will use the recovery protocol to catch up.
+pub(super) const DEFAULT_ACK_TIMEOUT_SECONDS: u64 = 5;
+
+// The default timeout for the coordinator's application to vote once all participants voted yes.
+// This is shorter than the decision timeout, so that participants which voted yes learn the
+// decision before they start the termination protocol.
+pub(super) const DEFAULT_COORDINATOR_VOTE_TIMEOUT_SECONDS: u64 = 20;
+
+// The default number of decided epochs kept in the decision history.
+pub(super) const DEFAULT_DECISION_HISTORY_SIZE: usize = 32;
+
+// The default maximum time a participant waits between rounds of DecisionRequest messages. The
+// wait starts at the decision timeout and doubles after each round.
+pub(super) const DEFAULT_DECISION_REQUEST_MAX_TIMEOUT_SECONDS: u64 = 300;
+
+// The default timeout for a participant waiting on a decision after voting yes.
+pub(super) const DEFAULT_DECISION_TIMEOUT_SECONDS: u64 = 30;
+
+// The default timeout for the application to vote after a RequestForVote notification. This is
+// shorter than the vote timeout, so that a participant's NO vote can reach the coordinator before
+// the coordinator gives up waiting on it.
+pub(super) const DEFAULT_LOCAL_VOTE_TIMEOUT_SECONDS: u64 = 20;
+
+// The default timeout for the coordinator waiting on votes from all participants.
+pub(super) const DEFAULT_VOTE_TIMEOUT_SECONDS: u64 = 30;
+
+/// The decision presumed for an epoch when the coordinator no longer has a record of it.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Presumption {
+    /// Every decision is acknowledged by the participants before the coordinator advances to the
+    /// next epoch, and no decision is presumed.
+    Nothing,
+
+    /// Abort decisions are neither acknowledged nor remembered by the coordinator. A request for
+    /// the decision of an epoch the coordinator has no record of is answered with abort.
+    Abort,
+
+    /// Commit decisions are neither acknowledged nor remembered by the coordinator. A request for
+    /// the decision of an epoch the coordinator has no record of is answered with commit.
+    Commit,
+}
+
+// A random source shared by the copies of a configuration. As a random source can not be compared,
+// two configurations are only equal if they share the same random source.
+#[derive(Clone, Debug)]
+pub(super) struct SharedRandomSource(pub(super) Arc<dyn RandomSource>);
+
+impl PartialEq for SharedRandomSource {
+    fn eq(&self, other: &Self) -> bool {
+        Arc::ptr_eq(&self.0, &other.0)
+    }
+}
+
+impl Eq for SharedRandomSource {}
+
+/// Configuration for an instance of the two-phase commit algorithm.
+///
+/// A configuration is created with [`TwoPhaseCommitConfigBuilder`] or, to use the default values,
+/// with [`TwoPhaseCommitConfig::default`].
+///
+/// [`TwoPhaseCommitConfigBuilder`]: super::TwoPhaseCommitConfigBuilder
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct TwoPhaseCommitConfig {
+    pub(super) ack_timeout: Duration,
+    pub(super) coordinator_vote_timeout: Duration,
+    pub(super) decision_history_size: usize,
+    pub(super) decision_request_limit: Option<u32>,
+    pub(super) decision_request_max_timeout: Duration,
+    pub(super) decision_request_random_source: Option<SharedRandomSource>,
+    pub(super) decision_timeout: Duration,
+    pub(super) decentralized: bool,
+    pub(super) local_vote_timeout: Duration,
+    pub(super) presumption: Presumption,
+    pub(super) vote_timeout: Duration,
+}
+
+impl TwoPhaseCommitConfig {
+    /// The time the coordinator waits for decision acks before advancing to the next epoch.
+    pub fn ack_timeout(&self) -> &Duration {
+        &self.ack_timeout
+    }
+
+    /// The time the coordinator's application has to vote after a RequestForVote notification,
+    /// which is sent once all participants have voted yes. When it expires, the coordinator
+    /// decides to abort, so that the participants are not left uncertain of the decision.
+    pub fn coordinator_vote_timeout(&self) -> &Duration {
+        &self.coordinator_vote_timeout
+    }
+
+    /// The number of decided epochs kept in the decision history of the context.
+    ///
+    /// Each decision is added to the history when it is made. Once the history holds more than
+    /// this number of decisions, the decisions of the oldest epochs are removed. A size of zero
+    /// disables the history.
+    pub fn decision_history_size(&self) -> &usize {
+        &self.decision_history_size
+    }
+
+    /// The number of rounds of DecisionRequest messages a participant sends in the termination
+    /// protocol before it gives up and notifies that it is blocked. If not set, a participant
+    /// retries until it learns the decision.
+    pub fn decision_request_limit(&self) -> &Option<u32> {
+        &self.decision_request_limit
+    }
+
+    /// The maximum time a participant waits for a decision between rounds of DecisionRequest
+    /// messages.
+    ///
+    /// The wait after the first round is twice the decision timeout, and doubles after each
+    /// further round until it reaches this maximum. This keeps participants which are blocked on
+    /// a failed coordinator from flooding it with requests once it is reachable again.
+    pub fn decision_request_max_timeout(&self) -> &Duration {
+        &self.decision_request_max_timeout
+    }
+
+    /// The random source for the jitter of the wait between rounds of DecisionRequest messages.
+    ///
+    /// If set, each wait is shortened by a random amount of up to half, so that participants
+    /// which timed out together do not send their requests together again. If not set, no jitter
+    /// is added.
+    pub fn decision_request_random_source(&self) -> Option<&dyn RandomSource> {
+        self.decision_request_random_source
+            .as_ref()
+            .map(|source| source.0.as_ref())
+    }
+
+    /// The time a participant waits for a decision after voting yes before it starts the
+    /// termination protocol.
+    pub fn decision_timeout(&self) -> &Duration {
+        &self.decision_timeout
+    }
+
+    /// Whether votes are exchanged among all participants.
+    ///
+    /// In decentralized two-phase commit, each participant sends its vote to every other
+    /// participant as well as to the coordinator, and decides on its own once it has all votes.
+    /// The coordinator does not vote or send decisions; starting an epoch is its yes vote, and it
+    /// decides the same way once it has all votes. This saves the coordinator's round of decision
+    /// messages and acks, at the cost of a quadratic number of vote messages, so it suits small
+    /// groups.
+    ///
+    /// As a participant may commit as soon as it has all votes, the coordinator can not abort an
+    /// epoch on its vote timeout. Instead, it sends a DecisionRequest to each participant whose
+    /// vote is missing, which is answered with the vote, or with a no vote if the participant has
+    /// not voted yet.
+    pub fn decentralized(&self) -> &bool {
+        &self.decentralized
+    }
+
+    /// The time a participant's application has to vote after a RequestForVote notification. When
+    /// it expires, the participant votes no on its own.
+    ///
+    /// This is never greater than the vote timeout.
+    pub fn local_vote_timeout(&self) -> &Duration {
+        &self.local_vote_timeout
+    }
+
+    /// The decision presumed by the coordinator for epochs it has no record of.
+    pub fn presumption(&self) -> &Presumption {
+        &self.presumption
+    }
+
+    /// The time the coordinator waits for all participants to vote before deciding to abort.
+    pub fn vote_timeout(&self) -> &Duration {
+        &self.vote_timeout
+    }
+}
+
+impl Default for TwoPhaseCommitConfig {
+    fn default() -> Self {
+        Self {
+            ack_timeout: Duration::from_secs(DEFAULT_ACK_TIMEOUT_SECONDS),
+            coordinator_vote_timeout: Duration::from_secs(DEFAULT_COORDINATOR_VOTE_TIMEOUT_SECONDS),
+            decision_history_size: DEFAULT_DECISION_HISTORY_SIZE,
+            decision_request_limit: None,
+            decision_request_max_timeout: Duration::from_secs(
+                DEFAULT_DECISION_REQUEST_MAX_TIMEOUT_SECONDS,
+            ),
+            decision_request_random_source: None,
+            decision_timeout: Duration::from_secs(DEFAULT_DECISION_TIMEOUT_SECONDS),
+            decentralized: false,
+            local_vote_timeout: Duration::from_secs(DEFAULT_LOCAL_VOTE_TIMEOUT_SECONDS),
+            presumption: Presumption::Nothing,
+            vote_timeout: Duration::from_secs(DEFAULT_VOTE_TIMEOUT_SECONDS),
+        }
+    }
+}
@@ -0,0 +1,235 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::sync::Arc;
+use std::time::Duration;
+
+use crate::error::InvalidStateError;
+
+use super::config::SharedRandomSource;
+use super::Presumption;
+use super::RandomSource;
+use super::TwoPhaseCommitConfig;
+
+/// Builds a [`TwoPhaseCommitConfig`].
+///
+/// Any value which is not set explicitly uses the default from [`TwoPhaseCommitConfig::default`].
+#[derive(Default)]
+pub struct TwoPhaseCommitConfigBuilder {
+    ack_timeout: Option<Duration>,
+    coordinator_vote_timeout: Option<Duration>,
+    decision_history_size: Option<usize>,
+    decision_request_limit: Option<u32>,
+    decision_request_max_timeout: Option<Duration>,
+    decision_request_random_source: Option<SharedRandomSource>,
+    decision_timeout: Option<Duration>,
+    decentralized: Option<bool>,
+    local_vote_timeout: Option<Duration>,
+    presumption: Option<Presumption>,
+    vote_timeout: Option<Duration>,
+}
+
+impl TwoPhaseCommitConfigBuilder {
+    pub fn new() -> Self {
+        Self {
+            ack_timeout: None,
+            coordinator_vote_timeout: None,
+            decision_history_size: None,
+            decision_request_limit: None,
+            decision_request_max_timeout: None,
+            decision_request_random_source: None,
+            decision_timeout: None,
+            decentralized: None,
+            local_vote_timeout: None,
+            presumption: None,
+            vote_timeout: None,
+        }
+    }
+
+    pub fn with_ack_timeout(mut self, ack_timeout: Duration) -> Self {
+        self.ack_timeout = Some(ack_timeout);
+        self
+    }
+
+    pub fn with_coordinator_vote_timeout(mut self, coordinator_vote_timeout: Duration) -> Self {
+        self.coordinator_vote_timeout = Some(coordinator_vote_timeout);
+        self
+    }
+
+    pub fn with_decision_history_size(mut self, decision_history_size: usize) -> Self {
+        self.decision_history_size = Some(decision_history_size);
+        self
+    }
+
+    pub fn with_decision_request_limit(mut self, decision_request_limit: u32) -> Self {
+        self.decision_request_limit = Some(decision_request_limit);
+        self
+    }
+
+    pub fn with_decision_request_max_timeout(
+        mut self,
+        decision_request_max_timeout: Duration,
+    ) -> Self {
+        self.decision_request_max_timeout = Some(decision_request_max_timeout);
+        self
+    }
+
+    pub fn with_decision_request_random_source<R>(mut self, random_source: R) -> Self
+    where
+        R: RandomSource + 'static,
+    {
+        self.decision_request_random_source = Some(SharedRandomSource(Arc::new(random_source)));
+        self
+    }
+
+    pub fn with_decision_timeout(mut self, decision_timeout: Duration) -> Self {
+        self.decision_timeout = Some(decision_timeout);
+        self
+    }
+
+    pub fn with_decentralized(mut self, decentralized: bool) -> Self {
+        self.decentralized = Some(decentralized);
+        self
+    }
+
+    pub fn with_local_vote_timeout(mut self, local_vote_timeout: Duration) -> Self {
+        self.local_vote_timeout = Some(local_vote_timeout);
+        self
+    }
+
+    pub fn with_presumption(mut self, presumption: Presumption) -> Self {
+        self.presumption = Some(presumption);
+        self
+    }
+
+    pub fn with_vote_timeout(mut self, vote_timeout: Duration) -> Self {
+        self.vote_timeout = Some(vote_timeout);
+        self
+    }
+
+    pub fn build(self) -> Result<TwoPhaseCommitConfig, InvalidStateError> {
+        let default = TwoPhaseCommitConfig::default();
+
+        let ack_timeout = self.ack_timeout.unwrap_or(default.ack_timeout);
+        let coordinator_vote_timeout = self
+            .coordinator_vote_timeout
+            .unwrap_or(default.coordinator_vote_timeout);
+        let decision_history_size = self
+            .decision_history_size
+            .unwrap_or(default.decision_history_size);
+        let decision_timeout = self.decision_timeout.unwrap_or(default.decision_timeout);
+        // The default maximum is raised to the decision timeout if needed, so that a long
+        // decision timeout can be set on its own.
+        let decision_request_max_timeout = self
+            .decision_request_max_timeout
+            .unwrap_or_else(|| default.decision_request_max_timeout.max(decision_timeout));
+        let decentralized = self.decentralized.unwrap_or(default.decentralized);
+        let presumption = self.presumption.unwrap_or(default.presumption);
+        let vote_timeout = self.vote_timeout.unwrap_or(default.vote_timeout);
+        // The default local vote timeout is lowered to the vote timeout if needed, so that a short
+        // vote timeout can be set on its own.
+        let local_vote_timeout = self
+            .local_vote_timeout
+            .unwrap_or_else(|| default.local_vote_timeout.min(vote_timeout));
+
+        // A zero timeout would expire as soon as it is set, so the algorithm could never receive
+        // the messages it is waiting for.
+        for (name, timeout) in [
+            ("ack_timeout", ack_timeout),
+            ("coordinator_vote_timeout", coordinator_vote_timeout),
+            ("decision_timeout", decision_timeout),
+            ("local_vote_timeout", local_vote_timeout),
+            ("vote_timeout", vote_timeout),
+        ] {
+            if timeout.is_zero() {
+                return Err(InvalidStateError::with_message(format!(
+                    "{name} field must be greater than zero"
+                )));
+            }
+        }
+
+        // A participant which has not voted when the coordinator's vote timeout expires has its
+        // NO vote ignored, so it must give up on the application first.
+        if local_vote_timeout > vote_timeout {
+            return Err(InvalidStateError::with_message(
+                "local_vote_timeout field must not be greater than vote_timeout field".into(),
+            ));
+        }
+
+        if decision_request_max_timeout < decision_timeout {
+            return Err(InvalidStateError::with_message(
+                "decision_request_max_timeout field must not be less than decision_timeout field"
+                    .into(),
+            ));
+        }
+
+        Ok(TwoPhaseCommitConfig {
+            ack_timeout,
+            coordinator_vote_timeout,
+            decision_history_size,
+            decision_request_limit: self.decision_request_limit,
+            decision_request_max_timeout,
+            decision_request_random_source: self.decision_request_random_source,
+            decision_timeout,
+            decentralized,
+            local_vote_timeout,
+            presumption,
+            vote_timeout,
+        })
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn build_config() {
+        let config = TwoPhaseCommitConfigBuilder::new()
+            .with_vote_timeout(Duration::from_millis(250))
+            .with_decision_timeout(Duration::from_secs(120))
+            .build()
+            .unwrap();
+
+        assert_eq!(*config.vote_timeout(), Duration::from_millis(250));
+        assert_eq!(*config.decision_timeout(), Duration::from_secs(120));
+        assert_eq!(
+            *config.ack_timeout(),
+            *TwoPhaseCommitConfig::default().ack_timeout()
+        );
+
+        assert!(TwoPhaseCommitConfigBuilder::new()
+            .with_ack_timeout(Duration::ZERO)
+            .build()
+            .is_err());
+
+        assert!(TwoPhaseCommitConfigBuilder::new()
+            .with_decision_timeout(Duration::from_secs(60))
+            .with_decision_request_max_timeout(Duration::from_secs(30))
+            .build()
+            .is_err());
+
+        assert!(TwoPhaseCommitConfigBuilder::new()
+            .with_vote_timeout(Duration::from_secs(30))
+            .with_local_vote_timeout(Duration::from_secs(60))
+            .build()
+            .is_err());
+
+        let config = TwoPhaseCommitConfigBuilder::new()
+            .with_vote_timeout(Duration::from_millis(1))
+            .build()
+            .unwrap();
+        assert_eq!(*config.local_vote_timeout(), Duration::from_millis(1));
+    }
+}
@@ -17,6 +17,7 @@ use crate::process::Process;
 use crate::time::Time;
 
 use super::CoordinatorContext;
+use super::Epoch;
 use super::TwoPhaseCommitContext;
 use super::TwoPhaseCommitMessage;
 use super::{TwoPhaseCommitAction, TwoPhaseCommitActionNotification};
@@ -28,19 +29,26 @@ where
     T: Time,
 {
     Update {
-        context: TwoPhaseCommitContext<P, T, CoordinatorContext<P, T>>,
+        context: TwoPhaseCommitContext<P, V, T, CoordinatorContext<P, V, T>>,
         alarm: Option<T>,
     },
-    SendMessage(P, TwoPhaseCommitMessage<V>),
-    Notify(CoordinatorActionNotification),
+    SendMessage(P, TwoPhaseCommitMessage<P, V>),
+    Notify(CoordinatorActionNotification<P, V>),
 }
 
-pub enum CoordinatorActionNotification {
-    RequestForStart(),
-    RequestForVote(),
-    Commit(),
-    Abort(),
+pub enum CoordinatorActionNotification<P, V>
+where
+    P: Process,
+    V: Value,
+{
+    RequestForStart(Epoch),
+    RequestForVote(Epoch, Option<V>, Vec<(P, V)>),
+    Commit(Epoch, Option<V>),
+    VoteValues(Epoch, Vec<(P, V)>),
+    Abort(Epoch, Option<V>),
     MessageDropped(String),
+    RequestForBatchVote(Epoch, Vec<V>, Vec<bool>),
+    BatchDecision(Epoch, Vec<V>, Vec<V>),
 }
 
 impl<P, V, T> From<CoordinatorAction<P, V, T>> for TwoPhaseCommitAction<P, V, T>
@@ -61,22 +69,42 @@ where
     }
 }
 
-impl<V> From<CoordinatorActionNotification> for TwoPhaseCommitActionNotification<V>
+impl<P, V> From<CoordinatorActionNotification<P, V>> for TwoPhaseCommitActionNotification<P, V>
 where
+    P: Process,
     V: Value,
 {
-    fn from(notification: CoordinatorActionNotification) -> Self {
+    fn from(notification: CoordinatorActionNotification<P, V>) -> Self {
         match notification {
-            CoordinatorActionNotification::Abort() => TwoPhaseCommitActionNotification::Abort(),
-            CoordinatorActionNotification::Commit() => TwoPhaseCommitActionNotification::Commit(),
+            CoordinatorActionNotification::Abort(epoch, value) => {
+                TwoPhaseCommitActionNotification::Abort(epoch, value)
+            }
+            CoordinatorActionNotification::Commit(epoch, value) => {
+                TwoPhaseCommitActionNotification::Commit(epoch, value)
+            }
+            CoordinatorActionNotification::VoteValues(epoch, vote_values) => {
+                TwoPhaseCommitActionNotification::CoordinatorVoteValues(epoch, vote_values)
+            }
             CoordinatorActionNotification::MessageDropped(s) => {
                 TwoPhaseCommitActionNotification::MessageDropped(s)
             }
-            CoordinatorActionNotification::RequestForStart() => {
-                TwoPhaseCommitActionNotification::RequestForStart()
+            CoordinatorActionNotification::RequestForStart(epoch) => {
+                TwoPhaseCommitActionNotification::RequestForStart(epoch)
+            }
+            CoordinatorActionNotification::RequestForVote(epoch, value, vote_values) => {
+                TwoPhaseCommitActionNotification::CoordinatorRequestForVote(
+                    epoch,
+                    value,
+                    vote_values,
+                )
+            }
+            CoordinatorActionNotification::RequestForBatchVote(epoch, values, accepted) => {
+                TwoPhaseCommitActionNotification::CoordinatorRequestForBatchVote(
+                    epoch, values, accepted,
+                )
             }
-            CoordinatorActionNotification::RequestForVote() => {
-                TwoPhaseCommitActionNotification::CoordinatorRequestForVote()
+            CoordinatorActionNotification::BatchDecision(epoch, committed, aborted) => {
+                TwoPhaseCommitActionNotification::BatchDecision(epoch, committed, aborted)
             }
         }
     }
@@ -13,7 +13,6 @@
 // limitations under the License.
 
 use std::marker::PhantomData;
-use std::time::Duration;
 
 use crate::algorithm::{Algorithm, Value};
 use crate::error::{AlgorithmError, InvalidStateError};
@@ -26,17 +25,24 @@ use super::CoordinatorContext;
 use super::CoordinatorEvent;
 use super::CoordinatorMessage;
 use super::CoordinatorState;
+use super::Decision;
+use super::Epoch;
+use super::Participant;
+use super::Presumption;
+use super::TwoPhaseCommitConfig;
 use super::TwoPhaseCommitContext;
 use super::TwoPhaseCommitMessage;
 
-// The timeout for receiving all decision acks before continuing to the next epoch.
-//
-// This value is not important for the overall correctness fo the algorithm. A timeout here is
-// processed the same way as receiving all acks. If participants aren't ready for the next epoch,
-// they will use the recovery protocol to catch up.
-const ACK_TIMEOUT_SECONDS: u64 = 5;
+type CoordinatorTwoPhaseCommitContext<P, V, T> =
+    TwoPhaseCommitContext<P, V, T, CoordinatorContext<P, V, T>>;
 
-const VOTE_TIMEOUT_SECONDS: u64 = 30;
+// A participant's response to our request for a vote, as it is recorded for that participant.
+struct ParticipantVote<V> {
+    vote: bool,
+    read_only: bool,
+    value: Option<V>,
+    batch_votes: Option<Vec<bool>>,
+}
 
 pub struct CoordinatorAlgorithm<P, V, TS>
 where
@@ -46,6 +52,7 @@ where
 {
     _process_phantom: PhantomData<P>,
     _value_phantom: PhantomData<V>,
+    config: TwoPhaseCommitConfig,
     time_source: TS,
 }
 
@@ -55,14 +62,37 @@ where
     V: Value,
     TS: TimeSource,
 {
-    pub fn new(time_source: TS) -> Self {
+    pub fn new(time_source: TS, config: TwoPhaseCommitConfig) -> Self {
         CoordinatorAlgorithm {
             _process_phantom: PhantomData,
             _value_phantom: PhantomData,
+            config,
             time_source,
         }
     }
 
+    // Returns the actions for aborting the current epoch before it has been decided, such as when
+    // the epoch has been aborted by the parent of a tree node.
+    pub(super) fn abort(
+        &self,
+        context: CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+    ) -> Result<Vec<CoordinatorAction<P, V, TS::Time>>, AlgorithmError> {
+        if !matches!(
+            context.state(),
+            CoordinatorState::Voting { .. } | CoordinatorState::WaitingForVote { .. }
+        ) {
+            return Err(AlgorithmError::InvalidState(
+                InvalidStateError::with_message(
+                    "abort when not in Voting or WaitingForVote state".into(),
+                ),
+            ));
+        }
+
+        let mut actions = Vec::new();
+        self.push_abort_actions(context, &mut actions);
+        Ok(actions)
+    }
+
     // Create actions for an abort decision. This set of actions is generated whenever an abort
     // occurs; an abort occurs when:
     //
@@ -70,49 +100,291 @@ where
     // - When all participants have voted but at least one voted NO
     fn push_abort_actions(
         &self,
-        mut context: TwoPhaseCommitContext<P, TS::Time, CoordinatorContext<P, TS::Time>>,
+        mut context: CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
         actions: &mut Vec<CoordinatorAction<P, V, TS::Time>>,
     ) {
         // The order of actions here is important! We must update our state to `Abort` before we
         // send any messages for correctness of the algorithm.
 
-        // Add an action to update the state to abort and unset the alarm.
+        // Add an action to update the state to abort and unset the alarm. Also record the
+        // decision in the decision history used to answer DecisionRequest messages.
+        context.record_decision(
+            *context.epoch(),
+            Decision::Abort,
+            self.config.decision_history_size,
+        );
         context.set_state(CoordinatorState::Abort);
         actions.push(CoordinatorAction::Update {
             context: context.clone(),
             alarm: None,
         });
 
-        // Send `Abort` to all participants which have voted yes.
+        // Send `Abort` to all participants which are expected to acknowledge it. In decentralized
+        // mode, participants decide on their own.
         for participant in context
             .participants()
             .iter()
-            .filter(|p| p.vote.unwrap_or(false))
+            .filter(|p| !self.config.decentralized && self.awaits_decision_ack(p, false))
         {
             actions.push(CoordinatorAction::SendMessage(
                 participant.process.clone(),
-                TwoPhaseCommitMessage::Abort(*context.epoch()),
+                context.abort_message(*context.epoch()),
             ))
         }
 
-        // Notify that we've aborted.
-        actions.push(CoordinatorAction::Notify(
-            CoordinatorActionNotification::Abort(),
-        ));
+        self.push_outcome_actions(&context, actions, Decision::Abort);
+
+        // Notify that we've aborted. The value is no longer needed once the epoch is decided.
+        Self::push_decision_notification_actions(&mut context, actions, false);
+
+        self.push_decided_actions(context, actions, false);
+    }
+
+    // Create actions for requesting the missing votes in decentralized mode. A DecisionRequest is
+    // sent to each participant which has not voted, and the vote timeout is restarted so that
+    // the requests are sent again if the votes are not received.
+    fn push_vote_decision_request_actions(
+        &self,
+        mut context: CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<CoordinatorAction<P, V, TS::Time>>,
+    ) {
+        for participant in context.participants().iter().filter(|p| p.vote.is_none()) {
+            actions.push(CoordinatorAction::SendMessage(
+                participant.process.clone(),
+                TwoPhaseCommitMessage::DecisionRequest(*context.epoch()),
+            ));
+        }
+
+        let vote_timeout_start = self.time_source.now();
+        context.set_state(CoordinatorState::Voting { vote_timeout_start });
+        actions.push(CoordinatorAction::Update {
+            context,
+            alarm: Some(vote_timeout_start + self.config.vote_timeout),
+        });
+    }
+
+    // Create actions for a commit decision. This set of actions is generated when all
+    // participants and the coordinator have voted yes. For a batch epoch, committed_items holds
+    // whether each item of the batch is committed.
+    fn push_commit_actions(
+        &self,
+        mut context: CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<CoordinatorAction<P, V, TS::Time>>,
+        committed_items: Vec<bool>,
+    ) {
+        // Add an action to update the state to commit and unset the alarm. Also update the last
+        // commit epoch and the decision history used to answer DecisionRequest messages.
+        context.set_last_commit_epoch(Some(*context.epoch()));
+        context.record_decision(
+            *context.epoch(),
+            Decision::Commit,
+            self.config.decision_history_size,
+        );
+        if !context.batch().is_empty() {
+            context.record_batch_decision(
+                *context.epoch(),
+                committed_items,
+                self.config.decision_history_size,
+            );
+        }
+        context.set_state(CoordinatorState::Commit);
+        actions.push(CoordinatorAction::Update {
+            context: context.clone(),
+            alarm: None,
+        });
 
-        // Wait for a decision ack.
-        self.push_wait_for_decision_ack(&mut context, actions);
+        // Send `Commit` to all participants, other than read-only participants. In decentralized
+        // mode, participants decide on their own.
+        for participant in context
+            .participants()
+            .iter()
+            .filter(|p| !self.config.decentralized && !p.read_only)
+        {
+            actions.push(CoordinatorAction::SendMessage(
+                participant.process.clone(),
+                context.commit_message(*context.epoch()),
+            ))
+        }
+
+        self.push_outcome_actions(&context, actions, Decision::Commit);
+
+        // Notify that we've committed, along with the values participants attached to their
+        // votes. The value is no longer needed once the epoch is decided.
+        Self::push_decision_notification_actions(&mut context, actions, true);
+
+        self.push_decided_actions(context, actions, true);
+    }
+
+    // Create the Commit or Abort notification for the current epoch, or the BatchDecision
+    // notification for a batch epoch. A Commit is preceded by a VoteValues notification if any
+    // participant attached a value to its yes vote. The value and the batch are taken from the
+    // context, as they are no longer needed once the epoch is decided.
+    fn push_decision_notification_actions(
+        context: &mut CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<CoordinatorAction<P, V, TS::Time>>,
+        committed: bool,
+    ) {
+        let epoch = *context.epoch();
+        let value = context.take_value();
+        let notification = if !context.batch().is_empty() {
+            let (committed_items, aborted_items) = context.take_batch_decision(epoch);
+            CoordinatorActionNotification::BatchDecision(epoch, committed_items, aborted_items)
+        } else if committed {
+            let vote_values = Self::vote_values(context);
+            if !vote_values.is_empty() {
+                actions.push(CoordinatorAction::Notify(
+                    CoordinatorActionNotification::VoteValues(epoch, vote_values),
+                ));
+            }
+            CoordinatorActionNotification::Commit(epoch, value)
+        } else {
+            CoordinatorActionNotification::Abort(epoch, value)
+        };
+        actions.push(CoordinatorAction::Notify(notification));
+    }
+
+    // Create actions for sending the outcome of the current epoch to the observers. The outcome of
+    // a batch epoch includes the batch and whether each item of it was committed.
+    fn push_outcome_actions(
+        &self,
+        context: &CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<CoordinatorAction<P, V, TS::Time>>,
+        decision: Decision,
+    ) {
+        let epoch = *context.epoch();
+        let outcome = if context.batch().is_empty() {
+            TwoPhaseCommitMessage::Outcome(epoch, context.value().clone(), decision)
+        } else {
+            let committed = match decision {
+                Decision::Commit => context.batch_decision(epoch).cloned().unwrap_or_default(),
+                Decision::Abort => vec![false; context.batch().len()],
+            };
+            TwoPhaseCommitMessage::BatchOutcome(epoch, context.batch().clone(), committed)
+        };
+
+        for observer in context.observers() {
+            actions.push(CoordinatorAction::SendMessage(
+                observer.clone(),
+                outcome.clone(),
+            ));
+        }
+    }
+
+    // Create actions for after a decision has been sent to the participants; either wait for
+    // decision acks or move directly to the next epoch.
+    fn push_decided_actions(
+        &self,
+        mut context: CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<CoordinatorAction<P, V, TS::Time>>,
+        committed: bool,
+    ) {
+        // With presumed abort, participants do not acknowledge an abort and the decision is
+        // forgotten. Likewise for a commit with presumed commit. In decentralized mode, no
+        // decision is sent, so none is acknowledged.
+        let acks_expected = !self.config.decentralized
+            && match self.config.presumption {
+                Presumption::Nothing => true,
+                Presumption::Abort => committed,
+                Presumption::Commit => !committed,
+            };
+
+        if acks_expected
+            && context
+                .participants()
+                .iter()
+                .any(|p| self.awaits_decision_ack(p, committed))
+        {
+            // Wait for a decision ack.
+            self.push_wait_for_decision_ack(&mut context, actions);
+        } else {
+            // There are no acks to wait for, so we can move directly to the next epoch.
+            self.push_advance_epoch_actions(&mut context, actions);
+        }
+    }
+
+    // Returns true if the participant is sent the decision, and is therefore expected to
+    // acknowledge it. A commit is sent to all participants. An abort is only sent to participants
+    // which voted yes, except with presumed commit: a yes vote may have been lost or may arrive
+    // after the vote timeout, and a participant which is never sent the abort would later be
+    // answered with the presumed commit. Read-only participants are not sent the decision.
+    fn awaits_decision_ack(&self, participant: &Participant<P, V>, committed: bool) -> bool {
+        !participant.decision_ack
+            && !participant.read_only
+            && (committed
+                || participant.vote.unwrap_or(false)
+                || self.config.presumption == Presumption::Commit)
+    }
+
+    // Returns the Commit or Abort message for the given epoch, if the decision for that epoch is
+    // known.
+    fn decision_message(
+        &self,
+        context: &CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+        epoch: Epoch,
+    ) -> Option<TwoPhaseCommitMessage<P, V>> {
+        // If the epoch is in the decision history, we know the decision exactly.
+        match context.decision(epoch) {
+            Some(Decision::Commit) => return Some(context.commit_message(epoch)),
+            Some(Decision::Abort) => return Some(context.abort_message(epoch)),
+            None => (),
+        }
+
+        // We record the last commit epoch in the context; if the epoch requested was the last
+        // commit epoch, the decision was Commit.
+        if Some(epoch) == *context.last_commit_epoch() {
+            return Some(context.commit_message(epoch));
+        }
+
+        // If the epoch is between the current epoch and the last commit epoch, we know that the
+        // decision must have been Abort.
+        if epoch < *context.epoch()
+            && (Some(epoch) > *context.last_commit_epoch() || context.last_commit_epoch().is_none())
+        {
+            return Some(context.abort_message(epoch));
+        }
+
+        // If we have decided to abort the current epoch but have not yet advanced to the next
+        // epoch, the decision was Abort. (A commit of the current epoch was handled above using
+        // the last commit epoch.)
+        if epoch == *context.epoch()
+            && matches!(
+                context.state(),
+                CoordinatorState::Abort | CoordinatorState::WaitingForDecisionAck { .. }
+            )
+        {
+            return Some(context.abort_message(epoch));
+        }
+
+        // With presumed abort, aborted epochs are not remembered and a commit is not forgotten
+        // until it has been acknowledged by every participant. The decision for any other past
+        // epoch is therefore presumed to be Abort.
+        //
+        // With presumed commit, the reverse is true and the decision for any other past epoch is
+        // presumed to be Commit.
+        if epoch < *context.epoch() {
+            match self.config.presumption {
+                Presumption::Abort => return Some(context.abort_message(epoch)),
+                Presumption::Commit => return Some(context.commit_message(epoch)),
+                Presumption::Nothing => (),
+            }
+        }
+
+        // Otherwise, the decision is unknown. If the epoch is before the last commit epoch, its
+        // decision has been pruned from the decision history. If the epoch is after our current
+        // epoch, we do not yet know what the future holds. Similarly, we do not yet have a
+        // decision for the current epoch.
+        None
     }
 
     // Create actions for switching into WaitingForDecisionAck state. This is the state after
     // a decision has been communicated to participants, before we start a new epoch.
     fn push_wait_for_decision_ack(
         &self,
-        context: &mut TwoPhaseCommitContext<P, TS::Time, CoordinatorContext<P, TS::Time>>,
+        context: &mut CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
         actions: &mut Vec<CoordinatorAction<P, V, TS::Time>>,
     ) {
         let ack_timeout_start = self.time_source.now();
-        let ack_timeout_end = ack_timeout_start + Duration::from_secs(ACK_TIMEOUT_SECONDS);
+        let ack_timeout_end = ack_timeout_start + self.config.ack_timeout;
         context.set_state(CoordinatorState::WaitingForDecisionAck { ack_timeout_start });
         actions.push(CoordinatorAction::Update {
             context: context.clone(),
@@ -120,18 +392,497 @@ where
         });
     }
 
+    // Returns true if the decision for the current epoch must be remembered until every
+    // participant has acknowledged it.
+    //
+    // With presumed abort, a commit decision may only be forgotten after all participants have
+    // acked it; otherwise, a participant which missed the commit would later be answered with the
+    // presumed abort. The same is true for an abort decision with presumed commit.
+    fn decision_requires_all_acks(
+        &self,
+        context: &CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+    ) -> bool {
+        let committed = *context.last_commit_epoch() == Some(*context.epoch());
+        match self.config.presumption {
+            Presumption::Nothing => false,
+            Presumption::Abort => committed,
+            Presumption::Commit => !committed,
+        }
+    }
+
+    // Create actions for re-sending the current epoch's decision to all participants which have
+    // not yet acknowledged it.
+    fn push_resend_decision_actions(
+        &self,
+        context: &CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<CoordinatorAction<P, V, TS::Time>>,
+    ) {
+        let epoch = *context.epoch();
+        let committed = *context.last_commit_epoch() == Some(epoch);
+
+        for participant in context
+            .participants()
+            .iter()
+            .filter(|p| self.awaits_decision_ack(p, committed))
+        {
+            let message = if committed {
+                context.commit_message(epoch)
+            } else {
+                context.abort_message(epoch)
+            };
+            actions.push(CoordinatorAction::SendMessage(
+                participant.process.clone(),
+                message,
+            ));
+        }
+    }
+
+    // Create a RequestForVote notification for the current epoch, including the values which
+    // participants attached to their yes votes. For a batch epoch, a RequestForBatchVote
+    // notification is created instead, including the items accepted by every participant.
+    fn request_for_vote(
+        context: &CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+    ) -> CoordinatorActionNotification<P, V> {
+        if !context.batch().is_empty() {
+            return CoordinatorActionNotification::RequestForBatchVote(
+                *context.epoch(),
+                context.batch().clone(),
+                Self::accepted_items(context),
+            );
+        }
+
+        CoordinatorActionNotification::RequestForVote(
+            *context.epoch(),
+            context.value().clone(),
+            Self::vote_values(context),
+        )
+    }
+
+    // Returns the values which participants attached to their yes votes in this epoch.
+    fn vote_values(context: &CoordinatorTwoPhaseCommitContext<P, V, TS::Time>) -> Vec<(P, V)> {
+        context
+            .participants()
+            .iter()
+            .filter_map(|participant| {
+                participant
+                    .vote_value
+                    .clone()
+                    .map(|value| (participant.process.clone(), value))
+            })
+            .collect()
+    }
+
+    // Returns whether each item of the current batch is accepted by every participant. A
+    // participant which has not voted on each item, such as one which has not voted yet or which
+    // sent a plain VoteResponse, accepts every item.
+    fn accepted_items(context: &CoordinatorTwoPhaseCommitContext<P, V, TS::Time>) -> Vec<bool> {
+        (0..context.batch().len())
+            .map(|index| {
+                context.participants().iter().all(|participant| {
+                    participant
+                        .batch_votes
+                        .as_ref()
+                        .is_none_or(|votes| votes[index])
+                })
+            })
+            .collect()
+    }
+
+    // Record a participant's vote and possibly decide commit or abort. For a batch epoch,
+    // batch_votes holds the participant's vote on each item, if it voted on each item.
+    fn handle_vote_response(
+        &self,
+        process: P,
+        epoch: Epoch,
+        participant_vote: ParticipantVote<V>,
+        mut context: CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+    ) -> Result<Vec<CoordinatorAction<P, V, TS::Time>>, AlgorithmError> {
+        let ParticipantVote {
+            vote,
+            read_only,
+            value: vote_value,
+            batch_votes,
+        } = participant_vote;
+
+        // Pull these out of context and copy/clone them because we borrow context to get
+        // a mut participant prior to using these values for additional checks.
+        let context_epoch = *context.epoch();
+        let context_state = context.state().clone();
+        let batch_len = context.batch().len();
+        let decision = self.decision_message(&context, epoch);
+
+        let participant = match context
+            .participants_mut()
+            .iter_mut()
+            .find(|participant| participant.process == process)
+        {
+            Some(inner) => inner,
+            None => {
+                return Ok(vec![CoordinatorAction::Notify(
+                    CoordinatorActionNotification::MessageDropped(
+                        "sender process is not a participant".into(),
+                    ),
+                )]);
+            }
+        };
+
+        // Since we abort as soon as any participant votes no, a vote may arrive after the epoch
+        // has already been aborted. A participant which voted yes is waiting for the decision,
+        // so send it Abort now rather than leaving it to time out and start the termination
+        // protocol. A late no vote needs no response, as that participant has already aborted.
+        if let Some(TwoPhaseCommitMessage::Abort(..)) = decision {
+            if !vote || read_only || participant.vote.is_some() {
+                return Ok(vec![CoordinatorAction::Notify(
+                    CoordinatorActionNotification::MessageDropped(
+                        "epoch has already been aborted".into(),
+                    ),
+                )]);
+            }
+
+            let mut actions = Vec::new();
+
+            // If we are still waiting for decision acks for this epoch, record the vote so the
+            // participant is also expected to acknowledge the abort.
+            if let CoordinatorState::WaitingForDecisionAck { ack_timeout_start } = context_state {
+                if context_epoch == epoch {
+                    participant.vote = Some(vote);
+                    actions.push(CoordinatorAction::Update {
+                        context: context.clone(),
+                        alarm: Some(ack_timeout_start + self.config.ack_timeout),
+                    });
+                }
+            }
+
+            actions.push(CoordinatorAction::SendMessage(
+                process,
+                context.abort_message(epoch),
+            ));
+
+            return Ok(actions);
+        }
+
+        // Ignore the message if the vote's epoch doesn't match our context epoch; this could
+        // happen under normal operation if a vote was processed after a timeout, and is therefore
+        // not an error.
+        if context_epoch != epoch {
+            return Ok(vec![CoordinatorAction::Notify(
+                CoordinatorActionNotification::MessageDropped(
+                    "epoch is not the current epoch".into(),
+                ),
+            )]);
+        }
+
+        // Ignore the message if we are not in the voting window. This could occur if we've move
+        // on to waiting for decision acks. After that, this is unlikely to occur because we will
+        // have advanced the epoch and the epoch is checked above; however, this could occur if
+        // not all Update actions were run successfully.
+        let vote_timeout_start = match context_state {
+            CoordinatorState::Voting { vote_timeout_start } => vote_timeout_start,
+            _ => {
+                return Ok(vec![CoordinatorAction::Notify(
+                    CoordinatorActionNotification::MessageDropped(
+                        "context state is not Voting".into(),
+                    ),
+                )]);
+            }
+        };
+
+        // Ignore if this participant already voted. This should not occur in normal operation.
+        if participant.vote.is_some() {
+            return Ok(vec![CoordinatorAction::Notify(
+                CoordinatorActionNotification::MessageDropped(
+                    "participant has already voted".into(),
+                ),
+            )]);
+        }
+
+        // Ignore the message if the votes do not match the items of the batch. This should not
+        // occur in normal operation.
+        if matches!(&batch_votes, Some(votes) if votes.len() != batch_len) {
+            return Ok(vec![CoordinatorAction::Notify(
+                CoordinatorActionNotification::MessageDropped(
+                    "batch votes do not match the items of the epoch".into(),
+                ),
+            )]);
+        }
+
+        let mut actions = Vec::new();
+
+        // Update the context to record the participant's vote, keeping the vote timeout alarm.
+        participant.vote = Some(vote);
+        participant.read_only = read_only;
+        participant.vote_value = vote_value;
+        participant.batch_votes = batch_votes;
+        actions.push(CoordinatorAction::Update {
+            context: context.clone(),
+            alarm: Some(vote_timeout_start + self.config.vote_timeout),
+        });
+
+        if !vote || (batch_len > 0 && !Self::accepted_items(&context).contains(&true)) {
+            // A single NO vote decides the epoch, as does a batch in which no item can still be
+            // accepted by every participant, so abort without waiting for the remaining votes.
+            // Use a function to fill in the abort since abort can occur in other situations as
+            // well.
+            self.push_abort_actions(context, &mut actions)
+        } else if self.config.decentralized
+            && context.participants().iter().all(|p| p.vote.is_some())
+        {
+            // In decentralized mode, starting the epoch was the coordinator's yes vote, and the
+            // participants commit on their own once they have all votes.
+            self.push_commit_actions(context, &mut actions, vec![])
+        } else if context.participants().iter().all(|p| p.vote.is_some()) {
+            // All participants voted yes, so we provide one last opportunity for the coordinator
+            // to vote no by waiting for the coordinators vote.
+            //
+            // A timeout will occur after the configured coordinator vote timeout, starting now, so
+            // that participants which voted yes are not left waiting on a stalled coordinator.
+            let local_vote_timeout_start = self.time_source.now();
+            context.set_state(CoordinatorState::WaitingForVote {
+                local_vote_timeout_start,
+            });
+            actions.push(CoordinatorAction::Update {
+                context: context.clone(),
+                alarm: Some(local_vote_timeout_start + self.config.coordinator_vote_timeout),
+            });
+
+            // Notify that we are requesting a coordinator vote.
+            actions.push(CoordinatorAction::Notify(Self::request_for_vote(&context)));
+        }
+
+        Ok(actions)
+    }
+
+    // Start voting on the given values, one for each participant. If the same value was given to
+    // every participant, it is also passed as the value of the epoch. If a batch is given instead,
+    // every participant is sent the whole batch and votes on each item.
+    //
+    // Steps:
+    //   - Send VoteRequest or BatchVoteRequest to all participants
+    //   - Update the state to Voting
+    //   - Set a timeout alarm for the maximum time to wait for votes
+    fn start(
+        &self,
+        mut context: CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+        value: Option<V>,
+        values: Vec<(P, V)>,
+        batch: Vec<V>,
+    ) -> Result<Vec<CoordinatorAction<P, V, TS::Time>>, AlgorithmError> {
+        // A Start event is only valid in response to a RequestForStart notification.
+        // Otherwise, it would restart voting in the current epoch and discard the value which is
+        // being decided, which indicates a programming error by the caller of the algorithm.
+        if !matches!(context.state(), CoordinatorState::WaitingForStart) {
+            return Err(AlgorithmError::InvalidState(
+                InvalidStateError::with_message(
+                    "Start event when not in WaitingForStart state".into(),
+                ),
+            ));
+        }
+
+        let mut actions = Vec::new();
+
+        // Create a VoteRequest message for all participants. Each VoteRequest includes the
+        // participants and observers of the epoch, so that every participant agrees on its peers
+        // for the termination protocol, even after a Reconfigure event.
+        let participants = context
+            .participants()
+            .iter()
+            .map(|participant| participant.process.clone())
+            .collect::<Vec<_>>();
+        let vote_requests = if batch.is_empty() {
+            values
+                .into_iter()
+                .map(|(process, value)| {
+                    CoordinatorAction::SendMessage(
+                        process,
+                        TwoPhaseCommitMessage::VoteRequest(
+                            *context.term(),
+                            *context.epoch(),
+                            value,
+                            participants.clone(),
+                            context.observers().clone(),
+                        ),
+                    )
+                })
+                .collect::<Vec<_>>()
+        } else {
+            participants
+                .iter()
+                .map(|process| {
+                    CoordinatorAction::SendMessage(
+                        process.clone(),
+                        TwoPhaseCommitMessage::BatchVoteRequest(
+                            *context.term(),
+                            *context.epoch(),
+                            batch.clone(),
+                            participants.clone(),
+                            context.observers().clone(),
+                        ),
+                    )
+                })
+                .collect::<Vec<_>>()
+        };
+
+        // A timeout will occur after the configured vote timeout, starting now. An alarm is set
+        // for the end of the timeout and the timeout is processed when an
+        // `CoordinatorEvent::Alarm` is received.
+        let vote_timeout_start = self.time_source.now();
+        let vote_timeout_end = vote_timeout_start + self.config.vote_timeout;
+
+        // Add an action to update the state to Voting and set the timeout alarm.
+        context.set_value(value);
+        context.set_batch(batch);
+        context.set_state(CoordinatorState::Voting { vote_timeout_start });
+        let update = CoordinatorAction::Update {
+            context,
+            alarm: Some(vote_timeout_end),
+        };
+
+        // With presumed commit, the participants of the epoch must be recorded before any
+        // VoteRequest is sent. Otherwise, after a failure, the coordinator would have no record of
+        // the epoch and would presume commit while participants are voting.
+        if self.config.presumption == Presumption::Commit {
+            actions.push(update);
+            actions.extend(vote_requests);
+        } else {
+            actions.extend(vote_requests);
+            actions.push(update);
+        }
+
+        Ok(actions)
+    }
+
+    // Create actions for switching into WaitingForNewTermAck state. A NewTerm message is sent to
+    // every participant, which answers with what it knows of its current epoch, and to every
+    // observer, which does not answer.
+    fn push_new_term_actions(
+        &self,
+        context: &mut CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<CoordinatorAction<P, V, TS::Time>>,
+    ) {
+        let ack_timeout_start = self.time_source.now();
+        context
+            .participants_mut()
+            .iter_mut()
+            .for_each(|participant| participant.new_term_ack = None);
+        context.set_state(CoordinatorState::WaitingForNewTermAck { ack_timeout_start });
+        actions.push(CoordinatorAction::Update {
+            context: context.clone(),
+            alarm: Some(ack_timeout_start + self.config.ack_timeout),
+        });
+
+        for process in context
+            .participants()
+            .iter()
+            .map(|participant| &participant.process)
+            .chain(context.observers())
+        {
+            actions.push(CoordinatorAction::SendMessage(
+                process.clone(),
+                TwoPhaseCommitMessage::NewTerm(*context.term()),
+            ));
+        }
+    }
+
+    // Returns the epoch which was in flight when the previous coordinator failed, along with its
+    // decision if it can be determined from the NewTermAck messages of all participants.
+    //
+    // A decision is never chosen here; it is only recovered. If any participant committed the
+    // epoch, it was committed. If any participant aborted the epoch or never received its
+    // VoteRequest, it can not have been committed. Otherwise, every participant voted yes and only
+    // the previous coordinator may know the decision.
+    fn new_term_decision(
+        context: &CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+    ) -> (Epoch, Option<Decision>) {
+        let acks = context
+            .participants()
+            .iter()
+            .filter_map(|participant| participant.new_term_ack)
+            .collect::<Vec<_>>();
+
+        let epoch = acks
+            .iter()
+            .map(|(epoch, _)| *epoch)
+            .fold(*context.epoch(), Epoch::max);
+
+        // If this process was the previous coordinator, it may know the decision itself.
+        if let Some(decision) = context.decision(epoch) {
+            return (epoch, Some(decision));
+        }
+        if *context.last_commit_epoch() == Some(epoch) {
+            return (epoch, Some(Decision::Commit));
+        }
+
+        if acks.contains(&(epoch, Some(Decision::Commit))) {
+            (epoch, Some(Decision::Commit))
+        } else if acks
+            .iter()
+            .any(|(ack_epoch, decision)| *ack_epoch < epoch || *decision == Some(Decision::Abort))
+        {
+            (epoch, Some(Decision::Abort))
+        } else {
+            (epoch, None)
+        }
+    }
+
+    // Create actions for finishing the epoch which was in flight when the previous coordinator
+    // failed. The decision is sent to the participants which did not know it, and then the new
+    // coordinator advances to the next epoch.
+    fn push_new_term_decided_actions(
+        &self,
+        mut context: CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<CoordinatorAction<P, V, TS::Time>>,
+        epoch: Epoch,
+        decision: Decision,
+    ) {
+        context.set_epoch(epoch);
+        context.record_decision(epoch, decision, self.config.decision_history_size);
+        match decision {
+            Decision::Commit => {
+                context.set_last_commit_epoch(Some(epoch));
+                context.set_state(CoordinatorState::Commit);
+            }
+            Decision::Abort => context.set_state(CoordinatorState::Abort),
+        }
+        actions.push(CoordinatorAction::Update {
+            context: context.clone(),
+            alarm: None,
+        });
+
+        for participant in context
+            .participants()
+            .iter()
+            .filter(|p| p.new_term_ack == Some((epoch, None)))
+        {
+            let message = match decision {
+                Decision::Commit => context.commit_message(epoch),
+                Decision::Abort => context.abort_message(epoch),
+            };
+            actions.push(CoordinatorAction::SendMessage(
+                participant.process.clone(),
+                message,
+            ));
+        }
+
+        self.push_outcome_actions(&context, actions, decision);
+
+        let value = context.take_value();
+        let notification = match decision {
+            Decision::Commit => CoordinatorActionNotification::Commit(epoch, value),
+            Decision::Abort => CoordinatorActionNotification::Abort(epoch, value),
+        };
+        actions.push(CoordinatorAction::Notify(notification));
+
+        self.push_advance_epoch_actions(&mut context, actions);
+    }
+
     // Create actions for advancing to the next epoch. This set of actions is generated whenever
     // a decision has been reached, either abort or commit.
     fn push_advance_epoch_actions(
         &self,
-        context: &mut TwoPhaseCommitContext<P, TS::Time, CoordinatorContext<P, TS::Time>>,
+        context: &mut CoordinatorTwoPhaseCommitContext<P, V, TS::Time>,
         actions: &mut Vec<CoordinatorAction<P, V, TS::Time>>,
     ) {
-        // Update the epoch and set the state to WaitingForStart. Also update the last commit epoch
-        // used to answer DecisionRequest messages.
-        if *context.state() == CoordinatorState::Commit {
-            context.set_last_commit_epoch(Some(*context.epoch()));
-        }
+        // Update the epoch and set the state to WaitingForStart.
         context.set_epoch(context.epoch() + 1);
         context.set_state(CoordinatorState::WaitingForStart);
         context
@@ -139,7 +890,11 @@ where
             .iter_mut()
             .for_each(|participant| {
                 participant.vote = None;
+                participant.read_only = false;
                 participant.decision_ack = false;
+                participant.vote_value = None;
+                participant.batch_votes = None;
+                participant.new_term_ack = None;
             });
         actions.push(CoordinatorAction::Update {
             context: context.clone(),
@@ -148,7 +903,7 @@ where
 
         // Notify that we need a new start value.
         actions.push(CoordinatorAction::Notify(
-            CoordinatorActionNotification::RequestForStart(),
+            CoordinatorActionNotification::RequestForStart(*context.epoch()),
         ));
     }
 }
@@ -161,7 +916,7 @@ where
 {
     type Event = CoordinatorEvent<P, V>;
     type Action = CoordinatorAction<P, V, TS::Time>;
-    type Context = TwoPhaseCommitContext<P, TS::Time, CoordinatorContext<P, TS::Time>>;
+    type Context = CoordinatorTwoPhaseCommitContext<P, V, TS::Time>;
 
     fn event(
         &self,
@@ -170,37 +925,165 @@ where
     ) -> Result<Vec<Self::Action>, AlgorithmError> {
         match event {
             // In response to a RequestForStart notification, a Start event provides the next value
-            // that should be considered.
-            //
-            // Steps:
-            //   - Send VoteRequest to all participants
-            //   - Update the state to Voting
-            //   - Set a timeout alarm for the maximum time to wait for votes
+            // that should be considered. The same value is sent to every participant.
             CoordinatorEvent::Start(value) => {
+                let values = context
+                    .participants()
+                    .iter()
+                    .map(|participant| (participant.process.clone(), value.clone()))
+                    .collect();
+
+                self.start(context, Some(value), values, vec![])
+            }
+
+            // In response to a RequestForStart notification, a StartWithValues event provides the
+            // next value for each participant, such as the part of a transaction which concerns
+            // that participant.
+            CoordinatorEvent::StartWithValues(values) => {
+                // Every participant must be given exactly one value, in the order of the
+                // participants, which indicates a programming error by the caller of the algorithm
+                // otherwise. As processes can only be compared for equality, the order lets this
+                // be checked in a single pass.
+                if values.len() != context.participants().len()
+                    || context
+                        .participants()
+                        .iter()
+                        .zip(&values)
+                        .any(|(participant, (process, _))| participant.process != *process)
+                {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "StartWithValues event must have one value for each participant, in \
+                            the order of the participants"
+                                .into(),
+                        ),
+                    ));
+                }
+
+                self.start(context, None, values, vec![])
+            }
+
+            // In response to a RequestForStart notification, a StartBatch event provides a batch
+            // of values which are decided together in the next epoch. Every participant votes on
+            // each item, and only the items accepted by every participant are committed.
+            CoordinatorEvent::StartBatch(batch) => {
+                if batch.is_empty() {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "StartBatch event must have at least one value".into(),
+                        ),
+                    ));
+                }
+
+                // In decentralized mode, participants decide on their own, and with presumed
+                // commit, a forgotten commit would be presumed without its committed items.
+                if self.config.decentralized || self.config.presumption == Presumption::Commit {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "StartBatch event can not be handled in decentralized mode or with \
+                            presumed commit"
+                                .into(),
+                        ),
+                    ));
+                }
+
+                self.start(context, None, vec![], batch)
+            }
+
+            // A Cancel event aborts the epoch in flight, such as for a runaway epoch or during
+            // maintenance, rather than waiting for the vote timeout. It is only accepted before
+            // the epoch has been decided.
+            CoordinatorEvent::Cancel() => {
+                if !matches!(
+                    context.state(),
+                    CoordinatorState::Voting { .. } | CoordinatorState::WaitingForVote { .. }
+                ) {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "Cancel event when not in Voting or WaitingForVote state".into(),
+                        ),
+                    ));
+                }
+
+                // In decentralized mode, a participant may commit as soon as it has all votes,
+                // so the coordinator can not abort on its own.
+                if self.config.decentralized {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "Cancel event can not be handled in decentralized mode".into(),
+                        ),
+                    ));
+                }
+
                 let mut actions = Vec::new();
+                self.push_abort_actions(context, &mut actions);
+                Ok(actions)
+            }
 
-                // Send a VoteRequest message to all participants
-                for participant in context.participants() {
-                    actions.push(CoordinatorAction::SendMessage(
-                        participant.process.clone(),
-                        TwoPhaseCommitMessage::VoteRequest(*context.epoch(), value.clone()),
-                    ))
+            // A Reconfigure event replaces the participants. It is only accepted between epochs,
+            // and the new participants take part starting with the next epoch.
+            CoordinatorEvent::Reconfigure(processes) => {
+                // Changing the participants during an epoch would change the set of votes needed
+                // to decide, which indicates a programming error by the caller of the algorithm.
+                if !matches!(context.state(), CoordinatorState::WaitingForStart) {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "Reconfigure event when not in WaitingForStart state".into(),
+                        ),
+                    ));
                 }
 
-                // A timeout will occur after VOTE_TIMEOUT_SECONDS, starting now. An alarm is set
-                // for the end of the timeout and the timeout is processed when an
-                // `CoordinatorEvent::Alarm` is received.
-                let vote_timeout_start = self.time_source.now();
-                let vote_timeout_end =
-                    vote_timeout_start + Duration::from_secs(VOTE_TIMEOUT_SECONDS);
+                if processes.is_empty() {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "Reconfigure event must have at least one participant".into(),
+                        ),
+                    ));
+                }
 
-                // Add an action to update the state to Voting and set the timeout alarm.
-                context.set_state(CoordinatorState::Voting { vote_timeout_start });
-                actions.push(CoordinatorAction::Update {
+                if processes.iter().enumerate().any(|(i, process)| {
+                    *process == *context.this_process() || processes[..i].contains(process)
+                }) {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "Reconfigure event must not contain duplicate participants or the \
+                            coordinator"
+                                .into(),
+                        ),
+                    ));
+                }
+
+                *context.participants_mut() = processes.into_iter().map(Participant::new).collect();
+
+                Ok(vec![CoordinatorAction::Update {
                     context,
-                    alarm: Some(vote_timeout_end),
-                });
+                    alarm: None,
+                }])
+            }
+
+            // A TakeOver event makes this process the coordinator for a new term, replacing
+            // a coordinator which has failed. Choosing the new coordinator and its term is left to
+            // the caller of the algorithm; the term must be greater than any previous term.
+            //
+            // Before starting any new epoch, the new coordinator must finish the epoch which was in
+            // flight, so it first asks every participant what it knows of that epoch.
+            CoordinatorEvent::TakeOver(term) => {
+                if term <= *context.term() {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(format!(
+                            "TakeOver event term {} must be greater than the current term {}",
+                            term,
+                            context.term()
+                        )),
+                    ));
+                }
+
+                context.set_term(term);
+                let this_process = context.this_process().clone();
+                context.set_coordinator(this_process);
 
+                let mut actions = Vec::new();
+                self.push_new_term_actions(&mut context, &mut actions);
                 Ok(actions)
             }
 
@@ -209,7 +1092,7 @@ where
             CoordinatorEvent::Vote(vote) => {
                 // If we receive a Vote event when not in WaitingForVote, it indicates
                 // a programming error by the caller of the algorithm.
-                if !matches!(context.state(), CoordinatorState::WaitingForVote) {
+                if !matches!(context.state(), CoordinatorState::WaitingForVote { .. }) {
                     return Err(AlgorithmError::InvalidState(
                         InvalidStateError::with_message(
                             "Vote event when not in WaitingForVote state".into(),
@@ -221,28 +1104,46 @@ where
 
                 // If vote is true, then we decide to commit; if vote is false, we decide to abort.
                 if vote {
-                    // Add an action to update the state to commit and unset the alarm.
-                    context.set_state(CoordinatorState::Commit);
-                    actions.push(CoordinatorAction::Update {
-                        context: context.clone(),
-                        alarm: None,
-                    });
+                    let committed_items = Self::accepted_items(&context);
+                    self.push_commit_actions(context, &mut actions, committed_items);
+                } else {
+                    self.push_abort_actions(context, &mut actions);
+                }
 
-                    // Send `Commit` to all participants.
-                    for participant in context.participants() {
-                        actions.push(CoordinatorAction::SendMessage(
-                            participant.process.clone(),
-                            TwoPhaseCommitMessage::Commit(*context.epoch()),
-                        ))
-                    }
+                Ok(actions)
+            }
 
-                    // Notify that we've committed.
-                    actions.push(CoordinatorAction::Notify(
-                        CoordinatorActionNotification::Commit(),
+            // In response to a RequestForBatchVote notification, a BatchVote event provides the
+            // coordinator's vote on each item of the batch. The items accepted by every
+            // participant and by the coordinator are committed; if there are none, the epoch is
+            // aborted.
+            CoordinatorEvent::BatchVote(votes) => {
+                if !matches!(context.state(), CoordinatorState::WaitingForVote { .. }) {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "BatchVote event when not in WaitingForVote state".into(),
+                        ),
                     ));
+                }
 
-                    // Wait for a decision ack.
-                    self.push_wait_for_decision_ack(&mut context, &mut actions);
+                if votes.len() != context.batch().len() {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "BatchVote event must have one vote for each item of the batch".into(),
+                        ),
+                    ));
+                }
+
+                let committed_items = Self::accepted_items(&context)
+                    .into_iter()
+                    .zip(votes)
+                    .map(|(accepted, vote)| accepted && vote)
+                    .collect::<Vec<_>>();
+
+                let mut actions = Vec::new();
+
+                if committed_items.contains(&true) {
+                    self.push_commit_actions(context, &mut actions, committed_items);
                 } else {
                     self.push_abort_actions(context, &mut actions);
                 }
@@ -255,19 +1156,23 @@ where
             // state.
             CoordinatorEvent::Alarm() => match context.state() {
                 // A vote timeout has occurred, which means we have not received votes within
-                // VOTE_TIMEOUT_SECONDS.
+                // the configured vote timeout.
                 CoordinatorState::Voting { vote_timeout_start } => {
                     let mut actions = Vec::new();
 
                     // Validate that the timeout has occurred. If this is false, we shouldn't have
                     // been woken up with an alarm; however, we can just ignore it and wait for the
                     // alarm to be triggered again later.
-                    if self.time_source.now()
-                        > *vote_timeout_start + Duration::from_secs(VOTE_TIMEOUT_SECONDS)
-                    {
-                        // Decide to abort. Use a function to fill in the abort actions since abort
-                        // can occur in other situations as well.
-                        self.push_abort_actions(context, &mut actions);
+                    if self.time_source.now() > *vote_timeout_start + self.config.vote_timeout {
+                        if self.config.decentralized {
+                            // A participant may have committed with all votes, so ask for the
+                            // missing votes instead of aborting.
+                            self.push_vote_decision_request_actions(context, &mut actions);
+                        } else {
+                            // Decide to abort. Use a function to fill in the abort actions since
+                            // abort can occur in other situations as well.
+                            self.push_abort_actions(context, &mut actions);
+                        }
                     }
 
                     Ok(actions)
@@ -281,24 +1186,41 @@ where
                 // in other circumstances, it indicates a bug (possibly in how the caller is using
                 // the algorithm), but we process it anyway in hopes of recovery.
                 CoordinatorState::WaitingForStart => Ok(vec![
-                    CoordinatorAction::Notify(CoordinatorActionNotification::RequestForStart()),
+                    CoordinatorAction::Notify(CoordinatorActionNotification::RequestForStart(
+                        *context.epoch(),
+                    )),
                     CoordinatorAction::Update {
                         context,
                         alarm: None,
                     },
                 ]),
 
-                // If we receive an alarm an the RequestforStart state, then we re-generate
-                // a RequestForVote notification. Since this is unexpected, it indicates a bug
-                // (possibly in how the caller is using the algorithm), but we process it anyway in
-                // hopes of recovery.
-                CoordinatorState::WaitingForVote => Ok(vec![
-                    CoordinatorAction::Notify(CoordinatorActionNotification::RequestForVote()),
-                    CoordinatorAction::Update {
-                        context,
-                        alarm: None,
-                    },
-                ]),
+                // A coordinator vote timeout has occurred, which means we have not received the
+                // coordinator's vote within the configured timeout.
+                CoordinatorState::WaitingForVote {
+                    local_vote_timeout_start,
+                } => {
+                    let mut actions = Vec::new();
+                    let local_vote_timeout_end =
+                        *local_vote_timeout_start + self.config.coordinator_vote_timeout;
+
+                    if self.time_source.now() > local_vote_timeout_end {
+                        // Decide to abort. Use a function to fill in the abort actions since abort
+                        // can occur in other situations as well.
+                        self.push_abort_actions(context, &mut actions);
+                    } else {
+                        // If the alarm is early, we re-generate a RequestForVote notification.
+                        // Since this is unexpected, it indicates a bug (possibly in how the caller
+                        // is using the algorithm), but we process it anyway in hopes of recovery.
+                        actions.push(CoordinatorAction::Notify(Self::request_for_vote(&context)));
+                        actions.push(CoordinatorAction::Update {
+                            context,
+                            alarm: Some(local_vote_timeout_end),
+                        });
+                    }
+
+                    Ok(actions)
+                }
 
                 // A decision ack timeout has occurred, which means we have not received all
                 // decision acks within the allowed timeout period.
@@ -314,17 +1236,45 @@ where
                 // not responded with a decision ack either processed the commit/abort or will
                 // timeout and start the recovery protocol. In either case, the correct behavior
                 // for the coordinator is to continue with the next epoch.
+                //
+                // The exception is a decision which must not be forgotten until it has been
+                // acknowledged by every participant. In that case, the decision is sent again to
+                // the participants which have not acked and the timeout is restarted.
                 CoordinatorState::WaitingForDecisionAck { ack_timeout_start } => {
                     let mut actions = Vec::new();
 
                     // Validate that the timeout has occurred. If this is false, we shouldn't have
                     // been woken up with an alarm; however, we can just ignore it and wait for the
                     // alarm to be triggered again later.
-                    if self.time_source.now()
-                        > *ack_timeout_start + Duration::from_secs(ACK_TIMEOUT_SECONDS)
-                    {
-                        // Move to the next epoch. This will unset the alarm.
-                        self.push_advance_epoch_actions(&mut context, &mut actions);
+                    if self.time_source.now() > *ack_timeout_start + self.config.ack_timeout {
+                        if self.decision_requires_all_acks(&context) {
+                            self.push_resend_decision_actions(&context, &mut actions);
+                            self.push_wait_for_decision_ack(&mut context, &mut actions);
+                        } else {
+                            // Move to the next epoch. This will unset the alarm.
+                            self.push_advance_epoch_actions(&mut context, &mut actions);
+                        }
+                    }
+
+                    Ok(actions)
+                }
+
+                // A new term ack timeout has occurred, which means we have not received all
+                // NewTermAck messages, or that the in-flight epoch could not yet be decided. Send
+                // NewTerm again to every participant.
+                //
+                // If every participant voted yes but none of them knows the decision, only the
+                // previous coordinator knows it. This is the blocking case of two-phase commit;
+                // the new coordinator keeps asking until a participant learns the decision, or
+                // until the previous coordinator is restarted and takes over with a new term.
+                CoordinatorState::WaitingForNewTermAck { ack_timeout_start } => {
+                    let mut actions = Vec::new();
+
+                    // Validate that the timeout has occurred. If this is false, we shouldn't have
+                    // been woken up with an alarm; however, we can just ignore it and wait for the
+                    // alarm to be triggered again later.
+                    if self.time_source.now() > *ack_timeout_start + self.config.ack_timeout {
+                        self.push_new_term_actions(&mut context, &mut actions);
                     }
 
                     Ok(actions)
@@ -347,147 +1297,197 @@ where
                 }
             },
 
-            // A participant has sent response to our request for a vote, record it and possibly
-            // decide commit or abort.
-            CoordinatorEvent::Deliver(process, CoordinatorMessage::VoteResponse(epoch, vote)) => {
-                // Pull these out of context and copy/clone them because we borrow context to get
-                // a mut participant prior to using these values for additional checks.
-                let context_epoch = *context.epoch();
-                let context_state = context.state().clone();
+            // A Recover event is sent once after the coordinator has been restarted from its
+            // persisted context. Any actions which followed the last persisted Update may not have
+            // been run, so they are generated again for the persisted state.
+            CoordinatorEvent::Recover() => {
+                let mut actions = Vec::new();
 
-                let participant = match context
-                    .participants_mut()
-                    .iter_mut()
-                    .find(|participant| participant.process == process)
-                {
-                    Some(inner) => inner,
-                    None => {
-                        return Ok(vec![CoordinatorAction::Notify(
-                            CoordinatorActionNotification::MessageDropped(
-                                "sender process is not a participant".into(),
-                            ),
-                        )]);
+                match context.state().clone() {
+                    // Request the next value again, as was done for an alarm in this state.
+                    CoordinatorState::WaitingForStart => {
+                        actions.push(CoordinatorAction::Notify(
+                            CoordinatorActionNotification::RequestForStart(*context.epoch()),
+                        ));
+                        actions.push(CoordinatorAction::Update {
+                            context,
+                            alarm: None,
+                        });
                     }
-                };
 
-                // Ignore the message if the vote's epoch doesn't match our context epoch; this
-                // could happen under normal operation if a vote was processed after a timeout, and
-                // is therefore not an error.
-                if context_epoch != epoch {
-                    return Ok(vec![CoordinatorAction::Notify(
-                        CoordinatorActionNotification::MessageDropped(
-                            "epoch is not the current epoch".into(),
-                        ),
-                    )]);
-                }
+                    // Votes received after the last persisted Update have been lost, and the
+                    // participants which sent them will not vote again. Rather than wait for the
+                    // vote timeout, decide to abort. In decentralized mode, a participant may
+                    // have committed, so ask for the missing votes instead.
+                    CoordinatorState::Voting { .. } => {
+                        if self.config.decentralized {
+                            self.push_vote_decision_request_actions(context, &mut actions);
+                        } else {
+                            self.push_abort_actions(context, &mut actions);
+                        }
+                    }
 
-                // Ignore the message if we are not in the voting window. This could occur if we've
-                // move on to waiting for decision acks. After that, this is unlikely to occur
-                // because we will have advanced the epoch and the epoch is checked above; however,
-                // this could occur if not all Update actions were run successfully.
-                if !matches!(
-                    context_state,
-                    CoordinatorState::Voting {
-                        vote_timeout_start: _,
+                    // Abort if the coordinator vote timeout has expired while we were down;
+                    // otherwise, request our vote again and re-arm the alarm for the remaining
+                    // time.
+                    CoordinatorState::WaitingForVote {
+                        local_vote_timeout_start,
+                    } => {
+                        let local_vote_timeout_end =
+                            local_vote_timeout_start + self.config.coordinator_vote_timeout;
+
+                        if self.time_source.now() > local_vote_timeout_end {
+                            self.push_abort_actions(context, &mut actions);
+                        } else {
+                            actions
+                                .push(CoordinatorAction::Notify(Self::request_for_vote(&context)));
+                            actions.push(CoordinatorAction::Update {
+                                context,
+                                alarm: Some(local_vote_timeout_end),
+                            });
+                        }
                     }
-                ) {
-                    return Ok(vec![CoordinatorAction::Notify(
-                        CoordinatorActionNotification::MessageDropped(
-                            "context state is not Voting".into(),
-                        ),
-                    )]);
-                }
 
-                // Ignore if this participant already voted. This should not occur in normal
-                // operation.
-                if participant.vote.is_some() {
-                    return Ok(vec![CoordinatorAction::Notify(
-                        CoordinatorActionNotification::MessageDropped(
-                            "participant has already voted".into(),
-                        ),
-                    )]);
-                }
+                    // The decision was persisted, but it may not have been sent to the participants
+                    // or notified. Send it again to the participants which have not acked and to
+                    // the observers, notify it again, and then either wait for acks or advance to
+                    // the next epoch.
+                    CoordinatorState::Commit | CoordinatorState::Abort => {
+                        let committed = *context.last_commit_epoch() == Some(*context.epoch());
+                        self.push_resend_decision_actions(&context, &mut actions);
+                        self.push_outcome_actions(
+                            &context,
+                            &mut actions,
+                            if committed {
+                                Decision::Commit
+                            } else {
+                                Decision::Abort
+                            },
+                        );
 
-                let mut actions = Vec::new();
+                        Self::push_decision_notification_actions(
+                            &mut context,
+                            &mut actions,
+                            committed,
+                        );
 
-                // Update the context to record the participant's vote
-                participant.vote = Some(vote);
-                actions.push(CoordinatorAction::Update {
-                    context: context.clone(),
-                    alarm: None,
-                });
+                        self.push_decided_actions(context, &mut actions, committed);
+                    }
 
-                // If all the participants have voted, then either decide to abort or change state.
-                if context.participants().iter().all(|p| p.vote.is_some()) {
-                    if context.participants().iter().any(|p| p.vote == Some(false)) {
-                        // We got at least one NO vote, so decide to abort. Use a function to fill
-                        // in the abort since abort can occur in other situations as well.
-                        self.push_abort_actions(context, &mut actions)
-                    } else {
-                        // All participants voted yes, so we provide one last opportunity for the
-                        // coordinator to vote no by waiting for the coordinators vote.
-                        context.set_state(CoordinatorState::WaitingForVote);
+                    // Ask the participants again what they know of the in-flight epoch.
+                    CoordinatorState::WaitingForNewTermAck { .. } => {
+                        self.push_new_term_actions(&mut context, &mut actions);
+                    }
+
+                    // Send the decision again to the participants which have not acked, and re-arm
+                    // the alarm for the remainder of the ack timeout.
+                    CoordinatorState::WaitingForDecisionAck { ack_timeout_start } => {
+                        self.push_resend_decision_actions(&context, &mut actions);
                         actions.push(CoordinatorAction::Update {
                             context,
-                            alarm: None,
+                            alarm: Some(ack_timeout_start + self.config.ack_timeout),
                         });
-                        actions.push(CoordinatorAction::Notify(
-                            // Notify that we are requesting a coordinator vote.
-                            CoordinatorActionNotification::RequestForVote(),
-                        ));
                     }
                 }
 
                 Ok(actions)
             }
 
+            // A participant has sent response to our request for a vote, record it and possibly
+            // decide commit or abort.
+            CoordinatorEvent::Deliver(process, CoordinatorMessage::VoteResponse(epoch, vote)) => {
+                self.handle_vote_response(
+                    process,
+                    epoch,
+                    ParticipantVote {
+                        vote,
+                        read_only: false,
+                        value: None,
+                        batch_votes: None,
+                    },
+                    context,
+                )
+            }
+
+            // A participant has voted yes and attached a value to its vote, such as the result of
+            // preparing the value it was sent. The value is included in the coordinator's
+            // RequestForVote and Commit notifications.
+            CoordinatorEvent::Deliver(
+                process,
+                CoordinatorMessage::VoteResponseWithValue(epoch, value),
+            ) => self.handle_vote_response(
+                process,
+                epoch,
+                ParticipantVote {
+                    vote: true,
+                    read_only: false,
+                    value: Some(value),
+                    batch_votes: None,
+                },
+                context,
+            ),
+
+            // A read-only participant is recorded as having voted yes, but takes no further part
+            // in the epoch; it is not sent the decision and is not expected to acknowledge it.
+            CoordinatorEvent::Deliver(process, CoordinatorMessage::ReadOnlyVoteResponse(epoch)) => {
+                self.handle_vote_response(
+                    process,
+                    epoch,
+                    ParticipantVote {
+                        vote: true,
+                        read_only: true,
+                        value: None,
+                        batch_votes: None,
+                    },
+                    context,
+                )
+            }
+
+            // A participant has voted on each item of a batch. It is recorded as having voted yes
+            // if it accepted at least one item.
+            CoordinatorEvent::Deliver(
+                process,
+                CoordinatorMessage::BatchVoteResponse(epoch, votes),
+            ) => {
+                let participant_vote = ParticipantVote {
+                    vote: votes.contains(&true),
+                    read_only: false,
+                    value: None,
+                    batch_votes: Some(votes),
+                };
+                self.handle_vote_response(process, epoch, participant_vote, context)
+            }
+
             // A node which has timed out in its uncertainty period will send a `DecisionRequest`
             // as part of its termination protocol. If we have the information, answer this request
             // with a commit or abort message.
             CoordinatorEvent::Deliver(process, CoordinatorMessage::DecisionRequest(epoch)) => {
-                // The sender must be a participant.
-                if context
+                // A process which is not a participant may have been removed by a Reconfigure
+                // event after it voted, so it is answered if the epoch is in the decision history.
+                if !context
                     .participants()
                     .iter()
-                    .any(|participant| participant.process != process)
+                    .any(|participant| participant.process == process)
                 {
-                    return Ok(vec![CoordinatorAction::Notify(
-                        CoordinatorActionNotification::MessageDropped(
-                            "sender process is not a participant".into(),
+                    return Ok(vec![match context.decision(epoch) {
+                        Some(Decision::Commit) => {
+                            CoordinatorAction::SendMessage(process, context.commit_message(epoch))
+                        }
+                        Some(Decision::Abort) => {
+                            CoordinatorAction::SendMessage(process, context.abort_message(epoch))
+                        }
+                        None => CoordinatorAction::Notify(
+                            CoordinatorActionNotification::MessageDropped(
+                                "sender process is not a participant".into(),
+                            ),
                         ),
-                    )]);
+                    }]);
                 }
 
-                // We record the last commit epoch in the context; if the epoch requested was the
-                // last commit epoch, send a commit message to the requesting process.
-                if Some(epoch) == *context.last_commit_epoch() {
-                    return Ok(vec![CoordinatorAction::SendMessage(
-                        process,
-                        TwoPhaseCommitMessage::Commit(epoch),
-                    )]);
+                if let Some(message) = self.decision_message(&context, epoch) {
+                    return Ok(vec![CoordinatorAction::SendMessage(process, message)]);
                 }
 
-                // If the epoch is between the current epoch and the last commit epoch, we know
-                // that the decision must have been Abort. Thus, we send an Abort message.
-                if epoch < *context.epoch()
-                    && (Some(epoch) > *context.last_commit_epoch()
-                        || context.last_commit_epoch().is_none())
-                {
-                    return Ok(vec![CoordinatorAction::SendMessage(
-                        process,
-                        TwoPhaseCommitMessage::Abort(epoch),
-                    )]);
-                }
-
-                // A note on ignored messages:
-                //
-                // If the epoch is before the last commit epoch, we ignore the message as we know
-                // all processes decided in the last commit epoch and no process can be in an
-                // uncertainty period for an older epoch.
-                //
-                // If the epoch is after our current epoch, we ignore the message as we do not yet
-                // know what the future holds. Similarly, we do not yet have a decision for the
-                // current epoch or we would have advanced to the next epoch already.
                 Ok(vec![CoordinatorAction::Notify(
                     CoordinatorActionNotification::MessageDropped(format!(
                         "decision for requested epoch {} is unknown (current epoch: {}, \
@@ -535,18 +1535,18 @@ where
                 // occur in practice because we will have advanced the epoch and the epoch is
                 // checked above; however, this could occur if not all Update actions were run
                 // successfully.
-                if !matches!(
-                    context_state,
-                    CoordinatorState::WaitingForDecisionAck {
-                        ack_timeout_start: _,
+                let ack_timeout_start = match context_state {
+                    CoordinatorState::WaitingForDecisionAck { ack_timeout_start } => {
+                        ack_timeout_start
                     }
-                ) {
-                    return Ok(vec![CoordinatorAction::Notify(
-                        CoordinatorActionNotification::MessageDropped(
-                            "context state is not WaitingForDecisionAck".into(),
-                        ),
-                    )]);
-                }
+                    _ => {
+                        return Ok(vec![CoordinatorAction::Notify(
+                            CoordinatorActionNotification::MessageDropped(
+                                "context state is not WaitingForDecisionAck".into(),
+                            ),
+                        )]);
+                    }
+                };
 
                 // Ignore if this participant already acked. This should not occur in normal
                 // operation.
@@ -560,20 +1560,94 @@ where
 
                 let mut actions = Vec::new();
 
-                // Update the context to record the participant's ack
+                // Update the context to record the participant's ack. The ack timeout alarm is
+                // kept, as the remaining acks may still be lost.
                 participant.decision_ack = true;
                 actions.push(CoordinatorAction::Update {
                     context: context.clone(),
-                    alarm: None,
+                    alarm: Some(ack_timeout_start + self.config.ack_timeout),
                 });
 
-                // If all the participants have acked, then move to the next epoch.
-                if context.participants().iter().all(|p| p.decision_ack) {
+                // If all the participants which were sent the decision have acked, then move to the
+                // next epoch.
+                let committed = *context.last_commit_epoch() == Some(context_epoch);
+                if !context
+                    .participants()
+                    .iter()
+                    .any(|p| self.awaits_decision_ack(p, committed))
+                {
                     self.push_advance_epoch_actions(&mut context, &mut actions);
                 }
 
                 Ok(actions)
             }
+
+            // A participant has answered our NewTerm message with its current epoch and, if it
+            // knows it, the decision of that epoch.
+            CoordinatorEvent::Deliver(
+                process,
+                CoordinatorMessage::NewTermAck(term, epoch, decision),
+            ) => {
+                // Ignore acks for an earlier term; these are answers to another coordinator.
+                if term != *context.term() {
+                    return Ok(vec![CoordinatorAction::Notify(
+                        CoordinatorActionNotification::MessageDropped(format!(
+                            "term {} is not the current term {}",
+                            term,
+                            context.term()
+                        )),
+                    )]);
+                }
+
+                let ack_timeout_start = match context.state() {
+                    CoordinatorState::WaitingForNewTermAck { ack_timeout_start } => {
+                        *ack_timeout_start
+                    }
+                    _ => {
+                        return Ok(vec![CoordinatorAction::Notify(
+                            CoordinatorActionNotification::MessageDropped(
+                                "context state is not WaitingForNewTermAck".into(),
+                            ),
+                        )]);
+                    }
+                };
+
+                match context
+                    .participants_mut()
+                    .iter_mut()
+                    .find(|participant| participant.process == process)
+                {
+                    Some(participant) => participant.new_term_ack = Some((epoch, decision)),
+                    None => {
+                        return Ok(vec![CoordinatorAction::Notify(
+                            CoordinatorActionNotification::MessageDropped(
+                                "sender process is not a participant".into(),
+                            ),
+                        )]);
+                    }
+                }
+
+                let mut actions = Vec::new();
+
+                if context
+                    .participants()
+                    .iter()
+                    .all(|participant| participant.new_term_ack.is_some())
+                {
+                    if let (epoch, Some(decision)) = Self::new_term_decision(&context) {
+                        self.push_new_term_decided_actions(context, &mut actions, epoch, decision);
+                        return Ok(actions);
+                    }
+                }
+
+                // Record the ack, keeping the ack timeout alarm.
+                actions.push(CoordinatorAction::Update {
+                    context,
+                    alarm: Some(ack_timeout_start + self.config.ack_timeout),
+                });
+
+                Ok(actions)
+            }
         }
     }
 }
@@ -12,22 +12,34 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::algorithm::Value;
 use crate::process::Process;
 use crate::time::Time;
 
+use super::Decision;
+use super::Epoch;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct Participant<P> {
+pub struct Participant<P, V> {
     pub process: P,
     pub vote: Option<bool>,
+    pub read_only: bool,
     pub decision_ack: bool,
+    pub vote_value: Option<V>,
+    pub batch_votes: Option<Vec<bool>>,
+    pub new_term_ack: Option<(Epoch, Option<Decision>)>,
 }
 
-impl<P> Participant<P> {
-    pub fn new(process: P) -> Participant<P> {
+impl<P, V> Participant<P, V> {
+    pub fn new(process: P) -> Participant<P, V> {
         Participant {
             process,
             vote: None,
+            read_only: false,
             decision_ack: false,
+            vote_value: None,
+            batch_votes: None,
+            new_term_ack: None,
         }
     }
 }
@@ -41,16 +53,19 @@ where
     Commit,
     Voting { vote_timeout_start: T },
     WaitingForDecisionAck { ack_timeout_start: T },
+    WaitingForNewTermAck { ack_timeout_start: T },
     WaitingForStart,
-    WaitingForVote,
+    WaitingForVote { local_vote_timeout_start: T },
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct CoordinatorContext<P, T>
+pub struct CoordinatorContext<P, V, T>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
-    pub(super) participants: Vec<Participant<P>>,
+    pub(super) observers: Vec<P>,
+    pub(super) participants: Vec<Participant<P, V>>,
     pub(super) state: CoordinatorState<T>,
 }
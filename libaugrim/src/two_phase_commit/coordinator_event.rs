@@ -17,6 +17,7 @@ use crate::error::InvalidStateError;
 use crate::process::Process;
 
 use super::CoordinatorMessage;
+use super::Term;
 use super::TwoPhaseCommitEvent;
 
 pub enum CoordinatorEvent<P, V>
@@ -25,9 +26,16 @@ where
     V: Value,
 {
     Alarm(),
-    Deliver(P, CoordinatorMessage),
+    Recover(),
+    Deliver(P, CoordinatorMessage<V>),
     Start(V),
+    StartWithValues(Vec<(P, V)>),
+    StartBatch(Vec<V>),
+    Cancel(),
+    Reconfigure(Vec<P>),
+    TakeOver(Term),
     Vote(bool),
+    BatchVote(Vec<bool>),
 }
 
 impl<P, V> TryFrom<TwoPhaseCommitEvent<P, V>> for CoordinatorEvent<P, V>
@@ -40,9 +48,28 @@ where
     fn try_from(event: TwoPhaseCommitEvent<P, V>) -> Result<Self, Self::Error> {
         Ok(match event {
             TwoPhaseCommitEvent::Alarm() => CoordinatorEvent::Alarm(),
+            TwoPhaseCommitEvent::Recover() => CoordinatorEvent::Recover(),
             TwoPhaseCommitEvent::Deliver(p, m) => CoordinatorEvent::Deliver(p, m.try_into()?),
             TwoPhaseCommitEvent::Start(value) => CoordinatorEvent::Start(value),
+            TwoPhaseCommitEvent::StartWithValues(values) => {
+                CoordinatorEvent::StartWithValues(values)
+            }
+            TwoPhaseCommitEvent::StartBatch(values) => CoordinatorEvent::StartBatch(values),
+            TwoPhaseCommitEvent::Cancel() => CoordinatorEvent::Cancel(),
+            TwoPhaseCommitEvent::Reconfigure(processes) => CoordinatorEvent::Reconfigure(processes),
+            TwoPhaseCommitEvent::TakeOver(term) => CoordinatorEvent::TakeOver(term),
             TwoPhaseCommitEvent::Vote(vote) => CoordinatorEvent::Vote(vote),
+            TwoPhaseCommitEvent::BatchVote(votes) => CoordinatorEvent::BatchVote(votes),
+            TwoPhaseCommitEvent::VoteWithValue(_) => {
+                return Err(InvalidStateError::with_message(
+                    "VoteWithValue event can not be handled by a coordinator".into(),
+                ))
+            }
+            TwoPhaseCommitEvent::ReadOnlyVote() => {
+                return Err(InvalidStateError::with_message(
+                    "ReadOnlyVote event can not be handled by a coordinator".into(),
+                ))
+            }
         })
     }
 }
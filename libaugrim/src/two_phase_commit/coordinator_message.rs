@@ -18,8 +18,11 @@ use std::convert::TryFrom;
 
 use crate::algorithm::Value;
 use crate::error::InvalidStateError;
+use crate::process::Process;
 
+use super::Decision;
 use super::Epoch;
+use super::Term;
 use super::TwoPhaseCommitMessage;
 
 /// A message which is delivered to the coordinator.
@@ -27,53 +30,101 @@ use super::TwoPhaseCommitMessage;
 /// This is a subset of `TwoPhaseCommitMessage`, containing only the set of messages which can be
 /// delivered to a coordinator.
 #[derive(Clone)]
-pub enum CoordinatorMessage {
+pub enum CoordinatorMessage<V>
+where
+    V: Value,
+{
     VoteResponse(Epoch, bool),
+    VoteResponseWithValue(Epoch, V),
+    ReadOnlyVoteResponse(Epoch),
     DecisionRequest(Epoch),
     DecisionAck(Epoch),
+    NewTermAck(Term, Epoch, Option<Decision>),
+    BatchVoteResponse(Epoch, Vec<bool>),
 }
 
-impl<V> From<CoordinatorMessage> for TwoPhaseCommitMessage<V>
+impl<P, V> From<CoordinatorMessage<V>> for TwoPhaseCommitMessage<P, V>
 where
+    P: Process,
     V: Value,
 {
-    fn from(message: CoordinatorMessage) -> Self {
+    fn from(message: CoordinatorMessage<V>) -> Self {
         match message {
             CoordinatorMessage::VoteResponse(epoch, vote) => {
                 TwoPhaseCommitMessage::VoteResponse(epoch, vote)
             }
+            CoordinatorMessage::VoteResponseWithValue(epoch, value) => {
+                TwoPhaseCommitMessage::VoteResponseWithValue(epoch, value)
+            }
+            CoordinatorMessage::ReadOnlyVoteResponse(epoch) => {
+                TwoPhaseCommitMessage::ReadOnlyVoteResponse(epoch)
+            }
             CoordinatorMessage::DecisionRequest(epoch) => {
                 TwoPhaseCommitMessage::DecisionRequest(epoch)
             }
             CoordinatorMessage::DecisionAck(epoch) => TwoPhaseCommitMessage::DecisionAck(epoch),
+            CoordinatorMessage::NewTermAck(term, epoch, decision) => {
+                TwoPhaseCommitMessage::NewTermAck(term, epoch, decision)
+            }
+            CoordinatorMessage::BatchVoteResponse(epoch, votes) => {
+                TwoPhaseCommitMessage::BatchVoteResponse(epoch, votes)
+            }
         }
     }
 }
 
-impl<V> TryFrom<TwoPhaseCommitMessage<V>> for CoordinatorMessage
+impl<P, V> TryFrom<TwoPhaseCommitMessage<P, V>> for CoordinatorMessage<V>
 where
+    P: Process,
     V: Value,
 {
     type Error = InvalidStateError;
 
-    fn try_from(message: TwoPhaseCommitMessage<V>) -> Result<Self, Self::Error> {
+    fn try_from(message: TwoPhaseCommitMessage<P, V>) -> Result<Self, Self::Error> {
         match message {
             TwoPhaseCommitMessage::VoteResponse(epoch, vote) => {
                 Ok(CoordinatorMessage::VoteResponse(epoch, vote))
             }
+            TwoPhaseCommitMessage::VoteResponseWithValue(epoch, value) => {
+                Ok(CoordinatorMessage::VoteResponseWithValue(epoch, value))
+            }
+            TwoPhaseCommitMessage::ReadOnlyVoteResponse(epoch) => {
+                Ok(CoordinatorMessage::ReadOnlyVoteResponse(epoch))
+            }
             TwoPhaseCommitMessage::DecisionRequest(epoch) => {
                 Ok(CoordinatorMessage::DecisionRequest(epoch))
             }
             TwoPhaseCommitMessage::DecisionAck(epoch) => Ok(CoordinatorMessage::DecisionAck(epoch)),
-            TwoPhaseCommitMessage::VoteRequest(_, _) => Err(InvalidStateError::with_message(
+            TwoPhaseCommitMessage::NewTermAck(term, epoch, decision) => {
+                Ok(CoordinatorMessage::NewTermAck(term, epoch, decision))
+            }
+            TwoPhaseCommitMessage::BatchVoteResponse(epoch, votes) => {
+                Ok(CoordinatorMessage::BatchVoteResponse(epoch, votes))
+            }
+            TwoPhaseCommitMessage::VoteRequest(..) => Err(InvalidStateError::with_message(
                 "VoteRequest message cannot be handled by a coordinator".into(),
             )),
-            TwoPhaseCommitMessage::Commit(_) => Err(InvalidStateError::with_message(
+            TwoPhaseCommitMessage::Commit(..) => Err(InvalidStateError::with_message(
                 "Commit message cannot be handled by a coordinator".into(),
             )),
-            TwoPhaseCommitMessage::Abort(_) => Err(InvalidStateError::with_message(
+            TwoPhaseCommitMessage::Abort(..) => Err(InvalidStateError::with_message(
                 "Abort message cannot be handled by a coordinator".into(),
             )),
+            TwoPhaseCommitMessage::NewTerm(_) => Err(InvalidStateError::with_message(
+                "NewTerm message cannot be handled by a coordinator".into(),
+            )),
+            TwoPhaseCommitMessage::Outcome(_, _, _) => Err(InvalidStateError::with_message(
+                "Outcome message cannot be handled by a coordinator".into(),
+            )),
+            TwoPhaseCommitMessage::BatchVoteRequest(..) => Err(InvalidStateError::with_message(
+                "BatchVoteRequest message cannot be handled by a coordinator".into(),
+            )),
+            TwoPhaseCommitMessage::BatchCommit(..) => Err(InvalidStateError::with_message(
+                "BatchCommit message cannot be handled by a coordinator".into(),
+            )),
+            TwoPhaseCommitMessage::BatchOutcome(..) => Err(InvalidStateError::with_message(
+                "BatchOutcome message cannot be handled by a coordinator".into(),
+            )),
         }
     }
 }
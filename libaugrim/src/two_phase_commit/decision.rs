@@ -0,0 +1,95 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// The outcome of an epoch.
+#[derive(Clone, Copy, Debug, Eq, PartialEq)]
+pub enum Decision {
+    Abort,
+    Commit,
+}
+
+// The decisions of recently decided epochs or transactions, ordered by key. The history is
+// bounded by removing the decisions with the lowest keys once it holds more than a given number
+// of decisions. The highest key removed this way is kept as a low-water mark.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub(super) struct DecisionHistory<K, D> {
+    decisions: Vec<(K, D)>,
+    pruned: Option<K>,
+}
+
+impl<K, D> DecisionHistory<K, D>
+where
+    K: Copy + Ord,
+{
+    pub(super) fn new() -> Self {
+        Self {
+            decisions: Vec::new(),
+            pruned: None,
+        }
+    }
+
+    // The decisions in the history, ordered from the lowest key to the highest.
+    pub(super) fn decisions(&self) -> &Vec<(K, D)> {
+        &self.decisions
+    }
+
+    // Returns the decision for the given key, if it is in the history.
+    pub(super) fn get(&self, key: K) -> Option<&D> {
+        self.decisions
+            .binary_search_by_key(&key, |(decided_key, _)| *decided_key)
+            .ok()
+            .map(|index| &self.decisions[index].1)
+    }
+
+    // Adds a decision to the history, unless the key already has one, then prunes the history to
+    // at most `size` decisions by removing the decisions with the lowest keys.
+    pub(super) fn record(&mut self, key: K, decision: D, size: usize) {
+        if let Err(index) = self
+            .decisions
+            .binary_search_by_key(&key, |(decided_key, _)| *decided_key)
+        {
+            self.decisions.insert(index, (key, decision));
+        }
+
+        let excess = self.decisions.len().saturating_sub(size);
+        if let Some((key, _)) = self.decisions.drain(..excess).last() {
+            self.pruned = self.pruned.max(Some(key));
+        }
+    }
+
+    // Returns true if the key is at or below the highest key pruned from the history, so its
+    // decision, if there was one, is no longer known.
+    pub(super) fn is_pruned(&self, key: K) -> bool {
+        self.pruned.is_some_and(|pruned| key <= pruned)
+    }
+
+    pub(super) fn clear(&mut self) {
+        self.decisions.clear();
+        self.pruned = None;
+    }
+}
+
+impl<K, D> From<Vec<(K, D)>> for DecisionHistory<K, D>
+where
+    K: Copy + Ord,
+{
+    fn from(mut decisions: Vec<(K, D)>) -> Self {
+        decisions.sort_by_key(|(key, _)| *key);
+        decisions.dedup_by_key(|(key, _)| *key);
+        Self {
+            decisions,
+            pruned: None,
+        }
+    }
+}
@@ -0,0 +1,725 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::marker::PhantomData;
+use std::time::Duration;
+
+use crate::algorithm::{Algorithm, Value};
+use crate::error::{AlgorithmError, InvalidStateError};
+use crate::process::Process;
+use crate::time::TimeSource;
+
+use super::Decision;
+use super::Epoch;
+use super::LinearTwoPhaseCommitAction;
+use super::LinearTwoPhaseCommitContext;
+use super::LinearTwoPhaseCommitState;
+use super::Term;
+use super::TwoPhaseCommitActionNotification;
+use super::TwoPhaseCommitConfig;
+use super::TwoPhaseCommitEvent;
+use super::TwoPhaseCommitMessage;
+
+// The term of every message sent along the chain.
+const LINEAR_TERM: Term = 0;
+
+/// The linear (chained) variant of two-phase commit.
+///
+/// Rather than a coordinator exchanging messages with every participant, the processes form
+/// a chain. The head of the chain starts an epoch and votes; each process which votes yes passes
+/// the VoteRequest on to the next process, so a VoteRequest means every earlier process has voted
+/// yes. The tail of the chain decides to commit when it votes yes, and a process which votes no
+/// decides to abort. The decision is then passed back along the chain to the head. This takes
+/// 2(n - 1) messages for a chain of n processes, at the cost of latency.
+///
+/// A process which has voted yes waits for the decision with a timeout which grows with the number
+/// of processes after it in the chain: the configured decision timeout plus a local vote timeout
+/// for each later process. When it expires, a DecisionRequest is sent to every later process, so
+/// that the epoch can be decided around a failed link. A process which has not yet voted in the
+/// epoch answers a DecisionRequest by aborting the epoch, which is safe because the tail can not
+/// have committed an epoch without its vote.
+///
+/// The presumption of the configuration is not used, as decisions are not acknowledged. The
+/// chain has no coordinator which can be taken over, so every message carries term 0.
+pub struct LinearTwoPhaseCommitAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource,
+{
+    _process_phantom: PhantomData<P>,
+    _value_phantom: PhantomData<V>,
+    config: TwoPhaseCommitConfig,
+    time_source: TS,
+}
+
+impl<P, V, TS> LinearTwoPhaseCommitAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource,
+{
+    /// Create a new algorithm using the default configuration.
+    pub fn new(time_source: TS) -> Self {
+        Self::with_config(time_source, TwoPhaseCommitConfig::default())
+    }
+
+    /// Create a new algorithm using the given configuration.
+    pub fn with_config(time_source: TS, config: TwoPhaseCommitConfig) -> Self {
+        LinearTwoPhaseCommitAlgorithm {
+            _process_phantom: PhantomData,
+            _value_phantom: PhantomData,
+            config,
+            time_source,
+        }
+    }
+
+    // The time to wait for a decision after voting yes. Each later process in the chain may take
+    // up to the local vote timeout to vote before passing the VoteRequest on.
+    fn decision_timeout(&self, context: &LinearTwoPhaseCommitContext<P, V, TS::Time>) -> Duration {
+        let successors = u32::try_from(context.successors().len()).unwrap_or(u32::MAX);
+        self.config
+            .local_vote_timeout
+            .saturating_mul(successors)
+            .saturating_add(self.config.decision_timeout)
+    }
+
+    // Create actions for a decision. The decision is recorded and notified, then passed back to
+    // the previous process in the chain. The head is then ready to start the next epoch.
+    fn push_decision_actions(
+        &self,
+        mut context: LinearTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<LinearTwoPhaseCommitAction<P, V, TS::Time>>,
+        decision: Decision,
+    ) {
+        let epoch = context.epoch;
+
+        context.record_decision(epoch, decision, self.config.decision_history_size);
+        context.state = LinearTwoPhaseCommitState::WaitingForVoteRequest;
+        let value = context.value.take();
+        actions.push(LinearTwoPhaseCommitAction::Update {
+            context: context.clone(),
+            alarm: None,
+        });
+
+        let (notification, message) = match decision {
+            Decision::Commit => (
+                TwoPhaseCommitActionNotification::Commit(epoch, value),
+                TwoPhaseCommitMessage::Commit(LINEAR_TERM, epoch),
+            ),
+            Decision::Abort => (
+                TwoPhaseCommitActionNotification::Abort(epoch, value),
+                TwoPhaseCommitMessage::Abort(LINEAR_TERM, epoch),
+            ),
+        };
+        actions.push(LinearTwoPhaseCommitAction::Notify(notification));
+
+        match context.previous() {
+            Some(previous) => actions.push(LinearTwoPhaseCommitAction::SendMessage(
+                previous.clone(),
+                message,
+            )),
+            None => actions.push(LinearTwoPhaseCommitAction::Notify(
+                TwoPhaseCommitActionNotification::RequestForStart(epoch + 1),
+            )),
+        }
+    }
+
+    // Create actions for voting yes. The tail decides to commit; any other process passes the
+    // VoteRequest on to the next process and waits for the decision.
+    fn push_vote_yes_actions(
+        &self,
+        mut context: LinearTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<LinearTwoPhaseCommitAction<P, V, TS::Time>>,
+        value: V,
+    ) {
+        let next = match context.successors().first() {
+            Some(next) => next.clone(),
+            None => {
+                self.push_decision_actions(context, actions, Decision::Commit);
+                return;
+            }
+        };
+
+        let decision_timeout_start = self.time_source.now();
+        let decision_timeout_end = decision_timeout_start + self.decision_timeout(&context);
+
+        context.state = LinearTwoPhaseCommitState::WaitingForDecision {
+            decision_timeout_start,
+        };
+        actions.push(LinearTwoPhaseCommitAction::Update {
+            context: context.clone(),
+            alarm: Some(decision_timeout_end),
+        });
+
+        actions.push(LinearTwoPhaseCommitAction::SendMessage(
+            next,
+            TwoPhaseCommitMessage::VoteRequest(
+                LINEAR_TERM,
+                context.epoch,
+                value,
+                context.chain.clone(),
+                vec![],
+            ),
+        ));
+    }
+
+    // Create actions for the recovery protocol. A DecisionRequest is sent to every later process
+    // in the chain, and the decision timeout is restarted so that the requests are sent again if
+    // no decision is received.
+    fn push_decision_request_actions(
+        &self,
+        mut context: LinearTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<LinearTwoPhaseCommitAction<P, V, TS::Time>>,
+    ) {
+        for process in context.successors() {
+            actions.push(LinearTwoPhaseCommitAction::SendMessage(
+                process.clone(),
+                TwoPhaseCommitMessage::DecisionRequest(context.epoch),
+            ));
+        }
+
+        let decision_timeout_start = self.time_source.now();
+        let decision_timeout_end = decision_timeout_start + self.decision_timeout(&context);
+
+        context.state = LinearTwoPhaseCommitState::WaitingForDecision {
+            decision_timeout_start,
+        };
+        actions.push(LinearTwoPhaseCommitAction::Update {
+            context,
+            alarm: Some(decision_timeout_end),
+        });
+    }
+
+    // Create actions for starting an epoch, requesting the vote of the application.
+    fn push_request_for_vote_actions(
+        &self,
+        mut context: LinearTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<LinearTwoPhaseCommitAction<P, V, TS::Time>>,
+        epoch: Epoch,
+        value: V,
+    ) {
+        let local_vote_timeout_start = self.time_source.now();
+        let local_vote_timeout_end = local_vote_timeout_start + self.config.local_vote_timeout;
+
+        context.epoch = epoch;
+        context.value = Some(value.clone());
+        context.state = LinearTwoPhaseCommitState::WaitingForVote {
+            local_vote_timeout_start,
+        };
+        actions.push(LinearTwoPhaseCommitAction::Update {
+            context,
+            alarm: Some(local_vote_timeout_end),
+        });
+
+        actions.push(LinearTwoPhaseCommitAction::Notify(
+            TwoPhaseCommitActionNotification::ParticipantRequestForVote(value),
+        ));
+    }
+
+    // Create actions for a decision delivered by a later process in the chain.
+    fn push_delivered_decision_actions(
+        &self,
+        context: LinearTwoPhaseCommitContext<P, V, TS::Time>,
+        actions: &mut Vec<LinearTwoPhaseCommitAction<P, V, TS::Time>>,
+        process: P,
+        epoch: Epoch,
+        decision: Decision,
+    ) {
+        if !context.successors().contains(&process) {
+            actions.extend(Self::message_dropped(
+                "sender process is not a later process in the chain",
+            ));
+        } else if context.epoch != epoch
+            || !matches!(
+                context.state,
+                LinearTwoPhaseCommitState::WaitingForDecision { .. }
+            )
+        {
+            actions.extend(Self::message_dropped(
+                "decision received outside decision window",
+            ));
+        } else {
+            self.push_decision_actions(context, actions, decision);
+        }
+    }
+
+    fn message_dropped(reason: &str) -> Vec<LinearTwoPhaseCommitAction<P, V, TS::Time>> {
+        vec![LinearTwoPhaseCommitAction::Notify(
+            TwoPhaseCommitActionNotification::MessageDropped(reason.into()),
+        )]
+    }
+}
+
+impl<P, V, TS> Algorithm for LinearTwoPhaseCommitAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource,
+{
+    type Event = TwoPhaseCommitEvent<P, V>;
+    type Action = LinearTwoPhaseCommitAction<P, V, TS::Time>;
+    type Context = LinearTwoPhaseCommitContext<P, V, TS::Time>;
+
+    fn event(
+        &self,
+        event: Self::Event,
+        context: Self::Context,
+    ) -> Result<Vec<Self::Action>, AlgorithmError> {
+        let mut actions = Vec::new();
+
+        match event {
+            TwoPhaseCommitEvent::Alarm() => match context.state.clone() {
+                // The application has not voted within the local vote timeout, so vote no on its
+                // behalf.
+                LinearTwoPhaseCommitState::WaitingForVote {
+                    local_vote_timeout_start,
+                } => {
+                    if self.time_source.now()
+                        > local_vote_timeout_start + self.config.local_vote_timeout
+                    {
+                        self.push_decision_actions(context, &mut actions, Decision::Abort);
+                    }
+                }
+
+                // No decision has come back along the chain, so ask the later processes for it.
+                LinearTwoPhaseCommitState::WaitingForDecision {
+                    decision_timeout_start,
+                } => {
+                    if self.time_source.now()
+                        > decision_timeout_start + self.decision_timeout(&context)
+                    {
+                        self.push_decision_request_actions(context, &mut actions);
+                    }
+                }
+
+                // An Alarm while in WaitingForVoteRequest is not allowed and indicates a bug in
+                // the caller.
+                LinearTwoPhaseCommitState::WaitingForVoteRequest => {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "Alarm unexpected in WaitingForVoteRequest state".into(),
+                        ),
+                    ))
+                }
+            },
+
+            // A Recover event is sent once after the process has been restarted from its
+            // persisted context. A decision which was not passed back along the chain before
+            // the restart is requested again by the previous process, so only the outstanding
+            // state needs to be recovered.
+            TwoPhaseCommitEvent::Recover() => match context.state.clone() {
+                LinearTwoPhaseCommitState::WaitingForVote {
+                    local_vote_timeout_start,
+                } => {
+                    let local_vote_timeout_end =
+                        local_vote_timeout_start + self.config.local_vote_timeout;
+
+                    match context.value.clone() {
+                        Some(value) if self.time_source.now() <= local_vote_timeout_end => {
+                            actions.push(LinearTwoPhaseCommitAction::Notify(
+                                TwoPhaseCommitActionNotification::ParticipantRequestForVote(value),
+                            ));
+                            actions.push(LinearTwoPhaseCommitAction::Update {
+                                context,
+                                alarm: Some(local_vote_timeout_end),
+                            });
+                        }
+                        _ => self.push_decision_actions(context, &mut actions, Decision::Abort),
+                    }
+                }
+
+                // The decision may have been passed back while we were down, so start the
+                // recovery protocol immediately rather than waiting for the decision timeout.
+                LinearTwoPhaseCommitState::WaitingForDecision { .. } => {
+                    self.push_decision_request_actions(context, &mut actions);
+                }
+
+                LinearTwoPhaseCommitState::WaitingForVoteRequest => {
+                    if context.previous().is_none() {
+                        actions.push(LinearTwoPhaseCommitAction::Notify(
+                            TwoPhaseCommitActionNotification::RequestForStart(context.epoch + 1),
+                        ));
+                    }
+                }
+            },
+
+            TwoPhaseCommitEvent::Start(value) => {
+                if context.previous().is_some() {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "Start event can only be handled by the head of the chain".into(),
+                        ),
+                    ));
+                }
+
+                if context.state != LinearTwoPhaseCommitState::WaitingForVoteRequest {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "Start event when not in WaitingForVoteRequest state".into(),
+                        ),
+                    ));
+                }
+
+                let epoch = context.epoch + 1;
+                self.push_request_for_vote_actions(context, &mut actions, epoch, value);
+            }
+
+            TwoPhaseCommitEvent::Vote(vote) => {
+                if !matches!(
+                    context.state,
+                    LinearTwoPhaseCommitState::WaitingForVote { .. }
+                ) {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "Vote event when not in WaitingForVote state".into(),
+                        ),
+                    ));
+                }
+
+                match (vote, context.value.clone()) {
+                    (true, Some(value)) => self.push_vote_yes_actions(context, &mut actions, value),
+                    _ => self.push_decision_actions(context, &mut actions, Decision::Abort),
+                }
+            }
+
+            // A VoteRequest from the previous process means every earlier process has voted yes.
+            TwoPhaseCommitEvent::Deliver(
+                process,
+                TwoPhaseCommitMessage::VoteRequest(_, epoch, value, ..),
+            ) => {
+                if context.previous() != Some(&process) {
+                    return Ok(Self::message_dropped(
+                        "sender process is not the previous process in the chain",
+                    ));
+                }
+
+                if context.state != LinearTwoPhaseCommitState::WaitingForVoteRequest {
+                    return Ok(Self::message_dropped(
+                        "context state is not WaitingForVoteRequest",
+                    ));
+                }
+
+                // Epochs which were aborted before reaching this process are skipped, so the new
+                // epoch only needs to be greater than the previous one.
+                if context.epoch >= epoch {
+                    return Ok(Self::message_dropped(&format!(
+                        "epoch {} was not greater than previous epoch {}",
+                        epoch, context.epoch
+                    )));
+                }
+
+                self.push_request_for_vote_actions(context, &mut actions, epoch, value);
+            }
+
+            // The decision is passed back from the next process, or sent by any later process in
+            // answer to a DecisionRequest.
+            TwoPhaseCommitEvent::Deliver(process, TwoPhaseCommitMessage::Commit(_, epoch)) => {
+                self.push_delivered_decision_actions(
+                    context,
+                    &mut actions,
+                    process,
+                    epoch,
+                    Decision::Commit,
+                );
+            }
+            TwoPhaseCommitEvent::Deliver(process, TwoPhaseCommitMessage::Abort(_, epoch)) => {
+                self.push_delivered_decision_actions(
+                    context,
+                    &mut actions,
+                    process,
+                    epoch,
+                    Decision::Abort,
+                );
+            }
+
+            // A DecisionRequest is sent by an earlier process which has not received the decision
+            // within its decision timeout.
+            TwoPhaseCommitEvent::Deliver(
+                process,
+                TwoPhaseCommitMessage::DecisionRequest(epoch),
+            ) => {
+                if !context.predecessors().contains(&process) {
+                    return Ok(Self::message_dropped(
+                        "sender process is not an earlier process in the chain",
+                    ));
+                }
+
+                let decision = if let Some(decision) = context.decision(epoch) {
+                    decision
+                } else if epoch > context.epoch {
+                    // The VoteRequest has not reached this process, so the epoch can not have
+                    // been committed. Abort it, so that a late VoteRequest is dropped. The
+                    // application never started the epoch, so it is not notified.
+                    let mut context = context;
+                    let mut aborted = None;
+                    match context.state {
+                        LinearTwoPhaseCommitState::WaitingForVoteRequest => (),
+
+                        // The earlier epoch is still waiting for the application's vote. The
+                        // earlier process has already moved past it, so abort it rather than
+                        // pass its value on under the later epoch.
+                        LinearTwoPhaseCommitState::WaitingForVote { .. } => {
+                            context.record_decision(
+                                context.epoch,
+                                Decision::Abort,
+                                self.config.decision_history_size,
+                            );
+                            context.state = LinearTwoPhaseCommitState::WaitingForVoteRequest;
+                            aborted = Some(TwoPhaseCommitActionNotification::Abort(
+                                context.epoch,
+                                context.value.take(),
+                            ));
+                        }
+
+                        // We voted yes in the earlier epoch, which a later process may have
+                        // committed. Its decision must be known before this process moves on.
+                        LinearTwoPhaseCommitState::WaitingForDecision { .. } => {
+                            return Ok(Self::message_dropped(
+                                "decision of the current epoch is not known",
+                            ));
+                        }
+                    }
+
+                    context.epoch = epoch;
+                    context.record_decision(
+                        epoch,
+                        Decision::Abort,
+                        self.config.decision_history_size,
+                    );
+                    actions.push(LinearTwoPhaseCommitAction::Update {
+                        context,
+                        alarm: None,
+                    });
+                    actions.extend(aborted.map(LinearTwoPhaseCommitAction::Notify));
+                    Decision::Abort
+                } else if epoch == context.epoch
+                    && matches!(
+                        context.state,
+                        LinearTwoPhaseCommitState::WaitingForVote { .. }
+                    )
+                {
+                    // The application has not voted yet, so vote no. The abort is passed back
+                    // to the previous process as well.
+                    let previous = context.previous().cloned();
+                    self.push_decision_actions(context, &mut actions, Decision::Abort);
+                    if previous.as_ref() == Some(&process) {
+                        return Ok(actions);
+                    }
+                    Decision::Abort
+                } else {
+                    return Ok(Self::message_dropped("decision is not known"));
+                };
+
+                let message = match decision {
+                    Decision::Commit => TwoPhaseCommitMessage::Commit(LINEAR_TERM, epoch),
+                    Decision::Abort => TwoPhaseCommitMessage::Abort(LINEAR_TERM, epoch),
+                };
+                actions.push(LinearTwoPhaseCommitAction::SendMessage(process, message));
+            }
+
+            TwoPhaseCommitEvent::Deliver(_, _) => {
+                return Ok(Self::message_dropped(
+                    "message is not used by linear two-phase commit",
+                ))
+            }
+
+            TwoPhaseCommitEvent::StartWithValues(_)
+            | TwoPhaseCommitEvent::StartBatch(_)
+            | TwoPhaseCommitEvent::Cancel()
+            | TwoPhaseCommitEvent::Reconfigure(_)
+            | TwoPhaseCommitEvent::TakeOver(_)
+            | TwoPhaseCommitEvent::VoteWithValue(_)
+            | TwoPhaseCommitEvent::BatchVote(_)
+            | TwoPhaseCommitEvent::ReadOnlyVote() => {
+                return Err(AlgorithmError::InvalidState(
+                    InvalidStateError::with_message(
+                        "event can not be handled by linear two-phase commit".into(),
+                    ),
+                ))
+            }
+        }
+
+        Ok(actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use crate::two_phase_commit::test_support::{Network, TestTimeSource};
+    use crate::two_phase_commit::TwoPhaseCommitConfigBuilder;
+
+    type Message = TwoPhaseCommitMessage<String, u32>;
+    type Notification = TwoPhaseCommitActionNotification<String, u32>;
+
+    // A chain of the processes "a", "b" and "c".
+    type Chain = Network<LinearTwoPhaseCommitAlgorithm<String, u32, TestTimeSource>>;
+
+    fn chain(time: &TestTimeSource) -> Chain {
+        let config = TwoPhaseCommitConfigBuilder::new()
+            .with_decision_timeout(Duration::from_millis(1))
+            .with_local_vote_timeout(Duration::from_millis(1))
+            .build()
+            .unwrap();
+        let chain = vec!["a".to_string(), "b".to_string(), "c".to_string()];
+        let contexts = chain
+            .iter()
+            .map(|process| {
+                let context = LinearTwoPhaseCommitContext::new(process.clone(), chain.clone());
+                (process.clone(), context.unwrap())
+            })
+            .collect();
+
+        Network::new(
+            LinearTwoPhaseCommitAlgorithm::with_config(time.clone(), config),
+            time,
+            contexts,
+        )
+    }
+
+    /// Test that the vote is passed down the chain and the commit decision of the tail is passed
+    /// back to the head.
+    #[test]
+    fn linear_commit() {
+        let mut chain = chain(&TestTimeSource::new());
+
+        chain.event("a", TwoPhaseCommitEvent::Start(7));
+        chain.event("a", TwoPhaseCommitEvent::Vote(true));
+        chain.deliver();
+        chain.event("b", TwoPhaseCommitEvent::Vote(true));
+        chain.deliver();
+        assert!(chain.notified("c", Notification::ParticipantRequestForVote(7)));
+
+        chain.event("c", TwoPhaseCommitEvent::Vote(true));
+        assert_eq!(
+            chain.messages,
+            vec![("c".into(), "b".into(), Message::Commit(0, 1))]
+        );
+        chain.deliver();
+        chain.deliver();
+        assert!(chain.messages.is_empty());
+
+        for process in ["a", "b", "c"] {
+            assert!(chain.notified(process, Notification::Commit(1, Some(7))));
+            assert_eq!(
+                *chain.contexts[process].state(),
+                LinearTwoPhaseCommitState::WaitingForVoteRequest
+            );
+        }
+        assert!(chain.notified("a", Notification::RequestForStart(2)));
+    }
+
+    /// Test that when the VoteRequest to the tail is lost, the middle process asks the tail for
+    /// the decision after its decision timeout, and the tail aborts the epoch it has not seen.
+    #[test]
+    fn linear_recovers_from_failed_link() {
+        let time = TestTimeSource::new();
+        let mut chain = chain(&time);
+
+        chain.event("a", TwoPhaseCommitEvent::Start(7));
+        chain.event("a", TwoPhaseCommitEvent::Vote(true));
+        chain.deliver();
+        chain.event("b", TwoPhaseCommitEvent::Vote(true));
+
+        // The link from b to c fails.
+        chain.messages.clear();
+
+        time.advance(Duration::from_millis(5));
+        chain.alarm("b");
+        assert_eq!(
+            chain.messages,
+            vec![("b".into(), "c".into(), Message::DecisionRequest(1))]
+        );
+        chain.deliver();
+        chain.deliver();
+        chain.deliver();
+
+        assert!(chain.notified("b", Notification::Abort(1, Some(7))));
+        assert!(chain.notified("a", Notification::Abort(1, Some(7))));
+
+        // The tail never started the epoch, so its application is not notified.
+        assert!(!chain
+            .notifications
+            .iter()
+            .any(|(process, _)| process == "c"));
+
+        // A late VoteRequest for the aborted epoch is dropped by the tail.
+        let actions = chain
+            .algorithm
+            .event(
+                TwoPhaseCommitEvent::Deliver(
+                    "b".into(),
+                    Message::VoteRequest(0, 1, 7, chain.contexts["c"].chain().clone(), vec![]),
+                ),
+                chain.contexts["c"].clone(),
+            )
+            .unwrap();
+        assert!(matches!(
+            actions[..],
+            [LinearTwoPhaseCommitAction::Notify(
+                Notification::MessageDropped(_)
+            )]
+        ));
+    }
+
+    /// Test that a DecisionRequest for a later epoch, received while the application has not yet
+    /// voted in an earlier epoch, aborts the earlier epoch rather than passing its value on under
+    /// the later epoch.
+    #[test]
+    fn linear_decision_request_aborts_epoch_waiting_for_vote() {
+        let time = TestTimeSource::new();
+        let mut chain = chain(&time);
+
+        chain.event("a", TwoPhaseCommitEvent::Start(7));
+        chain.event("a", TwoPhaseCommitEvent::Vote(true));
+        chain.deliver();
+        assert!(chain.notified("b", Notification::ParticipantRequestForVote(7)));
+
+        // The DecisionRequest to b is lost, so the tail aborts epoch 1 and the head moves on.
+        time.advance(Duration::from_millis(5));
+        chain.alarm("a");
+        chain.messages.pop_front();
+        chain.deliver_all(&[]);
+        assert!(chain.notified("a", Notification::RequestForStart(2)));
+
+        // The VoteRequest of epoch 2 is dropped by b, which is still waiting for its vote.
+        chain.event("a", TwoPhaseCommitEvent::Start(8));
+        chain.event("a", TwoPhaseCommitEvent::Vote(true));
+        chain.deliver();
+        time.advance(Duration::from_millis(5));
+        chain.alarm("a");
+        chain.deliver_all(&[]);
+
+        assert!(chain.notified("b", Notification::Abort(1, Some(7))));
+        assert!(!chain
+            .notifications
+            .iter()
+            .any(|(process, notification)| process == "b"
+                && matches!(notification, Notification::Abort(2, _))));
+        assert!(chain.notified("a", Notification::Abort(2, Some(8))));
+        assert_eq!(*chain.contexts["b"].epoch(), 2);
+        assert_eq!(
+            *chain.contexts["b"].state(),
+            LinearTwoPhaseCommitState::WaitingForVoteRequest
+        );
+
+        // A vote from the application for epoch 1 can no longer be passed on.
+        assert!(chain
+            .algorithm
+            .event(TwoPhaseCommitEvent::Vote(true), chain.contexts["b"].clone())
+            .is_err());
+    }
+}
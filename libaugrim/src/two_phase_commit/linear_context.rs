@@ -0,0 +1,146 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::algorithm::Value;
+use crate::error::InvalidStateError;
+use crate::process::Process;
+use crate::time::Time;
+
+use super::Decision;
+use super::DecisionHistory;
+use super::Epoch;
+use super::LinearTwoPhaseCommitState;
+
+/// The context of a process in linear two-phase commit.
+///
+/// The chain is the ordered list of all processes of the algorithm, including this process. The
+/// first process of the chain is the head, which starts each epoch, and the last process is the
+/// tail, which decides it.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub struct LinearTwoPhaseCommitContext<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    pub(super) chain: Vec<P>,
+    pub(super) decision_history: DecisionHistory<Epoch, Decision>,
+    pub(super) epoch: Epoch,
+    pub(super) state: LinearTwoPhaseCommitState<T>,
+    pub(super) this_process: P,
+    pub(super) value: Option<V>,
+}
+
+impl<P, V, T> LinearTwoPhaseCommitContext<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    /// Create the context of a process which is waiting for the first epoch.
+    pub fn new(this_process: P, chain: Vec<P>) -> Result<Self, InvalidStateError> {
+        if !chain.contains(&this_process) {
+            return Err(InvalidStateError::with_message(
+                "chain must contain this process".into(),
+            ));
+        }
+
+        if chain
+            .iter()
+            .enumerate()
+            .any(|(i, process)| chain[..i].contains(process))
+        {
+            return Err(InvalidStateError::with_message(
+                "chain must not contain duplicate processes".into(),
+            ));
+        }
+
+        Ok(Self {
+            chain,
+            decision_history: DecisionHistory::new(),
+            epoch: 0,
+            state: LinearTwoPhaseCommitState::WaitingForVoteRequest,
+            this_process,
+            value: None,
+        })
+    }
+
+    pub fn chain(&self) -> &Vec<P> {
+        &self.chain
+    }
+
+    // The position of this process in the chain.
+    fn position(&self) -> usize {
+        self.chain
+            .iter()
+            .position(|process| *process == self.this_process)
+            .unwrap_or_default()
+    }
+
+    // The process before this process in the chain, or None for the head.
+    pub(super) fn previous(&self) -> Option<&P> {
+        self.position()
+            .checked_sub(1)
+            .and_then(|index| self.chain.get(index))
+    }
+
+    // The processes after this process in the chain, nearest first.
+    pub(super) fn successors(&self) -> &[P] {
+        &self.chain[self.position() + 1..]
+    }
+
+    // The processes before this process in the chain.
+    pub(super) fn predecessors(&self) -> &[P] {
+        &self.chain[..self.position()]
+    }
+
+    /// The decisions of recently decided epochs, ordered from the oldest epoch to the newest.
+    pub fn decision_history(&self) -> &Vec<(Epoch, Decision)> {
+        self.decision_history.decisions()
+    }
+
+    // Returns the decision for the given epoch, if it is in the decision history.
+    pub(super) fn decision(&self, epoch: Epoch) -> Option<Decision> {
+        self.decision_history.get(epoch).copied()
+    }
+
+    // Adds a decision to the decision history, then prunes the history to at most
+    // `history_size` decisions by removing the decisions of the oldest epochs.
+    pub(super) fn record_decision(
+        &mut self,
+        epoch: Epoch,
+        decision: Decision,
+        history_size: usize,
+    ) {
+        self.decision_history.record(epoch, decision, history_size);
+    }
+
+    /// The most recent epoch seen by this process.
+    pub fn epoch(&self) -> &Epoch {
+        &self.epoch
+    }
+
+    pub fn state(&self) -> &LinearTwoPhaseCommitState<T> {
+        &self.state
+    }
+
+    pub fn this_process(&self) -> &P {
+        &self.this_process
+    }
+
+    /// The value of the current epoch, which is kept until the epoch is decided.
+    pub fn value(&self) -> &Option<V> {
+        &self.value
+    }
+}
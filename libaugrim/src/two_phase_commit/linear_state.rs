@@ -0,0 +1,29 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+/// The state of a process in linear two-phase commit.
+///
+/// A decided epoch does not have a state of its own; the decision is recorded in the decision
+/// history of the context and the process returns to `WaitingForVoteRequest`.
+#[derive(Clone, Debug, Eq, PartialEq)]
+pub enum LinearTwoPhaseCommitState<T> {
+    /// Waiting for the next epoch, which is started by a Start event at the head of the chain and
+    /// by a VoteRequest from the previous process everywhere else.
+    WaitingForVoteRequest,
+    /// Waiting for the application to vote.
+    WaitingForVote { local_vote_timeout_start: T },
+    /// Voted yes and passed the VoteRequest on to the next process, and waiting for the decision
+    /// to come back along the chain.
+    WaitingForDecision { decision_timeout_start: T },
+}
@@ -20,16 +20,38 @@
 //!   7.4.  This book may be downloaded for free from
 //!   <https://www.microsoft.com/en-us/research/people/philbe/>.
 
+mod config;
+mod config_builder;
 mod coordinator_action;
 mod coordinator_algorithm;
 mod coordinator_context;
 mod coordinator_event;
 mod coordinator_message;
+mod decision;
+mod linear_action;
+mod linear_algorithm;
+mod linear_context;
+mod linear_state;
+mod multi_action;
+mod multi_algorithm;
+mod multi_context;
+mod multi_event;
+mod observer_action;
+mod observer_algorithm;
+mod observer_context;
+mod observer_event;
+mod observer_message;
 mod participant_action;
 mod participant_algorithm;
 mod participant_context;
 mod participant_event;
 mod participant_message;
+mod random;
+#[cfg(test)]
+mod test_support;
+mod tree_action;
+mod tree_algorithm;
+mod tree_context;
 mod unified_action;
 mod unified_algorithm;
 mod unified_context;
@@ -39,17 +61,38 @@ mod unified_message;
 mod unified_role;
 mod unified_state;
 
+pub use config::{Presumption, TwoPhaseCommitConfig};
+pub use config_builder::TwoPhaseCommitConfigBuilder;
 use coordinator_action::{CoordinatorAction, CoordinatorActionNotification};
 use coordinator_algorithm::CoordinatorAlgorithm;
 pub use coordinator_context::Participant;
 use coordinator_context::{CoordinatorContext, CoordinatorState};
 use coordinator_event::CoordinatorEvent;
 use coordinator_message::CoordinatorMessage;
+pub use decision::Decision;
+use decision::DecisionHistory;
+pub use linear_action::LinearTwoPhaseCommitAction;
+pub use linear_algorithm::LinearTwoPhaseCommitAlgorithm;
+pub use linear_context::LinearTwoPhaseCommitContext;
+pub use linear_state::LinearTwoPhaseCommitState;
+pub use multi_action::MultiTwoPhaseCommitAction;
+pub use multi_algorithm::MultiTwoPhaseCommitAlgorithm;
+pub use multi_context::MultiTwoPhaseCommitContext;
+pub use multi_event::MultiTwoPhaseCommitEvent;
+use observer_action::{ObserverAction, ObserverActionNotification};
+use observer_algorithm::ObserverAlgorithm;
+use observer_context::ObserverContext;
+use observer_event::ObserverEvent;
+use observer_message::ObserverMessage;
 use participant_action::{ParticipantAction, ParticipantActionNotification};
 use participant_algorithm::ParticipantAlgorithm;
 use participant_context::{ParticipantContext, ParticipantState};
 use participant_event::ParticipantEvent;
 use participant_message::ParticipantMessage;
+pub use random::{RandomSource, SeededRandomSource};
+pub use tree_action::TreeTwoPhaseCommitAction;
+pub use tree_algorithm::TreeTwoPhaseCommitAlgorithm;
+pub use tree_context::TreeTwoPhaseCommitContext;
 pub use unified_action::{TwoPhaseCommitAction, TwoPhaseCommitActionNotification};
 pub use unified_algorithm::TwoPhaseCommitAlgorithm;
 pub use unified_context::TwoPhaseCommitContext;
@@ -67,3 +110,15 @@ pub use unified_state::TwoPhaseCommitState;
 /// This extension to the original algorithm allows running the algorithm continuously, to agree on
 /// a sequence of values instead of a single value. In each iteration, the epoch increases by 1.
 pub type Epoch = u64;
+
+/// The scope of a single coordinator.
+///
+/// A term starts when a coordinator takes over from a failed coordinator. Participants only accept
+/// messages from the coordinator of the highest term they have seen, which fences off a deposed
+/// coordinator.
+pub type Term = u64;
+
+/// The identifier of a transaction, when running many transactions at once.
+///
+/// See [`MultiTwoPhaseCommitAlgorithm`].
+pub type TransactionId = u64;
@@ -0,0 +1,37 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::algorithm::Value;
+use crate::process::Process;
+use crate::time::Time;
+
+use super::MultiTwoPhaseCommitContext;
+use super::TransactionId;
+use super::TwoPhaseCommitActionNotification;
+use super::TwoPhaseCommitMessage;
+
+#[derive(Debug, PartialEq)]
+pub enum MultiTwoPhaseCommitAction<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    Update {
+        context: MultiTwoPhaseCommitContext<P, V, T>,
+        alarm: Option<T>,
+    },
+    SendMessage(P, TransactionId, TwoPhaseCommitMessage<P, V>),
+    Notify(TransactionId, TwoPhaseCommitActionNotification<P, V>),
+}
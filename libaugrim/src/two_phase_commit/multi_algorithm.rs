@@ -0,0 +1,547 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::algorithm::{Algorithm, Value};
+use crate::error::{AlgorithmError, InvalidStateError};
+use crate::process::Process;
+use crate::time::TimeSource;
+
+use super::Decision;
+use super::MultiTwoPhaseCommitAction;
+use super::MultiTwoPhaseCommitContext;
+use super::MultiTwoPhaseCommitEvent;
+use super::TransactionId;
+use super::TwoPhaseCommitAction;
+use super::TwoPhaseCommitActionNotification;
+use super::TwoPhaseCommitAlgorithm;
+use super::TwoPhaseCommitConfig;
+use super::TwoPhaseCommitEvent;
+use super::TwoPhaseCommitMessage;
+
+/// The algorithm for a node running many two-phase commit transactions at once.
+///
+/// A [`TwoPhaseCommitContext`] runs one epoch at a time, so a single group can only decide one
+/// value per round trip. This algorithm runs an independent [`TwoPhaseCommitAlgorithm`] for each
+/// transaction, keyed by a transaction id which is carried by every event, message and
+/// notification. The context of each transaction is created from the template context of the
+/// node, so each transaction runs a single epoch with the same processes.
+///
+/// The node has a single alarm, which is the earliest alarm of the transactions in flight. Once
+/// a transaction has finished, its context is dropped and its decision is kept in a decision log
+/// shared by all transactions, which is used to answer late messages for the transaction. The
+/// log holds the decisions of at most `decision_history_size` transactions, dropping the decisions
+/// of the lowest transaction ids first. A transaction which is not in flight and whose id is at
+/// or below the highest id dropped from the log can no longer be started, and messages for it are
+/// dropped, so transaction ids should increase over time.
+///
+/// The RequestForStart notification is not passed on, as a new transaction may be started at any
+/// time. Changing the processes of the group and coordinator takeover are not supported.
+///
+/// [`TwoPhaseCommitContext`]: super::TwoPhaseCommitContext
+pub struct MultiTwoPhaseCommitAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource,
+{
+    algorithm: TwoPhaseCommitAlgorithm<P, V, TS>,
+    config: TwoPhaseCommitConfig,
+    time_source: TS,
+}
+
+impl<P, V, TS> MultiTwoPhaseCommitAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource + Clone,
+{
+    /// Create a new algorithm using the default configuration.
+    pub fn new(time_source: TS) -> Self {
+        Self::with_config(time_source, TwoPhaseCommitConfig::default())
+    }
+
+    /// Create a new algorithm using the given configuration.
+    pub fn with_config(time_source: TS, config: TwoPhaseCommitConfig) -> Self {
+        Self {
+            algorithm: TwoPhaseCommitAlgorithm::with_config(time_source.clone(), config.clone()),
+            config,
+            time_source,
+        }
+    }
+}
+
+// The context and the actions generated so far while processing an event.
+struct Step<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: crate::time::Time,
+{
+    context: MultiTwoPhaseCommitContext<P, V, T>,
+    actions: Vec<MultiTwoPhaseCommitAction<P, V, T>>,
+}
+
+impl<P, V, T> Step<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: crate::time::Time,
+{
+    fn push_update(&mut self) {
+        self.actions.push(MultiTwoPhaseCommitAction::Update {
+            context: self.context.clone(),
+            alarm: self.context.alarm(),
+        });
+    }
+
+    // Returns an error if the transaction is not in flight.
+    fn check_in_flight(&self, transaction_id: TransactionId) -> Result<(), AlgorithmError> {
+        match self.context.transaction(transaction_id) {
+            Some(_) => Ok(()),
+            None => Err(AlgorithmError::InvalidState(
+                InvalidStateError::with_message(format!(
+                    "transaction {transaction_id} is not in flight"
+                )),
+            )),
+        }
+    }
+
+    // Returns an error if the transaction has already finished, or may have finished and been
+    // pruned from the decision log.
+    fn check_not_finished(&self, transaction_id: TransactionId) -> Result<(), AlgorithmError> {
+        if self.context.is_forgotten(transaction_id) {
+            return Err(AlgorithmError::InvalidState(
+                InvalidStateError::with_message(format!(
+                    "transaction {transaction_id} is at or below the decision log low-water mark"
+                )),
+            ));
+        }
+
+        match (
+            self.context.transaction(transaction_id),
+            self.context.decision(transaction_id),
+        ) {
+            (None, Some(_)) => Err(AlgorithmError::InvalidState(
+                InvalidStateError::with_message(format!(
+                    "transaction {transaction_id} has already been decided"
+                )),
+            )),
+            _ => Ok(()),
+        }
+    }
+}
+
+impl<P, V, TS> MultiTwoPhaseCommitAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource,
+{
+    // Process an event with the context of the given transaction, creating the context from the
+    // template if the transaction is not in flight.
+    fn transaction_event(
+        &self,
+        step: &mut Step<P, V, TS::Time>,
+        transaction_id: TransactionId,
+        event: TwoPhaseCommitEvent<P, V>,
+    ) -> Result<(), AlgorithmError> {
+        let context = match step.context.transaction(transaction_id) {
+            Some(context) => context.clone(),
+            None => {
+                let mut context = (*step.context.template).clone();
+                context.decision_history.clear();
+                context
+            }
+        };
+
+        for action in self.algorithm.event(event, context)? {
+            match action {
+                // The context of a transaction only ever decides a single epoch, so any decision
+                // in its history is the decision of the transaction.
+                TwoPhaseCommitAction::Update { context, alarm } => {
+                    if let Some((_, decision)) = context.decision_history().first() {
+                        step.context.record_decision(
+                            transaction_id,
+                            *decision,
+                            *self.config.decision_history_size(),
+                        );
+                    }
+                    step.context
+                        .update_transaction(transaction_id, context, alarm);
+                    step.push_update();
+                }
+
+                TwoPhaseCommitAction::SendMessage(process, message) => step.actions.push(
+                    MultiTwoPhaseCommitAction::SendMessage(process, transaction_id, message),
+                ),
+
+                TwoPhaseCommitAction::Notify(
+                    TwoPhaseCommitActionNotification::RequestForStart(_),
+                ) => (),
+
+                TwoPhaseCommitAction::Notify(notification) => step.actions.push(
+                    MultiTwoPhaseCommitAction::Notify(transaction_id, notification),
+                ),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Answer a message for a transaction which has finished, using the decision log.
+    fn decided_message(
+        &self,
+        step: &mut Step<P, V, TS::Time>,
+        process: P,
+        transaction_id: TransactionId,
+        message: TwoPhaseCommitMessage<P, V>,
+        decision: Decision,
+    ) {
+        let action = match message {
+            TwoPhaseCommitMessage::DecisionRequest(epoch) => {
+                let term = *step.context.template.term();
+                let message = match decision {
+                    Decision::Commit => TwoPhaseCommitMessage::Commit(term, epoch),
+                    Decision::Abort => TwoPhaseCommitMessage::Abort(term, epoch),
+                };
+                MultiTwoPhaseCommitAction::SendMessage(process, transaction_id, message)
+            }
+
+            // The coordinator did not receive the decision ack, so send it again.
+            TwoPhaseCommitMessage::Commit(_, epoch) | TwoPhaseCommitMessage::Abort(_, epoch)
+                if &process == step.context.template.coordinator() =>
+            {
+                MultiTwoPhaseCommitAction::SendMessage(
+                    process,
+                    transaction_id,
+                    TwoPhaseCommitMessage::DecisionAck(epoch),
+                )
+            }
+
+            _ => MultiTwoPhaseCommitAction::Notify(
+                transaction_id,
+                TwoPhaseCommitActionNotification::MessageDropped(format!(
+                    "transaction {transaction_id} has already been decided",
+                )),
+            ),
+        };
+
+        step.actions.push(action);
+    }
+}
+
+impl<P, V, TS> Algorithm for MultiTwoPhaseCommitAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource,
+{
+    type Event = MultiTwoPhaseCommitEvent<P, V>;
+    type Action = MultiTwoPhaseCommitAction<P, V, TS::Time>;
+    type Context = MultiTwoPhaseCommitContext<P, V, TS::Time>;
+
+    fn event(
+        &self,
+        event: Self::Event,
+        context: Self::Context,
+    ) -> Result<Vec<Self::Action>, AlgorithmError> {
+        let mut step = Step {
+            context,
+            actions: Vec::new(),
+        };
+
+        match event {
+            // The alarm is the earliest alarm of the transactions, so pass it on to each
+            // transaction whose alarm is due. The alarm is always set again, as the alarms of the
+            // other transactions may still be pending.
+            MultiTwoPhaseCommitEvent::Alarm() => {
+                let now = self.time_source.now();
+                let due = step
+                    .context
+                    .transactions
+                    .iter()
+                    .filter(|transaction| matches!(transaction.alarm, Some(alarm) if alarm <= now))
+                    .map(|transaction| transaction.id)
+                    .collect::<Vec<_>>();
+
+                for transaction_id in due {
+                    self.transaction_event(
+                        &mut step,
+                        transaction_id,
+                        TwoPhaseCommitEvent::Alarm(),
+                    )?;
+                }
+
+                step.push_update();
+            }
+
+            MultiTwoPhaseCommitEvent::Recover() => {
+                for transaction_id in step.context.transaction_ids() {
+                    self.transaction_event(
+                        &mut step,
+                        transaction_id,
+                        TwoPhaseCommitEvent::Recover(),
+                    )?;
+                }
+            }
+
+            MultiTwoPhaseCommitEvent::Deliver(process, transaction_id, message) => {
+                match (
+                    step.context.transaction(transaction_id),
+                    step.context.decision(transaction_id),
+                ) {
+                    (None, Some(decision)) => {
+                        self.decided_message(&mut step, process, transaction_id, message, decision)
+                    }
+                    // The transaction may have finished and been forgotten, so a message for it
+                    // must not start it again.
+                    (None, None) if step.context.is_forgotten(transaction_id) => {
+                        step.actions.push(MultiTwoPhaseCommitAction::Notify(
+                            transaction_id,
+                            TwoPhaseCommitActionNotification::MessageDropped(format!(
+                                "transaction {transaction_id} is at or below the decision log \
+                                 low-water mark",
+                            )),
+                        ))
+                    }
+                    _ => self.transaction_event(
+                        &mut step,
+                        transaction_id,
+                        TwoPhaseCommitEvent::Deliver(process, message),
+                    )?,
+                }
+            }
+
+            MultiTwoPhaseCommitEvent::Start(transaction_id, value) => {
+                step.check_not_finished(transaction_id)?;
+                self.transaction_event(
+                    &mut step,
+                    transaction_id,
+                    TwoPhaseCommitEvent::Start(value),
+                )?
+            }
+            MultiTwoPhaseCommitEvent::StartWithValues(transaction_id, values) => {
+                step.check_not_finished(transaction_id)?;
+                self.transaction_event(
+                    &mut step,
+                    transaction_id,
+                    TwoPhaseCommitEvent::StartWithValues(values),
+                )?
+            }
+
+            MultiTwoPhaseCommitEvent::Cancel(transaction_id) => {
+                step.check_in_flight(transaction_id)?;
+                self.transaction_event(&mut step, transaction_id, TwoPhaseCommitEvent::Cancel())?
+            }
+            MultiTwoPhaseCommitEvent::Vote(transaction_id, vote) => {
+                step.check_in_flight(transaction_id)?;
+                self.transaction_event(&mut step, transaction_id, TwoPhaseCommitEvent::Vote(vote))?
+            }
+            MultiTwoPhaseCommitEvent::VoteWithValue(transaction_id, value) => {
+                step.check_in_flight(transaction_id)?;
+                self.transaction_event(
+                    &mut step,
+                    transaction_id,
+                    TwoPhaseCommitEvent::VoteWithValue(value),
+                )?
+            }
+            MultiTwoPhaseCommitEvent::ReadOnlyVote(transaction_id) => {
+                step.check_in_flight(transaction_id)?;
+                self.transaction_event(
+                    &mut step,
+                    transaction_id,
+                    TwoPhaseCommitEvent::ReadOnlyVote(),
+                )?
+            }
+        }
+
+        Ok(step.actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+
+    use crate::two_phase_commit::test_support::{Network, TestTimeSource};
+
+    use crate::two_phase_commit::{
+        Participant, Presumption, TwoPhaseCommitConfigBuilder, TwoPhaseCommitContextBuilder,
+        TwoPhaseCommitState,
+    };
+
+    type Message = TwoPhaseCommitMessage<String, u32>;
+
+    // A group with the coordinator "c" and the participants "a" and "b".
+    type Group = Network<MultiTwoPhaseCommitAlgorithm<String, u32, TestTimeSource>>;
+
+    fn group(decision_history_size: usize) -> Group {
+        // With presumed abort, the coordinator does not wait for the abort to be acked.
+        let config = TwoPhaseCommitConfigBuilder::new()
+            .with_presumption(Presumption::Abort)
+            .with_decision_history_size(decision_history_size)
+            .build()
+            .unwrap();
+        let coordinator = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(1)
+            .with_state(TwoPhaseCommitState::WaitingForStart)
+            .with_this_process("c".to_string())
+            .with_participants(vec![
+                Participant::new("a".to_string()),
+                Participant::new("b".to_string()),
+            ])
+            .build()
+            .unwrap();
+        let participant = |process: &str| {
+            TwoPhaseCommitContextBuilder::new()
+                .with_coordinator("c".to_string())
+                .with_epoch(0)
+                .with_state(TwoPhaseCommitState::WaitingForVoteRequest)
+                .with_this_process(process.to_string())
+                .with_participant_processes(vec!["a".to_string(), "b".to_string()])
+                .build()
+                .unwrap()
+        };
+
+        let mut contexts = HashMap::new();
+        for (process, template) in [
+            ("c", coordinator),
+            ("a", participant("a")),
+            ("b", participant("b")),
+        ] {
+            contexts.insert(
+                process.to_string(),
+                MultiTwoPhaseCommitContext::new(template).unwrap(),
+            );
+        }
+
+        let time = TestTimeSource::new();
+        Network::new(
+            MultiTwoPhaseCommitAlgorithm::with_config(time.clone(), config),
+            &time,
+            contexts,
+        )
+    }
+
+    /// Test that two transactions run at the same time are decided independently, that the
+    /// context of each is dropped once it has finished, and that a late DecisionRequest is
+    /// answered from the decision log.
+    #[test]
+    fn multi_concurrent_transactions() {
+        let mut group = group(*TwoPhaseCommitConfig::default().decision_history_size());
+
+        group.event("c", MultiTwoPhaseCommitEvent::Start(1, 7));
+        group.event("c", MultiTwoPhaseCommitEvent::Start(2, 8));
+        assert_eq!(group.contexts["c"].transaction_ids(), vec![1, 2]);
+        group.deliver_all(&[]);
+        assert_eq!(group.contexts["a"].transaction_ids(), vec![1, 2]);
+
+        group.event("b", MultiTwoPhaseCommitEvent::Vote(2, true));
+        group.event("b", MultiTwoPhaseCommitEvent::Vote(1, true));
+        group.event("a", MultiTwoPhaseCommitEvent::Vote(2, false));
+        group.event("a", MultiTwoPhaseCommitEvent::Vote(1, true));
+        group.deliver_all(&[]);
+        group.event("c", MultiTwoPhaseCommitEvent::Vote(1, true));
+        group.deliver_all(&[]);
+
+        for process in ["c", "a", "b"] {
+            let context = &group.contexts[process];
+            assert!(context.transaction_ids().is_empty());
+            assert_eq!(context.decision(1), Some(Decision::Commit));
+            assert_eq!(context.decision(2), Some(Decision::Abort));
+        }
+
+        let actions = group
+            .algorithm
+            .event(
+                MultiTwoPhaseCommitEvent::Deliver("a".into(), 1, Message::DecisionRequest(1)),
+                group.contexts["c"].clone(),
+            )
+            .unwrap();
+        assert_eq!(
+            actions,
+            vec![MultiTwoPhaseCommitAction::SendMessage(
+                "a".into(),
+                1,
+                Message::Commit(0, 1)
+            )]
+        );
+
+        assert!(group
+            .algorithm
+            .event(
+                MultiTwoPhaseCommitEvent::Start(1, 9),
+                group.contexts["c"].clone()
+            )
+            .is_err());
+    }
+
+    /// Test that once a transaction has been pruned from the decision log, it can not be started
+    /// again, and a late VoteRequest for it is dropped rather than starting a new vote.
+    #[test]
+    fn multi_rejects_pruned_transactions() {
+        let mut group = group(1);
+
+        for transaction_id in [1, 2] {
+            group.event("c", MultiTwoPhaseCommitEvent::Start(transaction_id, 7));
+            group.deliver_all(&[]);
+            group.event("a", MultiTwoPhaseCommitEvent::Vote(transaction_id, true));
+            group.event("b", MultiTwoPhaseCommitEvent::Vote(transaction_id, true));
+            group.deliver_all(&[]);
+            group.event("c", MultiTwoPhaseCommitEvent::Vote(transaction_id, true));
+            group.deliver_all(&[]);
+        }
+
+        for process in ["c", "a", "b"] {
+            assert_eq!(
+                group.contexts[process].decision_log(),
+                &vec![(2, Decision::Commit)]
+            );
+        }
+
+        assert!(group
+            .algorithm
+            .event(
+                MultiTwoPhaseCommitEvent::Start(1, 9),
+                group.contexts["c"].clone()
+            )
+            .is_err());
+
+        let actions = group
+            .algorithm
+            .event(
+                MultiTwoPhaseCommitEvent::Deliver(
+                    "c".into(),
+                    1,
+                    Message::VoteRequest(0, 1, 9, vec!["a".into(), "b".into()], vec![]),
+                ),
+                group.contexts["a"].clone(),
+            )
+            .unwrap();
+        assert!(matches!(
+            actions.as_slice(),
+            [MultiTwoPhaseCommitAction::Notify(
+                1,
+                TwoPhaseCommitActionNotification::MessageDropped(_)
+            )]
+        ));
+
+        // A new transaction with a higher id may still be started.
+        group.event("c", MultiTwoPhaseCommitEvent::Start(3, 7));
+        assert_eq!(group.contexts["c"].transaction_ids(), vec![3]);
+    }
+}
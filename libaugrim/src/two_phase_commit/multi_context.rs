@@ -0,0 +1,190 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::algorithm::Value;
+use crate::error::InvalidStateError;
+use crate::process::Process;
+use crate::time::Time;
+
+use super::Decision;
+use super::DecisionHistory;
+use super::TransactionId;
+use super::TwoPhaseCommitContext;
+use super::TwoPhaseCommitState;
+
+/// The context of a node running many two-phase commit transactions at once.
+///
+/// Each transaction in flight has its own context, created from the template context when the
+/// transaction is started or its first message is delivered. Once the transaction has finished,
+/// its context is dropped and its decision is kept in the decision log, which is shared by all
+/// transactions.
+#[derive(Clone, Debug, PartialEq)]
+pub struct MultiTwoPhaseCommitContext<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    pub(super) template: Box<TwoPhaseCommitContext<P, V, T>>,
+    // The transactions in flight, ordered by transaction id.
+    pub(super) transactions: Vec<Transaction<P, V, T>>,
+    pub(super) decision_log: DecisionHistory<TransactionId, Decision>,
+}
+
+// A transaction in flight, with the alarm of its context.
+#[derive(Clone, Debug, PartialEq)]
+pub(super) struct Transaction<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    pub(super) id: TransactionId,
+    pub(super) context: TwoPhaseCommitContext<P, V, T>,
+    pub(super) alarm: Option<T>,
+}
+
+impl<P, V, T> MultiTwoPhaseCommitContext<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    /// Create the context of a node from a template context, which gives the role of the node
+    /// and the processes taking part in each transaction.
+    ///
+    /// The template must not have an epoch in flight, so it must be in the WaitingForStart,
+    /// WaitingForVoteRequest or Observing state.
+    pub fn new(template: TwoPhaseCommitContext<P, V, T>) -> Result<Self, InvalidStateError> {
+        if !is_finished(&template) {
+            return Err(InvalidStateError::with_message(format!(
+                "template context must not have an epoch in flight: {:?}",
+                template.state()
+            )));
+        }
+
+        Ok(Self {
+            template: Box::new(template),
+            transactions: Vec::new(),
+            decision_log: DecisionHistory::new(),
+        })
+    }
+
+    /// The context which the context of each new transaction is created from.
+    pub fn template(&self) -> &TwoPhaseCommitContext<P, V, T> {
+        &self.template
+    }
+
+    /// The context of the given transaction, if it is in flight.
+    pub fn transaction(
+        &self,
+        transaction_id: TransactionId,
+    ) -> Option<&TwoPhaseCommitContext<P, V, T>> {
+        self.index(transaction_id)
+            .ok()
+            .map(|index| &self.transactions[index].context)
+    }
+
+    /// The ids of the transactions in flight, in ascending order.
+    pub fn transaction_ids(&self) -> Vec<TransactionId> {
+        self.transactions
+            .iter()
+            .map(|transaction| transaction.id)
+            .collect()
+    }
+
+    /// The decisions of recently finished transactions, ordered by transaction id.
+    pub fn decision_log(&self) -> &Vec<(TransactionId, Decision)> {
+        self.decision_log.decisions()
+    }
+
+    /// The decision of the given transaction, if it is in the decision log.
+    pub fn decision(&self, transaction_id: TransactionId) -> Option<Decision> {
+        self.decision_log.get(transaction_id).copied()
+    }
+
+    // Returns true if the transaction is not in flight and its id is at or below the highest
+    // transaction id pruned from the decision log. Such a transaction may have finished and been
+    // forgotten, so it must not be started again.
+    pub(super) fn is_forgotten(&self, transaction_id: TransactionId) -> bool {
+        self.transaction(transaction_id).is_none() && self.decision_log.is_pruned(transaction_id)
+    }
+
+    // Adds a decision to the decision log, then prunes the log to at most `history_size`
+    // decisions by removing the decisions of the lowest transaction ids.
+    pub(super) fn record_decision(
+        &mut self,
+        transaction_id: TransactionId,
+        decision: Decision,
+        history_size: usize,
+    ) {
+        self.decision_log
+            .record(transaction_id, decision, history_size);
+    }
+
+    // Stores the context of a transaction, or drops it if the transaction has finished.
+    pub(super) fn update_transaction(
+        &mut self,
+        transaction_id: TransactionId,
+        context: TwoPhaseCommitContext<P, V, T>,
+        alarm: Option<T>,
+    ) {
+        let finished = is_finished(&context);
+        let transaction = Transaction {
+            id: transaction_id,
+            context,
+            alarm,
+        };
+
+        match (self.index(transaction_id), finished) {
+            (Ok(index), true) => {
+                self.transactions.remove(index);
+            }
+            (Ok(index), false) => self.transactions[index] = transaction,
+            (Err(_), true) => (),
+            (Err(index), false) => self.transactions.insert(index, transaction),
+        }
+    }
+
+    // Returns the earliest alarm of the transactions in flight.
+    pub(super) fn alarm(&self) -> Option<T> {
+        self.transactions
+            .iter()
+            .filter_map(|transaction| transaction.alarm)
+            .fold(None, |earliest, alarm| match earliest {
+                Some(earliest) if earliest <= alarm => Some(earliest),
+                _ => Some(alarm),
+            })
+    }
+
+    fn index(&self, transaction_id: TransactionId) -> Result<usize, usize> {
+        self.transactions
+            .binary_search_by_key(&transaction_id, |transaction| transaction.id)
+    }
+}
+
+// Returns true if the context has no epoch in flight.
+fn is_finished<P, V, T>(context: &TwoPhaseCommitContext<P, V, T>) -> bool
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    matches!(
+        context.state(),
+        TwoPhaseCommitState::WaitingForStart
+            | TwoPhaseCommitState::WaitingForVoteRequest
+            | TwoPhaseCommitState::Observing
+    )
+}
@@ -0,0 +1,41 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::algorithm::Value;
+use crate::process::Process;
+
+use super::TransactionId;
+use super::TwoPhaseCommitMessage;
+
+/// An event of a node running many two-phase commit transactions at once.
+///
+/// Other than Alarm and Recover, which apply to every transaction in flight, each event is for
+/// the transaction with the given id.
+pub enum MultiTwoPhaseCommitEvent<P, V>
+where
+    P: Process,
+    V: Value,
+{
+    Alarm(),
+    Recover(),
+    Deliver(P, TransactionId, TwoPhaseCommitMessage<P, V>),
+    Start(TransactionId, V),
+    /// Starts the transaction with a value for each participant, in the order of the participants
+    /// of the context template.
+    StartWithValues(TransactionId, Vec<(P, V)>),
+    Cancel(TransactionId),
+    Vote(TransactionId, bool),
+    VoteWithValue(TransactionId, V),
+    ReadOnlyVote(TransactionId),
+}
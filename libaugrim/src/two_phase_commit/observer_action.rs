@@ -0,0 +1,85 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::algorithm::Value;
+use crate::process::Process;
+use crate::time::Time;
+
+use super::Epoch;
+use super::ObserverContext;
+use super::TwoPhaseCommitContext;
+use super::TwoPhaseCommitMessage;
+use super::{TwoPhaseCommitAction, TwoPhaseCommitActionNotification};
+
+pub enum ObserverAction<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    Notify(ObserverActionNotification<V>),
+    SendMessage(P, TwoPhaseCommitMessage<P, V>),
+    Update {
+        context: TwoPhaseCommitContext<P, V, T, ObserverContext>,
+        alarm: Option<T>,
+    },
+}
+
+pub enum ObserverActionNotification<V> {
+    Abort(Epoch, Option<V>),
+    Commit(Epoch, Option<V>),
+    BatchDecision(Epoch, Vec<V>, Vec<V>),
+    MessageDropped(String),
+}
+
+impl<P, V, T> From<ObserverAction<P, V, T>> for TwoPhaseCommitAction<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    fn from(action: ObserverAction<P, V, T>) -> Self {
+        match action {
+            ObserverAction::Update { context, alarm } => TwoPhaseCommitAction::Update {
+                context: context.into(),
+                alarm,
+            },
+            ObserverAction::SendMessage(p, m) => TwoPhaseCommitAction::SendMessage(p, m),
+            ObserverAction::Notify(n) => TwoPhaseCommitAction::Notify(n.into()),
+        }
+    }
+}
+
+impl<P, V> From<ObserverActionNotification<V>> for TwoPhaseCommitActionNotification<P, V>
+where
+    P: Process,
+    V: Value,
+{
+    fn from(notification: ObserverActionNotification<V>) -> Self {
+        match notification {
+            ObserverActionNotification::Abort(epoch, value) => {
+                TwoPhaseCommitActionNotification::Abort(epoch, value)
+            }
+            ObserverActionNotification::Commit(epoch, value) => {
+                TwoPhaseCommitActionNotification::Commit(epoch, value)
+            }
+            ObserverActionNotification::BatchDecision(epoch, committed, aborted) => {
+                TwoPhaseCommitActionNotification::BatchDecision(epoch, committed, aborted)
+            }
+            ObserverActionNotification::MessageDropped(s) => {
+                TwoPhaseCommitActionNotification::MessageDropped(s)
+            }
+        }
+    }
+}
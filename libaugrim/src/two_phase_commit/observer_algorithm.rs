@@ -0,0 +1,227 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::marker::PhantomData;
+
+use crate::algorithm::{Algorithm, Value};
+use crate::error::AlgorithmError;
+use crate::process::Process;
+use crate::time::TimeSource;
+
+use super::Decision;
+use super::Epoch;
+use super::ObserverAction;
+use super::ObserverActionNotification;
+use super::ObserverContext;
+use super::ObserverEvent;
+use super::ObserverMessage;
+use super::TwoPhaseCommitConfig;
+use super::TwoPhaseCommitContext;
+
+/// The algorithm for an observer, which follows the decisions of the coordinator without voting.
+///
+/// The coordinator sends an Outcome message with the value and decision of each epoch, or a
+/// BatchOutcome message with the items of a batch epoch and whether each was committed, to its
+/// observers once it has decided, so observers do not add to the time taken to decide. An
+/// observer does not acknowledge the outcome. The decisions are kept in the decision history, so
+/// that an observer can answer a DecisionRequest like a participant does.
+pub struct ObserverAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource,
+{
+    _process_phantom: PhantomData<P>,
+    _value_phantom: PhantomData<V>,
+    _time_source_phantom: PhantomData<TS>,
+    config: TwoPhaseCommitConfig,
+}
+
+impl<P, V, TS> ObserverAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource,
+{
+    pub fn new(config: TwoPhaseCommitConfig) -> Self {
+        ObserverAlgorithm {
+            _process_phantom: PhantomData,
+            _value_phantom: PhantomData,
+            _time_source_phantom: PhantomData,
+            config,
+        }
+    }
+
+    // Record the outcome of an epoch sent by the coordinator. The value or the batch of the epoch
+    // has been set in the context, and is taken for the decision notification. For a batch epoch,
+    // committed_items holds whether each item of the batch was committed.
+    fn handle_outcome(
+        &self,
+        process: P,
+        epoch: Epoch,
+        decision: Decision,
+        committed_items: Option<Vec<bool>>,
+        mut context: TwoPhaseCommitContext<P, V, TS::Time, ObserverContext>,
+    ) -> Result<Vec<ObserverAction<P, V, TS::Time>>, AlgorithmError> {
+        // An Outcome must come from the coordinator, drop it otherwise.
+        if *context.coordinator() != process {
+            return Ok(vec![ObserverAction::Notify(
+                ObserverActionNotification::MessageDropped(
+                    "sender process is not the coordinator".into(),
+                ),
+            )]);
+        }
+
+        // The coordinator may send the outcome again when it recovers, so an outcome which is not
+        // for a later epoch is dropped.
+        if *context.epoch() >= epoch {
+            return Ok(vec![ObserverAction::Notify(
+                ObserverActionNotification::MessageDropped(format!(
+                    "epoch {} was not greater than previous epoch {}",
+                    epoch,
+                    *context.epoch()
+                )),
+            )]);
+        }
+
+        let mut actions = Vec::new();
+
+        // Record the decision. Also update the last commit epoch and the decision history used to
+        // answer DecisionRequest messages.
+        context.set_epoch(epoch);
+        if decision == Decision::Commit {
+            context.set_last_commit_epoch(Some(epoch));
+        }
+        context.record_decision(epoch, decision, self.config.decision_history_size);
+        if let (Decision::Commit, Some(committed_items)) = (decision, committed_items) {
+            context.record_batch_decision(
+                epoch,
+                committed_items,
+                self.config.decision_history_size,
+            );
+        }
+
+        let value = context.take_value();
+        let notification = if !context.batch().is_empty() {
+            let (committed_items, aborted_items) = context.take_batch_decision(epoch);
+            ObserverActionNotification::BatchDecision(epoch, committed_items, aborted_items)
+        } else {
+            match decision {
+                Decision::Commit => ObserverActionNotification::Commit(epoch, value),
+                Decision::Abort => ObserverActionNotification::Abort(epoch, value),
+            }
+        };
+
+        actions.push(ObserverAction::Update {
+            context,
+            alarm: None,
+        });
+        actions.push(ObserverAction::Notify(notification));
+
+        Ok(actions)
+    }
+}
+
+impl<P, V, TS> Algorithm for ObserverAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource,
+{
+    type Event = ObserverEvent<P, V>;
+    type Action = ObserverAction<P, V, TS::Time>;
+    type Context = TwoPhaseCommitContext<P, V, TS::Time, ObserverContext>;
+
+    fn event(
+        &self,
+        event: Self::Event,
+        mut context: Self::Context,
+    ) -> Result<Vec<Self::Action>, AlgorithmError> {
+        match event {
+            // An observer does not wait on anything, so there is nothing to recover.
+            ObserverEvent::Recover() => Ok(vec![]),
+
+            ObserverEvent::Deliver(process, ObserverMessage::Outcome(epoch, value, decision)) => {
+                context.set_value(value);
+                self.handle_outcome(process, epoch, decision, None, context)
+            }
+
+            // A batch epoch is committed if any of its items was committed.
+            ObserverEvent::Deliver(
+                process,
+                ObserverMessage::BatchOutcome(epoch, batch, committed_items),
+            ) => {
+                let decision = if committed_items.contains(&true) {
+                    Decision::Commit
+                } else {
+                    Decision::Abort
+                };
+                context.set_batch(batch);
+                self.handle_outcome(process, epoch, decision, Some(committed_items), context)
+            }
+
+            // A standby coordinator which has taken over sends NewTerm to the observers as well,
+            // so that its outcomes are accepted. Unlike a participant, an observer has no vote to
+            // report, so it does not acknowledge the new term.
+            ObserverEvent::Deliver(process, ObserverMessage::NewTerm(term)) => {
+                // Only a greater term may replace the coordinator. The current coordinator may
+                // send NewTerm again if it did not receive the acks of the participants.
+                if term < *context.term()
+                    || (term == *context.term() && *context.coordinator() != process)
+                {
+                    return Ok(vec![ObserverAction::Notify(
+                        ObserverActionNotification::MessageDropped(format!(
+                            "term {} is not greater than the current term {}",
+                            term,
+                            context.term()
+                        )),
+                    )]);
+                }
+
+                context.set_term(term);
+                context.set_coordinator(process);
+                Ok(vec![ObserverAction::Update {
+                    context,
+                    alarm: None,
+                }])
+            }
+
+            // A DecisionRequest is answered if the decision is known. As an observer does not
+            // know the participants, a request from any process is answered.
+            ObserverEvent::Deliver(process, ObserverMessage::DecisionRequest(epoch)) => {
+                let decision = match context.decision(epoch) {
+                    Some(decision) => Some(decision),
+                    None if Some(epoch) == *context.last_commit_epoch() => Some(Decision::Commit),
+                    None => None,
+                };
+
+                match decision {
+                    Some(Decision::Commit) => Ok(vec![ObserverAction::SendMessage(
+                        process,
+                        context.commit_message(epoch),
+                    )]),
+                    Some(Decision::Abort) => Ok(vec![ObserverAction::SendMessage(
+                        process,
+                        context.abort_message(epoch),
+                    )]),
+                    None => Ok(vec![ObserverAction::Notify(
+                        ObserverActionNotification::MessageDropped(
+                            "decision for requested epoch is unknown".into(),
+                        ),
+                    )]),
+                }
+            }
+        }
+    }
+}
@@ -0,0 +1,77 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::algorithm::Value;
+use crate::error::InvalidStateError;
+use crate::process::Process;
+
+use super::ObserverMessage;
+use super::TwoPhaseCommitEvent;
+
+pub enum ObserverEvent<P, V>
+where
+    P: Process,
+    V: Value,
+{
+    Recover(),
+    Deliver(P, ObserverMessage<V>),
+}
+
+impl<P, V> TryFrom<TwoPhaseCommitEvent<P, V>> for ObserverEvent<P, V>
+where
+    P: Process,
+    V: Value,
+{
+    type Error = InvalidStateError;
+
+    fn try_from(event: TwoPhaseCommitEvent<P, V>) -> Result<Self, Self::Error> {
+        match event {
+            TwoPhaseCommitEvent::Recover() => Ok(ObserverEvent::Recover()),
+            TwoPhaseCommitEvent::Deliver(p, m) => Ok(ObserverEvent::Deliver(p, m.try_into()?)),
+            TwoPhaseCommitEvent::Alarm() => Err(InvalidStateError::with_message(
+                "Alarm event can not be handled by an observer".into(),
+            )),
+            TwoPhaseCommitEvent::Start(_) => Err(InvalidStateError::with_message(
+                "Start event can not be handled by an observer".into(),
+            )),
+            TwoPhaseCommitEvent::StartWithValues(_) => Err(InvalidStateError::with_message(
+                "StartWithValues event can not be handled by an observer".into(),
+            )),
+            TwoPhaseCommitEvent::StartBatch(_) => Err(InvalidStateError::with_message(
+                "StartBatch event can not be handled by an observer".into(),
+            )),
+            TwoPhaseCommitEvent::Cancel() => Err(InvalidStateError::with_message(
+                "Cancel event can not be handled by an observer".into(),
+            )),
+            TwoPhaseCommitEvent::Reconfigure(_) => Err(InvalidStateError::with_message(
+                "Reconfigure event can not be handled by an observer".into(),
+            )),
+            TwoPhaseCommitEvent::TakeOver(_) => Err(InvalidStateError::with_message(
+                "TakeOver event can not be handled by an observer".into(),
+            )),
+            TwoPhaseCommitEvent::Vote(_) => Err(InvalidStateError::with_message(
+                "Vote event can not be handled by an observer".into(),
+            )),
+            TwoPhaseCommitEvent::VoteWithValue(_) => Err(InvalidStateError::with_message(
+                "VoteWithValue event can not be handled by an observer".into(),
+            )),
+            TwoPhaseCommitEvent::BatchVote(_) => Err(InvalidStateError::with_message(
+                "BatchVote event can not be handled by an observer".into(),
+            )),
+            TwoPhaseCommitEvent::ReadOnlyVote() => Err(InvalidStateError::with_message(
+                "ReadOnlyVote event can not be handled by an observer".into(),
+            )),
+        }
+    }
+}
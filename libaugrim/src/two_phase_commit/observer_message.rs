@@ -0,0 +1,120 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Contains ObserverMessage, a message which is delivered to an observer.
+
+use std::convert::TryFrom;
+
+use crate::algorithm::Value;
+use crate::error::InvalidStateError;
+use crate::process::Process;
+
+use super::Decision;
+use super::Epoch;
+use super::Term;
+use super::TwoPhaseCommitMessage;
+
+/// A message which is delivered to an observer.
+///
+/// This is a subset of `TwoPhaseCommitMessage`, containing only the set of messages which can be
+/// delivered to an observer.
+#[derive(Clone)]
+pub enum ObserverMessage<V>
+where
+    V: Value,
+{
+    Outcome(Epoch, Option<V>, Decision),
+    DecisionRequest(Epoch),
+    BatchOutcome(Epoch, Vec<V>, Vec<bool>),
+    NewTerm(Term),
+}
+
+impl<P, V> From<ObserverMessage<V>> for TwoPhaseCommitMessage<P, V>
+where
+    P: Process,
+    V: Value,
+{
+    fn from(message: ObserverMessage<V>) -> Self {
+        match message {
+            ObserverMessage::Outcome(epoch, value, decision) => {
+                TwoPhaseCommitMessage::Outcome(epoch, value, decision)
+            }
+            ObserverMessage::DecisionRequest(epoch) => {
+                TwoPhaseCommitMessage::DecisionRequest(epoch)
+            }
+            ObserverMessage::BatchOutcome(epoch, batch, committed) => {
+                TwoPhaseCommitMessage::BatchOutcome(epoch, batch, committed)
+            }
+            ObserverMessage::NewTerm(term) => TwoPhaseCommitMessage::NewTerm(term),
+        }
+    }
+}
+
+impl<P, V> TryFrom<TwoPhaseCommitMessage<P, V>> for ObserverMessage<V>
+where
+    P: Process,
+    V: Value,
+{
+    type Error = InvalidStateError;
+
+    fn try_from(message: TwoPhaseCommitMessage<P, V>) -> Result<Self, Self::Error> {
+        match message {
+            TwoPhaseCommitMessage::Outcome(epoch, value, decision) => {
+                Ok(ObserverMessage::Outcome(epoch, value, decision))
+            }
+            TwoPhaseCommitMessage::DecisionRequest(epoch) => {
+                Ok(ObserverMessage::DecisionRequest(epoch))
+            }
+            TwoPhaseCommitMessage::BatchOutcome(epoch, batch, committed) => {
+                Ok(ObserverMessage::BatchOutcome(epoch, batch, committed))
+            }
+            TwoPhaseCommitMessage::NewTerm(term) => Ok(ObserverMessage::NewTerm(term)),
+            TwoPhaseCommitMessage::VoteRequest(..) => Err(InvalidStateError::with_message(
+                "VoteRequest message cannot be handled by an observer".into(),
+            )),
+            TwoPhaseCommitMessage::VoteResponse(_, _) => Err(InvalidStateError::with_message(
+                "VoteResponse message cannot be handled by an observer".into(),
+            )),
+            TwoPhaseCommitMessage::VoteResponseWithValue(_, _) => {
+                Err(InvalidStateError::with_message(
+                    "VoteResponseWithValue message cannot be handled by an observer".into(),
+                ))
+            }
+            TwoPhaseCommitMessage::ReadOnlyVoteResponse(_) => Err(InvalidStateError::with_message(
+                "ReadOnlyVoteResponse message cannot be handled by an observer".into(),
+            )),
+            TwoPhaseCommitMessage::Commit(..) => Err(InvalidStateError::with_message(
+                "Commit message cannot be handled by an observer".into(),
+            )),
+            TwoPhaseCommitMessage::Abort(..) => Err(InvalidStateError::with_message(
+                "Abort message cannot be handled by an observer".into(),
+            )),
+            TwoPhaseCommitMessage::DecisionAck(_) => Err(InvalidStateError::with_message(
+                "DecisionAck message cannot be handled by an observer".into(),
+            )),
+            TwoPhaseCommitMessage::NewTermAck(_, _, _) => Err(InvalidStateError::with_message(
+                "NewTermAck message cannot be handled by an observer".into(),
+            )),
+            TwoPhaseCommitMessage::BatchVoteRequest(..) => Err(InvalidStateError::with_message(
+                "BatchVoteRequest message cannot be handled by an observer".into(),
+            )),
+            TwoPhaseCommitMessage::BatchVoteResponse(_, _) => Err(InvalidStateError::with_message(
+                "BatchVoteResponse message cannot be handled by an observer".into(),
+            )),
+            TwoPhaseCommitMessage::BatchCommit(..) => Err(InvalidStateError::with_message(
+                "BatchCommit message cannot be handled by an observer".into(),
+            )),
+        }
+    }
+}
@@ -16,6 +16,7 @@ use crate::algorithm::Value;
 use crate::process::Process;
 use crate::time::Time;
 
+use super::Epoch;
 use super::ParticipantContext;
 use super::TwoPhaseCommitContext;
 use super::TwoPhaseCommitMessage;
@@ -28,18 +29,21 @@ where
     T: Time,
 {
     Notify(ParticipantActionNotification<V>),
-    SendMessage(P, TwoPhaseCommitMessage<V>),
+    SendMessage(P, TwoPhaseCommitMessage<P, V>),
     Update {
-        context: TwoPhaseCommitContext<P, T, ParticipantContext<P, T>>,
+        context: TwoPhaseCommitContext<P, V, T, ParticipantContext<P, T>>,
         alarm: Option<T>,
     },
 }
 
 pub enum ParticipantActionNotification<V> {
-    Abort(),
-    Commit(),
+    Abort(Epoch, Option<V>),
+    Commit(Epoch, Option<V>),
     MessageDropped(String),
     RequestForVote(V),
+    RequestForBatchVote(Vec<V>),
+    BatchDecision(Epoch, Vec<V>, Vec<V>),
+    Blocked(Epoch),
 }
 
 impl<P, V, T> From<ParticipantAction<P, V, T>> for TwoPhaseCommitAction<P, V, T>
@@ -60,20 +64,34 @@ where
     }
 }
 
-impl<V> From<ParticipantActionNotification<V>> for TwoPhaseCommitActionNotification<V>
+impl<P, V> From<ParticipantActionNotification<V>> for TwoPhaseCommitActionNotification<P, V>
 where
+    P: Process,
     V: Value,
 {
     fn from(notification: ParticipantActionNotification<V>) -> Self {
         match notification {
-            ParticipantActionNotification::Abort() => TwoPhaseCommitActionNotification::Abort(),
-            ParticipantActionNotification::Commit() => TwoPhaseCommitActionNotification::Commit(),
+            ParticipantActionNotification::Abort(epoch, value) => {
+                TwoPhaseCommitActionNotification::Abort(epoch, value)
+            }
+            ParticipantActionNotification::Commit(epoch, value) => {
+                TwoPhaseCommitActionNotification::Commit(epoch, value)
+            }
             ParticipantActionNotification::MessageDropped(s) => {
                 TwoPhaseCommitActionNotification::MessageDropped(s)
             }
             ParticipantActionNotification::RequestForVote(v) => {
                 TwoPhaseCommitActionNotification::ParticipantRequestForVote(v)
             }
+            ParticipantActionNotification::RequestForBatchVote(values) => {
+                TwoPhaseCommitActionNotification::ParticipantRequestForBatchVote(values)
+            }
+            ParticipantActionNotification::BatchDecision(epoch, committed, aborted) => {
+                TwoPhaseCommitActionNotification::BatchDecision(epoch, committed, aborted)
+            }
+            ParticipantActionNotification::Blocked(epoch) => {
+                TwoPhaseCommitActionNotification::ParticipantBlocked(epoch)
+            }
         }
     }
 }
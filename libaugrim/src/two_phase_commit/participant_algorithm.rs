@@ -21,17 +21,18 @@ use crate::process::Process;
 use crate::time::TimeSource;
 use crate::two_phase_commit::Epoch;
 
+use super::Decision;
 use super::ParticipantAction;
 use super::ParticipantActionNotification;
 use super::ParticipantContext;
 use super::ParticipantEvent;
 use super::ParticipantMessage;
 use super::ParticipantState;
+use super::Presumption;
+use super::TwoPhaseCommitConfig;
 use super::TwoPhaseCommitContext;
 use super::TwoPhaseCommitMessage;
 
-const DECISION_TIMEOUT_SECONDS: u64 = 30;
-
 pub struct ParticipantAlgorithm<P, V, TS>
 where
     P: Process,
@@ -40,6 +41,7 @@ where
 {
     _process_phantom: PhantomData<P>,
     _value_phantom: PhantomData<V>,
+    config: TwoPhaseCommitConfig,
     time_source: TS,
 }
 
@@ -49,10 +51,11 @@ where
     V: Value,
     TS: TimeSource,
 {
-    pub fn new(time_source: TS) -> Self {
+    pub fn new(time_source: TS, config: TwoPhaseCommitConfig) -> Self {
         ParticipantAlgorithm {
             _process_phantom: PhantomData,
             _value_phantom: PhantomData,
+            config,
             time_source,
         }
     }
@@ -61,19 +64,559 @@ where
     // a decision has been reached, either abort or commit.
     fn push_advance_epoch_actions(
         &self,
-        context: &mut TwoPhaseCommitContext<P, TS::Time, ParticipantContext<P, TS::Time>>,
+        context: &mut TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
         actions: &mut Vec<ParticipantAction<P, V, TS::Time>>,
         epoch: Epoch,
     ) {
-        // Update the epoch and set the state to WaitingForStart. Also update the last commit epoch
-        // used to answer DecisionRequest messages.
-        context.set_last_commit_epoch(Some(*context.epoch()));
+        // Update the epoch.
         context.set_epoch(epoch);
         actions.push(ParticipantAction::Update {
             context: context.clone(),
             alarm: None,
         });
     }
+
+    // Create actions for voting yes, sending the given vote response to the coordinator.
+    fn push_vote_yes_actions(
+        &self,
+        mut context: TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+        actions: &mut Vec<ParticipantAction<P, V, TS::Time>>,
+        vote_response: TwoPhaseCommitMessage<P, V>,
+    ) {
+        // A timeout will occur after the configured decision timeout, starting now. An alarm is
+        // set for the end of the timeout and the timeout is processed when an
+        // `ParticipantEvent::Alarm` is received.
+        let decision_timeout_start = self.time_source.now();
+        let decision_timeout_end = decision_timeout_start + self.config.decision_timeout;
+
+        // Record the vote and update the state to Voted. No DecisionRequest has been sent yet.
+        context.set_decision_requests(0);
+        context.set_state(ParticipantState::Voted {
+            vote: true,
+            decision_timeout_start,
+        });
+        actions.push(ParticipantAction::Update {
+            context: context.clone(),
+            alarm: Some(decision_timeout_end),
+        });
+
+        // Send the vote to the coordinator.
+        actions.push(ParticipantAction::SendMessage(
+            context.coordinator().clone(),
+            vote_response,
+        ));
+
+        // In decentralized mode, also send the vote to the other participants, and decide if
+        // their votes have already been received.
+        if self.config.decentralized {
+            self.push_peer_vote_actions(&context, actions, true);
+
+            if let Some(decision) = Self::peer_decision(&context) {
+                self.push_peer_decision_actions(context, actions, decision);
+            }
+        }
+    }
+
+    // Create actions for sending our vote to all other participants in decentralized mode.
+    fn push_peer_vote_actions(
+        &self,
+        context: &TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+        actions: &mut Vec<ParticipantAction<P, V, TS::Time>>,
+        vote: bool,
+    ) {
+        for process in context
+            .participant_processes()
+            .iter()
+            .filter(|p| *p != context.this_process())
+        {
+            actions.push(ParticipantAction::SendMessage(
+                process.clone(),
+                TwoPhaseCommitMessage::VoteResponse(*context.epoch(), vote),
+            ));
+        }
+    }
+
+    // Returns the decision of the current epoch in decentralized mode, if it can be made from the
+    // votes of the other participants. This is only used once we have voted yes.
+    fn peer_decision(
+        context: &TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+    ) -> Option<Decision> {
+        let epoch = *context.epoch();
+        let vote = |process: &P| {
+            context
+                .peer_votes()
+                .iter()
+                .find(|(p, e, _)| p == process && *e == epoch)
+                .map(|(_, _, vote)| *vote)
+        };
+
+        let mut votes = context
+            .participant_processes()
+            .iter()
+            .filter(|p| *p != context.this_process())
+            .map(vote);
+
+        if votes.clone().any(|vote| vote == Some(false)) {
+            Some(Decision::Abort)
+        } else if votes.all(|vote| vote == Some(true)) {
+            Some(Decision::Commit)
+        } else {
+            None
+        }
+    }
+
+    // Create actions for a decision made from the votes of all participants in decentralized
+    // mode. The coordinator does not send the decision, so it is not acknowledged.
+    fn push_peer_decision_actions(
+        &self,
+        mut context: TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+        actions: &mut Vec<ParticipantAction<P, V, TS::Time>>,
+        decision: Decision,
+    ) {
+        let epoch = *context.epoch();
+
+        // Record the decision, and drop the votes of this epoch as they are no longer needed.
+        if decision == Decision::Commit {
+            context.set_last_commit_epoch(Some(epoch));
+        }
+        context.record_decision(epoch, decision, self.config.decision_history_size);
+        context
+            .peer_votes_mut()
+            .retain(|(_, vote_epoch, _)| *vote_epoch > epoch);
+        context.set_state(match decision {
+            Decision::Commit => ParticipantState::Commit,
+            Decision::Abort => ParticipantState::Abort,
+        });
+        actions.push(ParticipantAction::Update {
+            context: context.clone(),
+            alarm: None,
+        });
+
+        // Notify the decision. The value is no longer needed once the epoch is decided.
+        actions.push(ParticipantAction::Notify(Self::decision_notification(
+            &mut context,
+            decision == Decision::Commit,
+        )));
+
+        // Switch to WaitingForVoteRequest to prepare for the next epoch
+        context.set_state(ParticipantState::WaitingForVoteRequest);
+        actions.push(ParticipantAction::Update {
+            context,
+            alarm: None,
+        });
+    }
+
+    // Returns the notification of the decision of the current epoch, taking the value of the
+    // epoch from the context. The decision of a batch epoch is notified with the committed and
+    // aborted items, which are also taken from the context.
+    fn decision_notification(
+        context: &mut TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+        committed: bool,
+    ) -> ParticipantActionNotification<V> {
+        let epoch = *context.epoch();
+        let value = context.take_value();
+        if !context.batch().is_empty() {
+            let (committed_items, aborted_items) = context.take_batch_decision(epoch);
+            ParticipantActionNotification::BatchDecision(epoch, committed_items, aborted_items)
+        } else if committed {
+            ParticipantActionNotification::Commit(epoch, value)
+        } else {
+            ParticipantActionNotification::Abort(epoch, value)
+        }
+    }
+
+    // Returns the alarm for the timeout of the current state, if any.
+    fn alarm(
+        &self,
+        context: &TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+    ) -> Option<TS::Time> {
+        match context.state() {
+            ParticipantState::WaitingForVote {
+                local_vote_timeout_start,
+            } => Some(*local_vote_timeout_start + self.config.local_vote_timeout),
+            ParticipantState::Voted {
+                decision_timeout_start,
+                ..
+            } if !self.blocked(context) => {
+                Some(*decision_timeout_start + self.decision_timeout(context))
+            }
+            _ => None,
+        }
+    }
+
+    // Returns the time to wait for a decision in the Voted state. After each round of
+    // DecisionRequest messages, the wait doubles, up to the configured maximum. If a random source
+    // is configured, the wait is then shortened by a random amount of up to half.
+    fn decision_timeout(
+        &self,
+        context: &TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+    ) -> Duration {
+        let decision_requests = *context.decision_requests();
+        if decision_requests == 0 {
+            return self.config.decision_timeout;
+        }
+
+        let max_timeout = self.config.decision_request_max_timeout;
+        let timeout = 2u32
+            .checked_pow(decision_requests)
+            .and_then(|factor| self.config.decision_timeout.checked_mul(factor))
+            .map_or(max_timeout, |timeout| timeout.min(max_timeout));
+
+        match &self.config.decision_request_random_source {
+            // The seed only depends on the context, so that the same wait is computed for the
+            // alarm and when the alarm is processed. Our position among the participants is mixed
+            // in, so that participants which share a random source still wait different times.
+            Some(random_source) => {
+                let position = context
+                    .participant_processes()
+                    .iter()
+                    .position(|process| process == context.this_process())
+                    .map_or(0, |position| u64::try_from(position).unwrap_or(u64::MAX));
+                let seed = context.epoch().rotate_left(32)
+                    ^ position.rotate_left(16)
+                    ^ u64::from(decision_requests);
+                let half = timeout / 2;
+                let range = u64::try_from(half.as_nanos()).unwrap_or(u64::MAX);
+                half + Duration::from_nanos(random_source.0.random(seed) % range.saturating_add(1))
+            }
+            None => timeout,
+        }
+    }
+
+    // Returns true if we have given up on the termination protocol after the configured number of
+    // rounds of DecisionRequest messages.
+    fn blocked(
+        &self,
+        context: &TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+    ) -> bool {
+        self.config
+            .decision_request_limit
+            .is_some_and(|limit| *context.decision_requests() > limit)
+    }
+
+    // Create actions for giving up on the termination protocol. We stay in the Voted state, as only
+    // the decision can end our uncertainty, but no longer set an alarm. Counting one more round
+    // than the limit marks us as blocked.
+    fn push_blocked_actions(
+        &self,
+        mut context: TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+        actions: &mut Vec<ParticipantAction<P, V, TS::Time>>,
+    ) {
+        context.set_decision_requests(context.decision_requests().saturating_add(1));
+        actions.push(ParticipantAction::Update {
+            context: context.clone(),
+            alarm: None,
+        });
+
+        actions.push(ParticipantAction::Notify(
+            ParticipantActionNotification::Blocked(*context.epoch()),
+        ));
+    }
+
+    // Create actions for the termination protocol. A DecisionRequest is sent to the coordinator,
+    // all other participants and the observers, and the decision timeout is restarted so that the
+    // requests are sent again if no decision is received. The timeout grows with each round of
+    // requests.
+    fn push_decision_request_actions(
+        &self,
+        mut context: TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+        actions: &mut Vec<ParticipantAction<P, V, TS::Time>>,
+        vote: bool,
+    ) {
+        // Send a Decision Request to all other participant processes and the observers, which
+        // learn the decision from the coordinator.
+        for process in context
+            .participant_processes()
+            .iter()
+            .filter(|p| *p != context.this_process())
+            .chain(context.observers())
+        {
+            actions.push(ParticipantAction::SendMessage(
+                process.clone(),
+                TwoPhaseCommitMessage::DecisionRequest(*context.epoch()),
+            ));
+        }
+
+        // Send a Decision Request to the coordinator
+        actions.push(ParticipantAction::SendMessage(
+            context.coordinator().clone(),
+            TwoPhaseCommitMessage::DecisionRequest(*context.epoch()),
+        ));
+
+        // Count the round of requests and calculate new decision timeout start/end.
+        context.set_decision_requests(context.decision_requests().saturating_add(1));
+        let decision_timeout_start = self.time_source.now();
+        let decision_timeout_end = decision_timeout_start + self.decision_timeout(&context);
+
+        // Updated the Voted state with the new timeout start value.
+        context.set_state(ParticipantState::Voted {
+            vote,
+            decision_timeout_start,
+        });
+        actions.push(ParticipantAction::Update {
+            context,
+            alarm: Some(decision_timeout_end),
+        });
+    }
+
+    // Create actions for voting no. This set of actions is generated when the application votes
+    // no, or when it fails to vote before the local vote timeout.
+    fn push_vote_no_actions(
+        &self,
+        mut context: TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+        actions: &mut Vec<ParticipantAction<P, V, TS::Time>>,
+    ) {
+        // The vote was no, so record our decision to Abort. Also update the decision history
+        // used to answer DecisionRequest messages.
+        context.record_decision(
+            *context.epoch(),
+            Decision::Abort,
+            self.config.decision_history_size,
+        );
+        context.set_state(ParticipantState::Abort);
+        actions.push(ParticipantAction::Update {
+            context: context.clone(),
+            alarm: None,
+        });
+
+        // Notify that we've aborted. The value is no longer needed once the epoch is decided.
+        actions.push(ParticipantAction::Notify(Self::decision_notification(
+            &mut context,
+            false,
+        )));
+
+        // Switch to WaitingForVoteRequest to prepare for the next epoch
+        context.set_state(ParticipantState::WaitingForVoteRequest);
+        actions.push(ParticipantAction::Update {
+            context: context.clone(),
+            alarm: None,
+        });
+
+        // Send the vote to the coordinator.
+        actions.push(ParticipantAction::SendMessage(
+            context.coordinator().clone(),
+            TwoPhaseCommitMessage::VoteResponse(*context.epoch(), false),
+        ));
+
+        // In decentralized mode, also send the vote to the other participants, which abort once
+        // they receive it.
+        if self.config.decentralized {
+            self.push_peer_vote_actions(&context, actions, false);
+        }
+    }
+
+    // Advance to the epoch of a vote request from the coordinator, and request our vote on its
+    // value, or on each item of its batch.
+    fn handle_vote_request(
+        &self,
+        process: P,
+        epoch: Epoch,
+        (participant_processes, observers): (Vec<P>, Vec<P>),
+        value: Option<V>,
+        batch: Vec<V>,
+        mut context: TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+    ) -> Result<Vec<ParticipantAction<P, V, TS::Time>>, AlgorithmError> {
+        // A vote request must come from the coordinator, drop it otherwise.
+        if *context.coordinator() != process {
+            return Ok(vec![ParticipantAction::Notify(
+                ParticipantActionNotification::MessageDropped(
+                    "sender process is not the coordinator".into(),
+                ),
+            )]);
+        }
+
+        // A vote request can only be processed when we are waiting for one, drop it
+        // otherwise.
+        if !matches!(context.state(), ParticipantState::WaitingForVoteRequest) {
+            return Ok(vec![ParticipantAction::Notify(
+                ParticipantActionNotification::MessageDropped(
+                    "context state is not WaitingForVoteRequest".into(),
+                ),
+            )]);
+        }
+
+        // New epoch must be greater than the previous epoch.
+        if *context.epoch() >= epoch {
+            return Ok(vec![ParticipantAction::Notify(
+                ParticipantActionNotification::MessageDropped(format!(
+                    "epoch {} was not greater than previous epoch {}",
+                    epoch,
+                    *context.epoch()
+                )),
+            )]);
+        }
+
+        let mut actions = Vec::new();
+
+        // Advance the epoch to the epoch sent from the coordinator
+        self.push_advance_epoch_actions(&mut context, &mut actions, epoch);
+
+        // A timeout will occur after the configured local vote timeout, starting now. An
+        // alarm is set for the end of the timeout and the timeout is processed when an
+        // `ParticipantEvent::Alarm` is received.
+        let local_vote_timeout_start = self.time_source.now();
+        let local_vote_timeout_end = local_vote_timeout_start + self.config.local_vote_timeout;
+
+        // We join in this epoch if this is our first vote request, or if we were not among the
+        // participants of our last epoch.
+        if context.join_epoch().is_none()
+            || !context
+                .participant_processes()
+                .contains(context.this_process())
+        {
+            context.set_join_epoch(Some(epoch));
+        }
+
+        // Update the context with the new state of WaitingForVote. The value and the batch are
+        // kept until the epoch is decided, so that they can be included in the decision
+        // notification. The participants and observers of the epoch are the peers used for the
+        // termination protocol.
+        context.set_participant_processes(participant_processes);
+        context.set_observers(observers);
+        context
+            .peer_votes_mut()
+            .retain(|(_, vote_epoch, _)| *vote_epoch >= epoch);
+        context.set_value(value.clone());
+        context.set_batch(batch.clone());
+        context.set_state(ParticipantState::WaitingForVote {
+            local_vote_timeout_start,
+        });
+        actions.push(ParticipantAction::Update {
+            context,
+            alarm: Some(local_vote_timeout_end),
+        });
+
+        // Send a RequestForVote notification, or a RequestForBatchVote notification for a batch.
+        actions.push(ParticipantAction::Notify(match value {
+            Some(value) => ParticipantActionNotification::RequestForVote(value),
+            None => ParticipantActionNotification::RequestForBatchVote(batch),
+        }));
+
+        Ok(actions)
+    }
+
+    // Record a commit decision received from the coordinator or another participant. For a batch
+    // epoch, committed_items holds whether each item of the batch was committed.
+    fn handle_commit(
+        &self,
+        process: P,
+        epoch: Epoch,
+        committed_items: Option<Vec<bool>>,
+        mut context: TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>,
+    ) -> Result<Vec<ParticipantAction<P, V, TS::Time>>, AlgorithmError> {
+        // The sender must be the coordinator, a participant or an observer. A participant or an
+        // observer sends the decision in response to our DecisionRequest.
+        if !(context.participant_processes().contains(&process)
+            || context.observers().contains(&process)
+            || *context.coordinator() == process)
+        {
+            return Ok(vec![ParticipantAction::Notify(
+                ParticipantActionNotification::MessageDropped(
+                    "sender process is not a coordinator, participant or observer".into(),
+                ),
+            )]);
+        }
+
+        // A Commit must be for the current epoch to be processed, drop it otherwise.
+        if *context.epoch() != epoch {
+            return Ok(vec![ParticipantAction::Notify(
+                ParticipantActionNotification::MessageDropped(
+                    "epoch is not the current epoch".into(),
+                ),
+            )]);
+        }
+
+        // If we have already committed this epoch, our ack may have been lost and the
+        // coordinator is re-sending the decision. Acknowledge it again.
+        if matches!(context.state(), ParticipantState::WaitingForVoteRequest)
+            && *context.last_commit_epoch() == Some(epoch)
+            && *context.coordinator() == process
+        {
+            return Ok(vec![ParticipantAction::SendMessage(
+                process,
+                TwoPhaseCommitMessage::DecisionAck(epoch),
+            )]);
+        }
+
+        if !matches!(
+            context.state(),
+            ParticipantState::Voted {
+                vote: _,
+                decision_timeout_start: _
+            }
+        ) {
+            return Ok(vec![ParticipantAction::Notify(
+                ParticipantActionNotification::MessageDropped(
+                    "commit received outside decision window".into(),
+                ),
+            )]);
+        }
+
+        // The Commit of a batch epoch must include the committed items, one for each item of the
+        // batch, drop it otherwise.
+        match &committed_items {
+            None if !context.batch().is_empty() => {
+                return Ok(vec![ParticipantAction::Notify(
+                    ParticipantActionNotification::MessageDropped(
+                        "commit of a batch epoch must include the committed items".into(),
+                    ),
+                )]);
+            }
+            Some(items) if items.len() != context.batch().len() => {
+                return Ok(vec![ParticipantAction::Notify(
+                    ParticipantActionNotification::MessageDropped(
+                        "committed items do not match the items of the epoch".into(),
+                    ),
+                )]);
+            }
+            _ => (),
+        }
+
+        let mut actions = Vec::new();
+
+        // Record our decision to Commit. Also update the last commit epoch and the decision
+        // history used to answer DecisionRequest messages.
+        context.set_last_commit_epoch(Some(epoch));
+        context.record_decision(epoch, Decision::Commit, self.config.decision_history_size);
+        if let Some(committed_items) = committed_items {
+            context.record_batch_decision(
+                epoch,
+                committed_items,
+                self.config.decision_history_size,
+            );
+        }
+        context.set_state(ParticipantState::Commit);
+        actions.push(ParticipantAction::Update {
+            context: context.clone(),
+            alarm: None,
+        });
+
+        // Notify that we've committed. The value is no longer needed once the epoch is
+        // decided.
+        actions.push(ParticipantAction::Notify(Self::decision_notification(
+            &mut context,
+            true,
+        )));
+
+        // Send an acknowledgement to the coordinator, even if the decision was received
+        // from another participant. With presumed commit, commit decisions are not
+        // acknowledged.
+        if self.config.presumption != Presumption::Commit {
+            actions.push(ParticipantAction::SendMessage(
+                context.coordinator().clone(),
+                TwoPhaseCommitMessage::DecisionAck(epoch),
+            ));
+        }
+
+        // Switch to WaitingForVoteRequest to prepare for the next epoch
+        context.set_state(ParticipantState::WaitingForVoteRequest);
+        actions.push(ParticipantAction::Update {
+            context: context.clone(),
+            alarm: None,
+        });
+
+        Ok(actions)
+    }
 }
 
 impl<P, V, TS> Algorithm for ParticipantAlgorithm<P, V, TS>
@@ -84,13 +627,30 @@ where
 {
     type Event = ParticipantEvent<P, V>;
     type Action = ParticipantAction<P, V, TS::Time>;
-    type Context = TwoPhaseCommitContext<P, TS::Time, ParticipantContext<P, TS::Time>>;
+    type Context = TwoPhaseCommitContext<P, V, TS::Time, ParticipantContext<P, TS::Time>>;
 
     fn event(
         &self,
         event: Self::Event,
         mut context: Self::Context,
     ) -> Result<Vec<Self::Action>, AlgorithmError> {
+        // A vote request from the coordinator of an earlier term was sent before a standby
+        // coordinator took over, and is dropped. A NewTerm is checked against the current term
+        // when it is handled, and a decision is accepted from any term.
+        if let ParticipantEvent::Deliver(_, message) = &event {
+            if let Some(term) = message.vote_request_term() {
+                if term < *context.term() {
+                    return Ok(vec![ParticipantAction::Notify(
+                        ParticipantActionNotification::MessageDropped(format!(
+                            "term {} is below the current term {}",
+                            term,
+                            context.term()
+                        )),
+                    )]);
+                }
+            }
+        }
+
         match event {
             ParticipantEvent::Alarm() => match context.state() {
                 // Receiving alarms in the Abort state is unexpected and indicates a bug in the
@@ -106,7 +666,8 @@ where
                 )),
 
                 // A vote timeout has occurred, which means we have not received a decision within
-                // DECISION_TIMEOUT_SECONDS.
+                // the decision timeout. Send a new round of DecisionRequest messages, unless the
+                // configured number of rounds has been reached.
                 ParticipantState::Voted {
                     vote,
                     decision_timeout_start,
@@ -117,52 +678,44 @@ where
                     // been woken up with an alarm; however, we can just ignore it and wait for the
                     // alarm to be triggered again later.
                     if self.time_source.now()
-                        > *decision_timeout_start + Duration::from_secs(DECISION_TIMEOUT_SECONDS)
+                        > *decision_timeout_start + self.decision_timeout(&context)
                     {
-                        // Send a Decision Request to all other participant processes
-                        for process in context
-                            .participant_processes()
-                            .iter()
-                            .filter(|p| *p != context.this_process())
+                        let vote = *vote;
+                        if self
+                            .config
+                            .decision_request_limit
+                            .is_some_and(|limit| *context.decision_requests() >= limit)
                         {
-                            actions.push(ParticipantAction::SendMessage(
-                                process.clone(),
-                                TwoPhaseCommitMessage::DecisionRequest(*context.epoch()),
-                            ));
+                            self.push_blocked_actions(context, &mut actions);
+                        } else {
+                            self.push_decision_request_actions(context, &mut actions, vote);
                         }
+                    }
 
-                        // Send a Decision Request to the coordinator
-                        actions.push(ParticipantAction::SendMessage(
-                            context.coordinator().clone(),
-                            TwoPhaseCommitMessage::DecisionRequest(*context.epoch()),
-                        ));
+                    Ok(actions)
+                }
 
-                        // Calculate new decision timeout start/end.
-                        let new_decision_timeout_start = self.time_source.now();
-                        let new_decision_timeout_end = new_decision_timeout_start
-                            + Duration::from_secs(DECISION_TIMEOUT_SECONDS);
+                // A local vote timeout has occurred, which means the application has not voted
+                // within the configured local vote timeout.
+                ParticipantState::WaitingForVote {
+                    local_vote_timeout_start,
+                } => {
+                    let mut actions = Vec::new();
 
-                        // Updated the Voted state with the new timeout start value.
-                        let mut new_context = context.clone();
-                        new_context.set_state(ParticipantState::Voted {
-                            vote: *vote,
-                            decision_timeout_start: new_decision_timeout_start,
-                        });
-                        actions.push(ParticipantAction::Update {
-                            context: new_context,
-                            alarm: Some(new_decision_timeout_end),
-                        });
+                    // Validate that the timeout has occurred. If this is false, we shouldn't have
+                    // been woken up with an alarm; however, we can just ignore it and wait for the
+                    // alarm to be triggered again later.
+                    if self.time_source.now()
+                        > *local_vote_timeout_start + self.config.local_vote_timeout
+                    {
+                        // Rather than block the epoch until the coordinator's vote timeout, vote
+                        // no on behalf of the application.
+                        self.push_vote_no_actions(context, &mut actions);
                     }
 
                     Ok(actions)
                 }
 
-                // An Alarm while in WaitingForVote is not allowed and indicates a bug in the
-                // caller.
-                ParticipantState::WaitingForVote => Err(AlgorithmError::InvalidState(
-                    InvalidStateError::with_message("Alarm unexpected in WaitForVote state".into()),
-                )),
-
                 // An Alarm while in WaitingForVoteRequest is not allowed and indicates a bug in
                 // the caller.
                 ParticipantState::WaitingForVoteRequest => Err(AlgorithmError::InvalidState(
@@ -172,60 +725,169 @@ where
                 )),
             },
 
+            // A Recover event is sent once after the participant has been restarted from its
+            // persisted context. Any actions which followed the last persisted Update may not have
+            // been run, so they are generated again for the persisted state.
+            ParticipantEvent::Recover() => {
+                let mut actions = Vec::new();
+
+                match context.state().clone() {
+                    // The decision was persisted, but it may not have been notified or
+                    // acknowledged. Notify it again and prepare for the next epoch.
+                    ParticipantState::Commit | ParticipantState::Abort => {
+                        let epoch = *context.epoch();
+                        let committed = *context.last_commit_epoch() == Some(epoch);
+
+                        actions.push(ParticipantAction::Notify(Self::decision_notification(
+                            &mut context,
+                            committed,
+                        )));
+
+                        if committed {
+                            if self.config.presumption != Presumption::Commit {
+                                actions.push(ParticipantAction::SendMessage(
+                                    context.coordinator().clone(),
+                                    TwoPhaseCommitMessage::DecisionAck(epoch),
+                                ));
+                            }
+                        } else {
+                            // If we aborted by voting no, the vote may not have been sent. A
+                            // coordinator which has already decided drops it.
+                            actions.push(ParticipantAction::SendMessage(
+                                context.coordinator().clone(),
+                                TwoPhaseCommitMessage::VoteResponse(epoch, false),
+                            ));
+                        }
+
+                        context.set_state(ParticipantState::WaitingForVoteRequest);
+                        actions.push(ParticipantAction::Update {
+                            context,
+                            alarm: None,
+                        });
+                    }
+
+                    // The decision may have been sent while we were down, so start the termination
+                    // protocol immediately rather than waiting for the decision timeout. If we were
+                    // blocked, this sends one more round of DecisionRequest messages.
+                    ParticipantState::Voted { vote, .. } => {
+                        self.push_decision_request_actions(context, &mut actions, vote);
+                    }
+
+                    // The RequestForVote or RequestForBatchVote notification has been lost. Vote
+                    // no if the local vote timeout has expired while we were down; otherwise,
+                    // notify it again and re-arm the alarm for the remaining time.
+                    ParticipantState::WaitingForVote {
+                        local_vote_timeout_start,
+                    } => {
+                        let local_vote_timeout_end =
+                            local_vote_timeout_start + self.config.local_vote_timeout;
+
+                        let notification = if context.batch().is_empty() {
+                            context
+                                .value()
+                                .clone()
+                                .map(ParticipantActionNotification::RequestForVote)
+                        } else {
+                            Some(ParticipantActionNotification::RequestForBatchVote(
+                                context.batch().clone(),
+                            ))
+                        };
+
+                        match notification {
+                            Some(notification)
+                                if self.time_source.now() <= local_vote_timeout_end =>
+                            {
+                                actions.push(ParticipantAction::Notify(notification));
+                                actions.push(ParticipantAction::Update {
+                                    context,
+                                    alarm: Some(local_vote_timeout_end),
+                                });
+                            }
+                            _ => self.push_vote_no_actions(context, &mut actions),
+                        }
+                    }
+
+                    // There is nothing outstanding while waiting for the next VoteRequest.
+                    ParticipantState::WaitingForVoteRequest => (),
+                }
+
+                Ok(actions)
+            }
+
             // If the coordinator sends a VoteRequest, generate a RequestForVote
             // notification to determine how to respond and update our state.
-            ParticipantEvent::Deliver(process, ParticipantMessage::VoteRequest(epoch, value)) => {
-                // A VoteRequest must come from the coordinator, drop it otherwise.
-                if *context.coordinator() != process {
-                    return Ok(vec![ParticipantAction::Notify(
-                        ParticipantActionNotification::MessageDropped(
-                            "sender process is not the coordinator".into(),
-                        ),
-                    )]);
-                }
+            ParticipantEvent::Deliver(
+                process,
+                ParticipantMessage::VoteRequest(_, epoch, value, participant_processes, observers),
+            ) => self.handle_vote_request(
+                process,
+                epoch,
+                (participant_processes, observers),
+                Some(value),
+                vec![],
+                context,
+            ),
 
-                // A VoteRequest can only be processed when we are waiting for one, drop it
-                // otherwise.
-                if !matches!(context.state(), ParticipantState::WaitingForVoteRequest) {
+            // A BatchVoteRequest is handled like a VoteRequest, except that we vote on each item
+            // of the batch.
+            ParticipantEvent::Deliver(
+                process,
+                ParticipantMessage::BatchVoteRequest(
+                    _,
+                    epoch,
+                    batch,
+                    participant_processes,
+                    observers,
+                ),
+            ) => self.handle_vote_request(
+                process,
+                epoch,
+                (participant_processes, observers),
+                None,
+                batch,
+                context,
+            ),
+            ParticipantEvent::Deliver(process, ParticipantMessage::Commit(_, epoch)) => {
+                self.handle_commit(process, epoch, None, context)
+            }
+
+            // The Commit of a batch epoch includes whether each item of the batch was committed.
+            ParticipantEvent::Deliver(
+                process,
+                ParticipantMessage::BatchCommit(_, epoch, committed_items),
+            ) => self.handle_commit(process, epoch, Some(committed_items), context),
+            ParticipantEvent::Deliver(process, ParticipantMessage::Abort(_, epoch)) => {
+                // The sender must be the coordinator, a participant or an observer. A participant
+                // or an observer sends the decision in response to our DecisionRequest.
+                if !(context.participant_processes().contains(&process)
+                    || context.observers().contains(&process)
+                    || *context.coordinator() == process)
+                {
                     return Ok(vec![ParticipantAction::Notify(
                         ParticipantActionNotification::MessageDropped(
-                            "context state is not WaitingForVoteRequest".into(),
+                            "sender process is not a coordinator, participant or observer".into(),
                         ),
                     )]);
                 }
 
-                // New epoch must be greater than the previous epoch.
-                if *context.epoch() >= epoch {
-                    return Ok(vec![ParticipantAction::Notify(
-                        ParticipantActionNotification::MessageDropped(format!(
-                            "epoch {} was not greater than previous epoch {}",
-                            epoch,
-                            *context.epoch()
-                        )),
+                // With presumed commit, the coordinator re-sends an abort until it is
+                // acknowledged. If we have already aborted this epoch, our ack may have been lost,
+                // so acknowledge it again. If the epoch is later than ours, its VoteRequest was
+                // lost and we did not vote, so acknowledge it as well.
+                if self.config.presumption == Presumption::Commit
+                    && matches!(context.state(), ParticipantState::WaitingForVoteRequest)
+                    && *context.coordinator() == process
+                    && (epoch > *context.epoch()
+                        || (epoch == *context.epoch()
+                            && *context.last_commit_epoch() != Some(epoch)))
+                {
+                    return Ok(vec![ParticipantAction::SendMessage(
+                        process,
+                        TwoPhaseCommitMessage::DecisionAck(epoch),
                     )]);
                 }
 
-                let mut actions = Vec::new();
-
-                // Advance the epoch to the epoch sent from the coordinator
-                self.push_advance_epoch_actions(&mut context, &mut actions, epoch);
-
-                // Update the context with the new state of WaitingForVote
-                context.set_state(ParticipantState::WaitingForVote);
-                actions.push(ParticipantAction::Update {
-                    context,
-                    alarm: None,
-                });
-
-                // Send a RequestForVote notification
-                actions.push(ParticipantAction::Notify(
-                    ParticipantActionNotification::RequestForVote(value),
-                ));
-
-                Ok(actions)
-            }
-            ParticipantEvent::Deliver(process, ParticipantMessage::Commit(epoch)) => {
-                // A Commit must be for the current epoch to be processed, drop it otherwise.
+                // An Abort must be for the current epoch to be processed, drop it otherwise.
                 if *context.epoch() != epoch {
                     return Ok(vec![ParticipantAction::Notify(
                         ParticipantActionNotification::MessageDropped(
@@ -243,30 +905,37 @@ where
                 ) {
                     return Ok(vec![ParticipantAction::Notify(
                         ParticipantActionNotification::MessageDropped(
-                            "commit received outside decision window".into(),
+                            "abort received outside decision window".into(),
                         ),
                     )]);
                 }
 
                 let mut actions = Vec::new();
 
-                // The vote was no, so record our decision to Abort.
-                context.set_state(ParticipantState::Commit);
+                // Record our decision to Abort. Also update the decision history used to answer
+                // DecisionRequest messages.
+                context.record_decision(epoch, Decision::Abort, self.config.decision_history_size);
+                context.set_state(ParticipantState::Abort);
                 actions.push(ParticipantAction::Update {
                     context: context.clone(),
                     alarm: None,
                 });
 
-                // Notify that we've committed.
-                actions.push(ParticipantAction::Notify(
-                    ParticipantActionNotification::Commit(),
-                ));
+                // Notify that we've aborted. The value is no longer needed once the epoch is
+                // decided.
+                actions.push(ParticipantAction::Notify(Self::decision_notification(
+                    &mut context,
+                    false,
+                )));
 
-                // Send an acknowledgement back to the coordinator.
-                actions.push(ParticipantAction::SendMessage(
-                    process,
-                    TwoPhaseCommitMessage::DecisionAck(epoch),
-                ));
+                // With presumed commit, send an acknowledgement to the coordinator, even if the
+                // decision was received from another participant.
+                if self.config.presumption == Presumption::Commit {
+                    actions.push(ParticipantAction::SendMessage(
+                        context.coordinator().clone(),
+                        TwoPhaseCommitMessage::DecisionAck(epoch),
+                    ));
+                }
 
                 // Switch to WaitingForVoteRequest to prepare for the next epoch
                 context.set_state(ParticipantState::WaitingForVoteRequest);
@@ -277,50 +946,77 @@ where
 
                 Ok(actions)
             }
-            ParticipantEvent::Deliver(_process, ParticipantMessage::Abort(epoch)) => {
-                // An Abort must be for the current epoch to be processed, drop it otherwise.
-                if *context.epoch() != epoch {
+
+            // In decentralized mode, each participant sends its vote to all other participants.
+            ParticipantEvent::Deliver(process, ParticipantMessage::VoteResponse(epoch, vote)) => {
+                if !self.config.decentralized {
                     return Ok(vec![ParticipantAction::Notify(
                         ParticipantActionNotification::MessageDropped(
-                            "epoch is not the current epoch".into(),
+                            "votes are only sent to participants in decentralized mode".into(),
                         ),
                     )]);
                 }
 
-                if !matches!(
-                    context.state(),
-                    ParticipantState::Voted {
-                        vote: _,
-                        decision_timeout_start: _
-                    }
-                ) {
+                if !context.participant_processes().contains(&process)
+                    || process == *context.this_process()
+                {
                     return Ok(vec![ParticipantAction::Notify(
                         ParticipantActionNotification::MessageDropped(
-                            "abort received outside decision window".into(),
+                            "sender process is not another participant".into(),
                         ),
                     )]);
                 }
 
-                let mut actions = Vec::new();
+                // A vote for an epoch which we have already decided, or in which we voted
+                // read-only, is no longer needed.
+                let decided = epoch < *context.epoch()
+                    || (epoch == *context.epoch()
+                        && !matches!(
+                            context.state(),
+                            ParticipantState::WaitingForVote { .. }
+                                | ParticipantState::Voted { .. }
+                        ));
+                if decided {
+                    return Ok(vec![ParticipantAction::Notify(
+                        ParticipantActionNotification::MessageDropped(
+                            "epoch has already been decided".into(),
+                        ),
+                    )]);
+                }
 
-                // The vote was no, so record our decision to Abort.
-                context.set_state(ParticipantState::Abort);
-                actions.push(ParticipantAction::Update {
-                    context: context.clone(),
-                    alarm: None,
-                });
+                if !context
+                    .peer_votes()
+                    .iter()
+                    .any(|(p, e, _)| *p == process && *e == epoch)
+                {
+                    context.peer_votes_mut().push((process, epoch, vote));
+                }
 
-                // Notify that we've aborted.
-                actions.push(ParticipantAction::Notify(
-                    ParticipantActionNotification::Abort(),
-                ));
+                let mut actions = Vec::new();
+                let current = epoch == *context.epoch();
 
-                // Switch to WaitingForVoteRequest to prepare for the next epoch
-                context.set_state(ParticipantState::WaitingForVoteRequest);
-                actions.push(ParticipantAction::Update {
-                    context: context.clone(),
-                    alarm: None,
-                });
+                match context.state() {
+                    // Another participant voted no before we voted, so vote no as well.
+                    ParticipantState::WaitingForVote { .. } if current && !vote => {
+                        self.push_vote_no_actions(context, &mut actions);
+                    }
+                    ParticipantState::Voted { vote: true, .. } if current => {
+                        match Self::peer_decision(&context) {
+                            Some(decision) => {
+                                self.push_peer_decision_actions(context, &mut actions, decision)
+                            }
+                            None => actions.push(ParticipantAction::Update {
+                                alarm: self.alarm(&context),
+                                context,
+                            }),
+                        }
+                    }
+                    // Keep the vote until we have voted in its epoch.
+                    _ => actions.push(ParticipantAction::Update {
+                        alarm: self.alarm(&context),
+                        context,
+                    }),
+                }
 
                 Ok(actions)
             }
@@ -328,7 +1024,7 @@ where
             ParticipantEvent::Deliver(process, ParticipantMessage::DecisionRequest(epoch)) => {
                 // The sender must be a coordinator or participant.
                 if !(context.participant_processes().contains(&process)
-                    && *context.coordinator() != process)
+                    || *context.coordinator() == process)
                 {
                     return Ok(vec![ParticipantAction::Notify(
                         ParticipantActionNotification::MessageDropped(
@@ -337,36 +1033,111 @@ where
                     )]);
                 }
 
+                // In decentralized mode, the coordinator requests the votes it has not received.
+                // Our vote is sent again if we know it; otherwise, we have not voted yet and vote
+                // no below.
+                if *context.coordinator() == process {
+                    let vote = match (context.decision(epoch), context.state()) {
+                        (Some(decision), _) => Some(decision == Decision::Commit),
+                        (None, _) if Some(epoch) == *context.last_commit_epoch() => Some(true),
+                        (None, ParticipantState::Voted { vote, .. })
+                            if epoch == *context.epoch() =>
+                        {
+                            Some(*vote)
+                        }
+                        _ => None,
+                    };
+
+                    if let Some(vote) = vote {
+                        return Ok(vec![ParticipantAction::SendMessage(
+                            process,
+                            TwoPhaseCommitMessage::VoteResponse(epoch, vote),
+                        )]);
+                    }
+                }
+
+                // If the epoch is in the decision history, we know the decision exactly.
+                match context.decision(epoch) {
+                    Some(Decision::Commit) => {
+                        return Ok(vec![ParticipantAction::SendMessage(
+                            process,
+                            context.commit_message(epoch),
+                        )])
+                    }
+                    Some(Decision::Abort) => {
+                        return Ok(vec![ParticipantAction::SendMessage(
+                            process,
+                            context.abort_message(epoch),
+                        )])
+                    }
+                    None => (),
+                }
+
                 // We record the last commit epoch in the context; if the epoch requested was the
                 // last commit epoch, send a commit message to the requesting process.
                 if Some(epoch) == *context.last_commit_epoch() {
                     return Ok(vec![ParticipantAction::SendMessage(
                         process,
-                        TwoPhaseCommitMessage::Commit(epoch),
+                        context.commit_message(epoch),
                     )]);
                 }
 
                 // If the epoch is between the current epoch and the last commit epoch, we know
                 // that the decision must have been Abort. Thus, we send an Abort message.
+                //
+                // This does not hold for an epoch in which we voted read-only, as we never learn
+                // the decision of that epoch; so we only answer for epochs after the last
+                // read-only epoch. Nor does it hold for an epoch before we joined, which may have
+                // been decided without us.
                 if epoch < *context.epoch()
                     && (Some(epoch) > *context.last_commit_epoch()
                         || context.last_commit_epoch().is_none())
+                    && Some(epoch) > *context.last_read_only_epoch()
+                    && context
+                        .join_epoch()
+                        .is_some_and(|join_epoch| epoch >= join_epoch)
                 {
                     return Ok(vec![ParticipantAction::SendMessage(
                         process,
-                        TwoPhaseCommitMessage::Abort(epoch),
+                        context.abort_message(epoch),
                     )]);
                 }
 
+                // If we have not yet voted in the requested epoch, either because we are still
+                // waiting for our vote or because we have not yet received the VoteRequest, we
+                // may unilaterally decide to abort. The requesting process is sent the decision
+                // and the coordinator is sent a NO vote, so the epoch can not commit.
+                let not_voted = match context.state() {
+                    ParticipantState::WaitingForVote { .. } => epoch == *context.epoch(),
+                    ParticipantState::WaitingForVoteRequest => epoch > *context.epoch(),
+                    _ => false,
+                };
+                if not_voted {
+                    let mut actions = Vec::new();
+
+                    // Move to the requested epoch, so a VoteRequest for it which arrives later is
+                    // dropped.
+                    context.set_epoch(epoch);
+                    let coordinator = context.coordinator().clone();
+                    let abort = context.abort_message(epoch);
+                    self.push_vote_no_actions(context, &mut actions);
+
+                    // The coordinator only needs the NO vote.
+                    if process != coordinator {
+                        actions.push(ParticipantAction::SendMessage(process, abort));
+                    }
+
+                    return Ok(actions);
+                }
+
                 // A note on ignored messages:
                 //
-                // If the epoch is before the last commit epoch, we ignore the message as we know
-                // all processes decided in the last commit epoch and no process can be in an
-                // uncertainty period for an older epoch.
+                // If the epoch is before the last commit epoch, we ignore the message as its
+                // decision has been pruned from the decision history.
                 //
-                // If the epoch is after our current epoch, we ignore the message as we do not yet
-                // know what the future holds. Similarly, we do not yet have a decision for the
-                // current epoch or we would have advanced to the next epoch already.
+                // If we have voted yes in the requested epoch, we ignore the message as we are
+                // also uncertain of the decision. The requesting process will ask again after its
+                // decision timeout.
                 Ok(vec![ParticipantAction::Notify(
                     ParticipantActionNotification::MessageDropped(
                         "decision for requested epoch is unknown".into(),
@@ -374,12 +1145,90 @@ where
                 )])
             }
 
+            // A coordinator which has taken over for a new term asks what we know of our current
+            // epoch, so that it can finish that epoch before starting new ones.
+            ParticipantEvent::Deliver(process, ParticipantMessage::NewTerm(term)) => {
+                // Only a greater term may replace the coordinator. The current coordinator may
+                // send NewTerm again if it did not receive our ack.
+                if term < *context.term()
+                    || (term == *context.term() && *context.coordinator() != process)
+                {
+                    return Ok(vec![ParticipantAction::Notify(
+                        ParticipantActionNotification::MessageDropped(format!(
+                            "term {} is not greater than the current term {}",
+                            term,
+                            context.term()
+                        )),
+                    )]);
+                }
+
+                let mut actions = Vec::new();
+
+                // From now on, messages from the previous coordinator are dropped, as the sender
+                // is no longer the coordinator.
+                context.set_term(term);
+                context.set_coordinator(process.clone());
+
+                let epoch = *context.epoch();
+                let decision = match context.state().clone() {
+                    // We have not yet voted, so we abort unilaterally; the epoch can not commit.
+                    ParticipantState::WaitingForVote { .. } => {
+                        self.push_vote_no_actions(context, &mut actions);
+                        Some(Decision::Abort)
+                    }
+
+                    // We voted yes and are uncertain of the decision. Keep the decision timeout
+                    // running, so the termination protocol continues with the new coordinator.
+                    // The rounds of DecisionRequest messages start over, as the new coordinator
+                    // may be able to answer them.
+                    ParticipantState::Voted {
+                        decision_timeout_start,
+                        ..
+                    } => {
+                        context.set_decision_requests(0);
+                        actions.push(ParticipantAction::Update {
+                            context,
+                            alarm: Some(decision_timeout_start + self.config.decision_timeout),
+                        });
+                        None
+                    }
+
+                    // The epoch is over for us. If we did not commit it, we aborted it, unless we
+                    // voted read-only and never learned the decision.
+                    ParticipantState::WaitingForVoteRequest
+                    | ParticipantState::Commit
+                    | ParticipantState::Abort => {
+                        let decision = if let Some(decision) = context.decision(epoch) {
+                            Some(decision)
+                        } else if *context.last_commit_epoch() == Some(epoch) {
+                            Some(Decision::Commit)
+                        } else if *context.last_read_only_epoch() == Some(epoch) {
+                            None
+                        } else {
+                            Some(Decision::Abort)
+                        };
+                        actions.push(ParticipantAction::Update {
+                            context,
+                            alarm: None,
+                        });
+                        decision
+                    }
+                };
+
+                actions.push(ParticipantAction::SendMessage(
+                    process,
+                    TwoPhaseCommitMessage::NewTermAck(term, epoch, decision),
+                ));
+
+                Ok(actions)
+            }
+
             // In response to a RequestForVote, a Vote message contains either true (vote yes) or
             // false (vote no).
             ParticipantEvent::Vote(vote) => {
                 // If we receive a Vote event when not in WaitingForVote, it indicates
                 // a programming error by the caller of the algorithm.
-                if !matches!(context.state(), ParticipantState::WaitingForVote) {
+                if !matches!(context.state(), ParticipantState::WaitingForVote { .. }) {
                     return Err(AlgorithmError::InvalidState(
                         InvalidStateError::with_message(
                             "Vote event when not in WaitingForVote state".into(),
@@ -390,49 +1239,112 @@ where
                 let mut actions = Vec::new();
 
                 if vote {
-                    // A timeout will occur after DECISION_TIMEOUT_SECONDS, starting now. An alarm is
-                    // set for the end of the timeout and the timeout is processed when an
-                    // `ParticipantEvent::Alarm` is received.
-                    let decision_timeout_start = self.time_source.now();
-                    let decision_timeout_end =
-                        decision_timeout_start + Duration::from_secs(DECISION_TIMEOUT_SECONDS);
-
-                    // Record the vote and update the state to Voted.
-                    context.set_state(ParticipantState::Voted {
-                        vote,
-                        decision_timeout_start,
-                    });
-                    actions.push(ParticipantAction::Update {
-                        context: context.clone(),
-                        alarm: Some(decision_timeout_end),
-                    });
+                    let vote_response = TwoPhaseCommitMessage::VoteResponse(*context.epoch(), vote);
+                    self.push_vote_yes_actions(context, &mut actions, vote_response);
                 } else {
-                    // The vote was no, so record our decision to Abort.
-                    context.set_state(ParticipantState::Abort);
-                    actions.push(ParticipantAction::Update {
-                        context: context.clone(),
-                        alarm: None,
-                    });
-
-                    // Notify that we've aborted.
-                    actions.push(ParticipantAction::Notify(
-                        ParticipantActionNotification::Abort(),
+                    self.push_vote_no_actions(context, &mut actions);
+                }
+
+                Ok(actions)
+            }
+
+            // In response to a RequestForVote, a VoteWithValue votes yes and attaches a value to
+            // the vote, which is passed on to the coordinator.
+            ParticipantEvent::VoteWithValue(value) => {
+                // If we receive a VoteWithValue event when not in WaitingForVote, it indicates
+                // a programming error by the caller of the algorithm.
+                if !matches!(context.state(), ParticipantState::WaitingForVote { .. }) {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "VoteWithValue event when not in WaitingForVote state".into(),
+                        ),
+                    ));
+                }
+
+                let mut actions = Vec::new();
+
+                let vote_response =
+                    TwoPhaseCommitMessage::VoteResponseWithValue(*context.epoch(), value);
+                self.push_vote_yes_actions(context, &mut actions, vote_response);
+
+                Ok(actions)
+            }
+
+            // In response to a RequestForBatchVote, a BatchVote event contains our vote on each
+            // item of the batch. We vote yes if we accept at least one item.
+            ParticipantEvent::BatchVote(votes) => {
+                // If we receive a BatchVote event when not in WaitingForVote, or with a vote
+                // missing for an item of the batch, it indicates a programming error by the caller
+                // of the algorithm.
+                if !matches!(context.state(), ParticipantState::WaitingForVote { .. }) {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "BatchVote event when not in WaitingForVote state".into(),
+                        ),
+                    ));
+                }
+
+                if votes.len() != context.batch().len() {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "BatchVote event must have one vote for each item of the batch".into(),
+                        ),
                     ));
+                }
+
+                let mut actions = Vec::new();
+
+                if votes.contains(&true) {
+                    let vote_response =
+                        TwoPhaseCommitMessage::BatchVoteResponse(*context.epoch(), votes);
+                    self.push_vote_yes_actions(context, &mut actions, vote_response);
+                } else {
+                    self.push_vote_no_actions(context, &mut actions);
+                }
 
-                    // Switch to WaitingForVoteRequest to prepare for the next epoch
-                    context.set_state(ParticipantState::WaitingForVoteRequest);
-                    actions.push(ParticipantAction::Update {
-                        context: context.clone(),
-                        alarm: None,
-                    });
+                Ok(actions)
+            }
+
+            // In response to a RequestForVote, a ReadOnlyVote indicates that the value does not
+            // change any state on this participant. We take no further part in this epoch, so the
+            // coordinator will not send us the decision.
+            ParticipantEvent::ReadOnlyVote() => {
+                // If we receive a ReadOnlyVote event when not in WaitingForVote, it indicates
+                // a programming error by the caller of the algorithm.
+                if !matches!(context.state(), ParticipantState::WaitingForVote { .. }) {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "ReadOnlyVote event when not in WaitingForVote state".into(),
+                        ),
+                    ));
                 }
 
+                let mut actions = Vec::new();
+
+                // Record the read-only epoch, drop the value and the batch as this participant
+                // takes no further part in the epoch, and switch to WaitingForVoteRequest to
+                // prepare for the next epoch.
+                context.set_last_read_only_epoch(Some(*context.epoch()));
+                context.set_value(None);
+                context.set_batch(vec![]);
+                context.set_state(ParticipantState::WaitingForVoteRequest);
+                actions.push(ParticipantAction::Update {
+                    context: context.clone(),
+                    alarm: None,
+                });
+
                 // Send the vote to the coordinator.
                 actions.push(ParticipantAction::SendMessage(
                     context.coordinator().clone(),
-                    TwoPhaseCommitMessage::VoteResponse(*context.epoch(), vote),
+                    TwoPhaseCommitMessage::ReadOnlyVoteResponse(*context.epoch()),
                 ));
 
+                // In decentralized mode, the other participants are waiting for our vote. As the
+                // decision does not matter to us, we vote yes.
+                if self.config.decentralized {
+                    self.push_peer_vote_actions(&context, &mut actions, true);
+                }
+
                 Ok(actions)
             }
         }
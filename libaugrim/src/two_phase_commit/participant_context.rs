@@ -15,6 +15,8 @@
 use crate::process::Process;
 use crate::time::Time;
 
+use super::Epoch;
+
 #[derive(Clone, Debug, Eq, PartialEq)]
 pub enum ParticipantState<T>
 where
@@ -27,7 +29,9 @@ where
         decision_timeout_start: T,
     },
     WaitingForVoteRequest,
-    WaitingForVote,
+    WaitingForVote {
+        local_vote_timeout_start: T,
+    },
 }
 
 #[derive(Clone, Debug, Eq, PartialEq)]
@@ -36,6 +40,21 @@ where
     P: Process,
     T: Time,
 {
+    // The number of rounds of DecisionRequest messages sent in the termination protocol of the
+    // current epoch, which determines the time to wait for the decision before the next round.
+    pub(super) decision_requests: u32,
+    // The epoch of the first VoteRequest received since this participant was last not among the
+    // participants. Earlier epochs may have been decided without us, so their decision can not be
+    // inferred.
+    pub(super) join_epoch: Option<Epoch>,
+    pub(super) last_read_only_epoch: Option<Epoch>,
+    // The observers of the current epoch, which may answer a DecisionRequest in the termination
+    // protocol.
+    pub(super) observers: Vec<P>,
     pub(super) participant_processes: Vec<P>,
+    // The votes received from other participants in decentralized mode, with the epoch of each
+    // vote. A vote may arrive before the VoteRequest for its epoch, so votes are kept until their
+    // epoch is decided.
+    pub(super) peer_votes: Vec<(P, Epoch, bool)>,
     pub(super) state: ParticipantState<T>,
 }
@@ -25,8 +25,12 @@ where
     V: Value,
 {
     Alarm(),
-    Deliver(P, ParticipantMessage<V>),
+    Recover(),
+    Deliver(P, ParticipantMessage<P, V>),
     Vote(bool),
+    VoteWithValue(V),
+    BatchVote(Vec<bool>),
+    ReadOnlyVote(),
 }
 
 impl<P, V> TryFrom<TwoPhaseCommitEvent<P, V>> for ParticipantEvent<P, V>
@@ -39,11 +43,30 @@ where
     fn try_from(event: TwoPhaseCommitEvent<P, V>) -> Result<Self, Self::Error> {
         match event {
             TwoPhaseCommitEvent::Alarm() => Ok(ParticipantEvent::Alarm()),
+            TwoPhaseCommitEvent::Recover() => Ok(ParticipantEvent::Recover()),
             TwoPhaseCommitEvent::Deliver(p, m) => Ok(ParticipantEvent::Deliver(p, m.try_into()?)),
             TwoPhaseCommitEvent::Start(_) => Err(InvalidStateError::with_message(
                 "Start event can not be handled by a participant".into(),
             )),
+            TwoPhaseCommitEvent::StartWithValues(_) => Err(InvalidStateError::with_message(
+                "StartWithValues event can not be handled by a participant".into(),
+            )),
+            TwoPhaseCommitEvent::StartBatch(_) => Err(InvalidStateError::with_message(
+                "StartBatch event can not be handled by a participant".into(),
+            )),
+            TwoPhaseCommitEvent::Cancel() => Err(InvalidStateError::with_message(
+                "Cancel event can not be handled by a participant".into(),
+            )),
+            TwoPhaseCommitEvent::Reconfigure(_) => Err(InvalidStateError::with_message(
+                "Reconfigure event can not be handled by a participant".into(),
+            )),
+            TwoPhaseCommitEvent::TakeOver(_) => Err(InvalidStateError::with_message(
+                "TakeOver event can not be handled by a participant".into(),
+            )),
             TwoPhaseCommitEvent::Vote(vote) => Ok(ParticipantEvent::Vote(vote)),
+            TwoPhaseCommitEvent::VoteWithValue(value) => Ok(ParticipantEvent::VoteWithValue(value)),
+            TwoPhaseCommitEvent::BatchVote(votes) => Ok(ParticipantEvent::BatchVote(votes)),
+            TwoPhaseCommitEvent::ReadOnlyVote() => Ok(ParticipantEvent::ReadOnlyVote()),
         }
     }
 }
@@ -18,8 +18,10 @@ use std::convert::TryFrom;
 
 use crate::algorithm::Value;
 use crate::error::InvalidStateError;
+use crate::process::Process;
 
 use super::Epoch;
+use super::Term;
 use super::TwoPhaseCommitMessage;
 
 /// A message which is delivered to the participant.
@@ -27,56 +29,140 @@ use super::TwoPhaseCommitMessage;
 /// This is a subset of `TwoPhaseCommitMessage`, containing only the set of messages which can be
 /// delivered to a participant.
 #[derive(Clone)]
-pub enum ParticipantMessage<V>
+pub enum ParticipantMessage<P, V>
 where
+    P: Process,
     V: Value,
 {
-    VoteRequest(Epoch, V),
-    Commit(Epoch),
-    Abort(Epoch),
+    VoteRequest(Term, Epoch, V, Vec<P>, Vec<P>),
+    VoteResponse(Epoch, bool),
+    Commit(Term, Epoch),
+    Abort(Term, Epoch),
     DecisionRequest(Epoch),
+    NewTerm(Term),
+    BatchVoteRequest(Term, Epoch, Vec<V>, Vec<P>, Vec<P>),
+    BatchCommit(Term, Epoch, Vec<bool>),
 }
 
-impl<V> From<ParticipantMessage<V>> for TwoPhaseCommitMessage<V>
+impl<P, V> ParticipantMessage<P, V>
 where
+    P: Process,
     V: Value,
 {
-    fn from(message: ParticipantMessage<V>) -> Self {
+    // Returns the term of a vote request. A vote request from a deposed coordinator has a term
+    // below the current term. The term of a decision is not returned, as a new coordinator only
+    // recovers the decision of an earlier term and never changes it.
+    pub(super) fn vote_request_term(&self) -> Option<Term> {
+        match self {
+            ParticipantMessage::VoteRequest(term, ..)
+            | ParticipantMessage::BatchVoteRequest(term, ..) => Some(*term),
+            ParticipantMessage::VoteResponse(..)
+            | ParticipantMessage::Commit(..)
+            | ParticipantMessage::Abort(..)
+            | ParticipantMessage::BatchCommit(..)
+            | ParticipantMessage::DecisionRequest(_)
+            | ParticipantMessage::NewTerm(_) => None,
+        }
+    }
+}
+
+impl<P, V> From<ParticipantMessage<P, V>> for TwoPhaseCommitMessage<P, V>
+where
+    P: Process,
+    V: Value,
+{
+    fn from(message: ParticipantMessage<P, V>) -> Self {
         match message {
-            ParticipantMessage::VoteRequest(epoch, value) => {
-                TwoPhaseCommitMessage::VoteRequest(epoch, value)
+            ParticipantMessage::VoteRequest(term, epoch, value, participants, observers) => {
+                TwoPhaseCommitMessage::VoteRequest(term, epoch, value, participants, observers)
             }
-            ParticipantMessage::Commit(epoch) => TwoPhaseCommitMessage::Commit(epoch),
-            ParticipantMessage::Abort(epoch) => TwoPhaseCommitMessage::Abort(epoch),
+            ParticipantMessage::VoteResponse(epoch, vote) => {
+                TwoPhaseCommitMessage::VoteResponse(epoch, vote)
+            }
+            ParticipantMessage::Commit(term, epoch) => TwoPhaseCommitMessage::Commit(term, epoch),
+            ParticipantMessage::Abort(term, epoch) => TwoPhaseCommitMessage::Abort(term, epoch),
             ParticipantMessage::DecisionRequest(epoch) => {
                 TwoPhaseCommitMessage::DecisionRequest(epoch)
             }
+            ParticipantMessage::NewTerm(term) => TwoPhaseCommitMessage::NewTerm(term),
+            ParticipantMessage::BatchVoteRequest(term, epoch, values, participants, observers) => {
+                TwoPhaseCommitMessage::BatchVoteRequest(
+                    term,
+                    epoch,
+                    values,
+                    participants,
+                    observers,
+                )
+            }
+            ParticipantMessage::BatchCommit(term, epoch, committed) => {
+                TwoPhaseCommitMessage::BatchCommit(term, epoch, committed)
+            }
         }
     }
 }
 
-impl<V> TryFrom<TwoPhaseCommitMessage<V>> for ParticipantMessage<V>
+impl<P, V> TryFrom<TwoPhaseCommitMessage<P, V>> for ParticipantMessage<P, V>
 where
+    P: Process,
     V: Value,
 {
     type Error = InvalidStateError;
 
-    fn try_from(message: TwoPhaseCommitMessage<V>) -> Result<Self, Self::Error> {
+    fn try_from(message: TwoPhaseCommitMessage<P, V>) -> Result<Self, Self::Error> {
         match message {
-            TwoPhaseCommitMessage::VoteRequest(epoch, value) => {
-                Ok(ParticipantMessage::VoteRequest(epoch, value))
+            TwoPhaseCommitMessage::VoteRequest(term, epoch, value, participants, observers) => Ok(
+                ParticipantMessage::VoteRequest(term, epoch, value, participants, observers),
+            ),
+            TwoPhaseCommitMessage::VoteResponse(epoch, vote) => {
+                Ok(ParticipantMessage::VoteResponse(epoch, vote))
+            }
+            TwoPhaseCommitMessage::Commit(term, epoch) => {
+                Ok(ParticipantMessage::Commit(term, epoch))
             }
-            TwoPhaseCommitMessage::Commit(epoch) => Ok(ParticipantMessage::Commit(epoch)),
-            TwoPhaseCommitMessage::Abort(epoch) => Ok(ParticipantMessage::Abort(epoch)),
+            TwoPhaseCommitMessage::Abort(term, epoch) => Ok(ParticipantMessage::Abort(term, epoch)),
             TwoPhaseCommitMessage::DecisionRequest(epoch) => {
                 Ok(ParticipantMessage::DecisionRequest(epoch))
             }
-            TwoPhaseCommitMessage::VoteResponse(_, _) => Err(InvalidStateError::with_message(
-                "VoteResponse message cannot be handled by a participant".into(),
+            TwoPhaseCommitMessage::NewTerm(term) => Ok(ParticipantMessage::NewTerm(term)),
+            TwoPhaseCommitMessage::BatchVoteRequest(
+                term,
+                epoch,
+                values,
+                participants,
+                observers,
+            ) => Ok(ParticipantMessage::BatchVoteRequest(
+                term,
+                epoch,
+                values,
+                participants,
+                observers,
+            )),
+            TwoPhaseCommitMessage::BatchCommit(term, epoch, committed) => {
+                Ok(ParticipantMessage::BatchCommit(term, epoch, committed))
+            }
+            TwoPhaseCommitMessage::VoteResponseWithValue(_, _) => {
+                Err(InvalidStateError::with_message(
+                    "VoteResponseWithValue message cannot be handled by a participant".into(),
+                ))
+            }
+            TwoPhaseCommitMessage::ReadOnlyVoteResponse(_) => Err(InvalidStateError::with_message(
+                "ReadOnlyVoteResponse message cannot be handled by a participant".into(),
             )),
             TwoPhaseCommitMessage::DecisionAck(_) => Err(InvalidStateError::with_message(
                 "DecisionAck message cannot be handled by a participant".into(),
             )),
+            TwoPhaseCommitMessage::NewTermAck(_, _, _) => Err(InvalidStateError::with_message(
+                "NewTermAck message cannot be handled by a participant".into(),
+            )),
+            TwoPhaseCommitMessage::Outcome(_, _, _) => Err(InvalidStateError::with_message(
+                "Outcome message cannot be handled by a participant".into(),
+            )),
+            TwoPhaseCommitMessage::BatchVoteResponse(_, _) => Err(InvalidStateError::with_message(
+                "BatchVoteResponse message cannot be handled by a participant".into(),
+            )),
+            TwoPhaseCommitMessage::BatchOutcome(..) => Err(InvalidStateError::with_message(
+                "BatchOutcome message cannot be handled by a participant".into(),
+            )),
         }
     }
 }
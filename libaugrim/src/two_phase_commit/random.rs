@@ -0,0 +1,53 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use std::fmt::Debug;
+
+/// A source of randomness for the jitter added to the retries of the termination protocol.
+///
+/// Jitter spreads out the retries of participants which started waiting at the same time, such as
+/// after a network partition heals, so that they do not all reach the coordinator at once.
+///
+/// The algorithm must remain deterministic, so a random source holds no state of its own: the same
+/// seed must always return the same number. The algorithm derives the seed from the epoch, the
+/// number of the retry and the position of the participant among the participants of the epoch,
+/// so participants which share a source still wait different times.
+pub trait RandomSource: Debug + Send + Sync {
+    /// Return a random number for the given seed.
+    fn random(&self, seed: u64) -> u64;
+}
+
+/// An implementation of [`RandomSource`] which mixes the seed given by the algorithm with its own
+/// seed, using the SplitMix64 generator.
+#[derive(Clone, Debug, Default, Eq, PartialEq)]
+pub struct SeededRandomSource {
+    seed: u64,
+}
+
+impl SeededRandomSource {
+    pub fn new(seed: u64) -> Self {
+        SeededRandomSource { seed }
+    }
+}
+
+impl RandomSource for SeededRandomSource {
+    fn random(&self, seed: u64) -> u64 {
+        let mut z = self
+            .seed
+            .wrapping_add(seed.wrapping_add(1).wrapping_mul(0x9e37_79b9_7f4a_7c15));
+        z = (z ^ (z >> 30)).wrapping_mul(0xbf58_476d_1ce4_e5b9);
+        z = (z ^ (z >> 27)).wrapping_mul(0x94d0_49bb_1331_11eb);
+        z ^ (z >> 31)
+    }
+}
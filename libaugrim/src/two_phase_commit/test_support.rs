@@ -0,0 +1,274 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+//! Helpers shared by the tests of the two-phase commit algorithms.
+
+use std::cell::Cell;
+use std::collections::{HashMap, VecDeque};
+use std::rc::Rc;
+use std::time::{Duration, SystemTime};
+
+use crate::algorithm::Algorithm;
+use crate::time::TimeSource;
+
+use super::LinearTwoPhaseCommitAction;
+use super::LinearTwoPhaseCommitAlgorithm;
+use super::MultiTwoPhaseCommitAction;
+use super::MultiTwoPhaseCommitAlgorithm;
+use super::MultiTwoPhaseCommitEvent;
+use super::TransactionId;
+use super::TwoPhaseCommitAction;
+use super::TwoPhaseCommitActionNotification;
+use super::TwoPhaseCommitAlgorithm;
+use super::TwoPhaseCommitEvent;
+use super::TwoPhaseCommitMessage;
+
+/// A time source which only moves forward when advanced by the test, so that timeouts expire
+/// without waiting on the system clock. Clones share the same clock.
+#[derive(Clone)]
+pub(super) struct TestTimeSource {
+    now: Rc<Cell<SystemTime>>,
+}
+
+impl TestTimeSource {
+    pub(super) fn new() -> Self {
+        Self {
+            now: Rc::new(Cell::new(SystemTime::UNIX_EPOCH)),
+        }
+    }
+
+    /// Moves the clock forward by the given duration.
+    pub(super) fn advance(&self, duration: Duration) {
+        self.now.set(self.now.get() + duration);
+    }
+}
+
+impl TimeSource for TestTimeSource {
+    type Time = SystemTime;
+
+    fn now(&self) -> Self::Time {
+        self.now.get()
+    }
+}
+
+/// An action of an algorithm, reduced to what a `Network` needs to run it.
+pub(super) enum SimulatedAction<C, M, N> {
+    Update(C, Option<SystemTime>),
+    SendMessage(String, M),
+    Notify(N),
+}
+
+/// An algorithm which can be run by every process of a `Network`.
+pub(super) trait Simulated: Algorithm
+where
+    Self::Context: Clone,
+{
+    type Message: Clone;
+    type Notification;
+
+    /// Returns the event which delivers a message from the given process.
+    fn deliver_event(from: String, message: Self::Message) -> Self::Event;
+
+    fn alarm_event() -> Self::Event;
+
+    fn simulated_action(
+        action: Self::Action,
+    ) -> SimulatedAction<Self::Context, Self::Message, Self::Notification>;
+}
+
+/// A set of processes running an algorithm, each with its own context. Messages are queued in
+/// the order they are sent, and are only delivered when the test asks for it. The alarm last set
+/// by each process is kept, and only goes off once the test clock has reached it.
+pub(super) struct Network<A>
+where
+    A: Simulated,
+    A::Context: Clone,
+{
+    pub(super) algorithm: A,
+    pub(super) alarms: HashMap<String, SystemTime>,
+    pub(super) contexts: HashMap<String, A::Context>,
+    pub(super) messages: VecDeque<(String, String, A::Message)>,
+    pub(super) notifications: Vec<(String, A::Notification)>,
+    time: TestTimeSource,
+}
+
+impl<A> Network<A>
+where
+    A: Simulated,
+    A::Context: Clone,
+{
+    pub(super) fn new(
+        algorithm: A,
+        time: &TestTimeSource,
+        contexts: HashMap<String, A::Context>,
+    ) -> Self {
+        Self {
+            algorithm,
+            alarms: HashMap::new(),
+            contexts,
+            messages: VecDeque::new(),
+            notifications: Vec::new(),
+            time: time.clone(),
+        }
+    }
+
+    /// Processes an event on the given process and runs the resulting actions.
+    pub(super) fn event(&mut self, process: &str, event: A::Event) {
+        let mut context = self.contexts[process].clone();
+        for action in self.algorithm.event(event, context.clone()).unwrap() {
+            match A::simulated_action(action) {
+                SimulatedAction::Update(new, alarm) => {
+                    context = new;
+                    match alarm {
+                        Some(alarm) => self.alarms.insert(process.into(), alarm),
+                        None => self.alarms.remove(process),
+                    };
+                }
+                SimulatedAction::SendMessage(to, message) => {
+                    self.messages.push_back((process.into(), to, message))
+                }
+                SimulatedAction::Notify(notification) => {
+                    self.notifications.push((process.into(), notification))
+                }
+            }
+        }
+        self.contexts.insert(process.into(), context);
+    }
+
+    /// Processes an Alarm event on the given process, which must have set an alarm that is due.
+    pub(super) fn alarm(&mut self, process: &str) {
+        let alarm = self
+            .alarms
+            .remove(process)
+            .unwrap_or_else(|| panic!("{process} has not set an alarm"));
+        assert!(
+            alarm <= self.time.now(),
+            "the alarm of {process} is not due yet"
+        );
+        self.event(process, A::alarm_event());
+    }
+
+    /// Delivers the next message.
+    pub(super) fn deliver(&mut self) {
+        let (from, to, message) = self.messages.pop_front().unwrap();
+        self.event(&to, A::deliver_event(from, message));
+    }
+
+    /// Delivers messages until none are left, returning the delivered messages. Messages sent to
+    /// or from a process which is down are lost.
+    pub(super) fn deliver_all(&mut self, down: &[&str]) -> Vec<(String, String, A::Message)> {
+        let mut delivered = Vec::new();
+        while let Some((from, to, message)) = self.messages.pop_front() {
+            if down.contains(&from.as_str()) || down.contains(&to.as_str()) {
+                continue;
+            }
+            delivered.push((from.clone(), to.clone(), message.clone()));
+            self.event(&to, A::deliver_event(from, message));
+        }
+        delivered
+    }
+
+    pub(super) fn notified(&self, process: &str, notification: A::Notification) -> bool
+    where
+        A::Notification: PartialEq,
+    {
+        self.notifications.contains(&(process.into(), notification))
+    }
+}
+
+impl Simulated for TwoPhaseCommitAlgorithm<String, u32, TestTimeSource> {
+    type Message = TwoPhaseCommitMessage<String, u32>;
+    type Notification = TwoPhaseCommitActionNotification<String, u32>;
+
+    fn deliver_event(from: String, message: Self::Message) -> Self::Event {
+        TwoPhaseCommitEvent::Deliver(from, message)
+    }
+
+    fn alarm_event() -> Self::Event {
+        TwoPhaseCommitEvent::Alarm()
+    }
+
+    fn simulated_action(
+        action: Self::Action,
+    ) -> SimulatedAction<Self::Context, Self::Message, Self::Notification> {
+        match action {
+            TwoPhaseCommitAction::Update { context, alarm } => {
+                SimulatedAction::Update(context, alarm)
+            }
+            TwoPhaseCommitAction::SendMessage(to, message) => {
+                SimulatedAction::SendMessage(to, message)
+            }
+            TwoPhaseCommitAction::Notify(notification) => SimulatedAction::Notify(notification),
+        }
+    }
+}
+
+impl Simulated for LinearTwoPhaseCommitAlgorithm<String, u32, TestTimeSource> {
+    type Message = TwoPhaseCommitMessage<String, u32>;
+    type Notification = TwoPhaseCommitActionNotification<String, u32>;
+
+    fn deliver_event(from: String, message: Self::Message) -> Self::Event {
+        TwoPhaseCommitEvent::Deliver(from, message)
+    }
+
+    fn alarm_event() -> Self::Event {
+        TwoPhaseCommitEvent::Alarm()
+    }
+
+    fn simulated_action(
+        action: Self::Action,
+    ) -> SimulatedAction<Self::Context, Self::Message, Self::Notification> {
+        match action {
+            LinearTwoPhaseCommitAction::Update { context, alarm } => {
+                SimulatedAction::Update(context, alarm)
+            }
+            LinearTwoPhaseCommitAction::SendMessage(to, message) => {
+                SimulatedAction::SendMessage(to, message)
+            }
+            LinearTwoPhaseCommitAction::Notify(notification) => {
+                SimulatedAction::Notify(notification)
+            }
+        }
+    }
+}
+
+// The messages and notifications of many transactions are told apart by the transaction id.
+impl Simulated for MultiTwoPhaseCommitAlgorithm<String, u32, TestTimeSource> {
+    type Message = (TransactionId, TwoPhaseCommitMessage<String, u32>);
+    type Notification = (TransactionId, TwoPhaseCommitActionNotification<String, u32>);
+
+    fn deliver_event(from: String, (transaction_id, message): Self::Message) -> Self::Event {
+        MultiTwoPhaseCommitEvent::Deliver(from, transaction_id, message)
+    }
+
+    fn alarm_event() -> Self::Event {
+        MultiTwoPhaseCommitEvent::Alarm()
+    }
+
+    fn simulated_action(
+        action: Self::Action,
+    ) -> SimulatedAction<Self::Context, Self::Message, Self::Notification> {
+        match action {
+            MultiTwoPhaseCommitAction::Update { context, alarm } => {
+                SimulatedAction::Update(context, alarm)
+            }
+            MultiTwoPhaseCommitAction::SendMessage(to, transaction_id, message) => {
+                SimulatedAction::SendMessage(to, (transaction_id, message))
+            }
+            MultiTwoPhaseCommitAction::Notify(transaction_id, notification) => {
+                SimulatedAction::Notify((transaction_id, notification))
+            }
+        }
+    }
+}
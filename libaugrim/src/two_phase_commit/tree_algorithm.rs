@@ -0,0 +1,730 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::algorithm::{Algorithm, Value};
+use crate::error::{AlgorithmError, InvalidStateError};
+use crate::process::Process;
+use crate::time::TimeSource;
+
+use super::CoordinatorAction;
+use super::CoordinatorActionNotification;
+use super::CoordinatorAlgorithm;
+use super::CoordinatorEvent;
+use super::ParticipantAction;
+use super::ParticipantActionNotification;
+use super::ParticipantAlgorithm;
+use super::ParticipantEvent;
+use super::TreeTwoPhaseCommitAction;
+use super::TreeTwoPhaseCommitContext;
+use super::TwoPhaseCommitActionNotification;
+use super::TwoPhaseCommitConfig;
+use super::TwoPhaseCommitEvent;
+use super::TwoPhaseCommitMessage;
+use super::TwoPhaseCommitState;
+
+/// The algorithm for an inner node of a two-phase commit tree.
+///
+/// In tree two-phase commit, the root of the tree is a coordinator and the leaves are
+/// participants, both using [`TwoPhaseCommitAlgorithm`]. Each inner node is a participant to its
+/// parent and the coordinator of its children, so a message only crosses the edges of the tree
+/// rather than going from the root to every participant.
+///
+/// An inner node passes each VoteRequest from its parent on to its children. Once all children
+/// have voted yes, the application is asked for the node's own vote with a
+/// `ParticipantRequestForVote` notification, and the vote is sent to the parent. If a child votes
+/// no, the node votes no to its parent. The decision of the parent is then passed on to the
+/// children, and the node's decision ack is held back until the children have acknowledged the
+/// decision, so acks are also aggregated up the tree.
+///
+/// The application is only notified of the decisions of the parent's epochs; the epochs of the
+/// children are internal to the node.
+///
+/// [`TwoPhaseCommitAlgorithm`]: super::TwoPhaseCommitAlgorithm
+pub struct TreeTwoPhaseCommitAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource,
+{
+    coordinator: CoordinatorAlgorithm<P, V, TS>,
+    participant: ParticipantAlgorithm<P, V, TS>,
+    time_source: TS,
+}
+
+impl<P, V, TS> TreeTwoPhaseCommitAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource + Clone,
+{
+    /// Create a new algorithm using the default configuration.
+    pub fn new(time_source: TS) -> Self {
+        Self::with_config(time_source, TwoPhaseCommitConfig::default())
+    }
+
+    /// Create a new algorithm using the given configuration.
+    pub fn with_config(time_source: TS, config: TwoPhaseCommitConfig) -> Self {
+        Self {
+            coordinator: CoordinatorAlgorithm::new(time_source.clone(), config.clone()),
+            participant: ParticipantAlgorithm::new(time_source.clone(), config),
+            time_source,
+        }
+    }
+}
+
+// The context and the actions generated so far while processing an event.
+struct Step<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: crate::time::Time,
+{
+    context: TreeTwoPhaseCommitContext<P, V, T>,
+    actions: Vec<TreeTwoPhaseCommitAction<P, V, T>>,
+}
+
+impl<P, V, T> Step<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: crate::time::Time,
+{
+    fn push_update(&mut self) {
+        self.actions.push(TreeTwoPhaseCommitAction::Update {
+            context: self.context.clone(),
+            alarm: self.context.alarm(),
+        });
+    }
+}
+
+impl<P, V, TS> TreeTwoPhaseCommitAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource,
+{
+    // Items of a batch can not be passed on to the children, as the epochs of the children decide
+    // a single value.
+    fn batch_error() -> AlgorithmError {
+        AlgorithmError::InvalidState(InvalidStateError::with_message(
+            "batches can not be handled by a tree node".into(),
+        ))
+    }
+
+    // Process an event with the participant context of the node.
+    fn parent_event(
+        &self,
+        step: &mut Step<P, V, TS::Time>,
+        event: ParticipantEvent<P, V>,
+    ) -> Result<(), AlgorithmError> {
+        let actions = self
+            .participant
+            .event(event, step.context.parent.clone().try_into()?)?;
+
+        for action in actions {
+            match action {
+                ParticipantAction::Update { context, alarm } => {
+                    step.context.parent = context.into();
+                    step.context.parent_alarm = alarm;
+                    step.push_update();
+                }
+
+                // Hold back the decision ack until the children have acknowledged the decision.
+                ParticipantAction::SendMessage(_, TwoPhaseCommitMessage::DecisionAck(epoch))
+                    if step.context.children.state() != TwoPhaseCommitState::WaitingForStart =>
+                {
+                    step.context.pending_decision_ack = Some(epoch);
+                    step.push_update();
+                }
+
+                ParticipantAction::SendMessage(process, message) => step
+                    .actions
+                    .push(TreeTwoPhaseCommitAction::SendMessage(process, message)),
+
+                // The parent has requested a vote, so first request votes from the children. If
+                // the children have not finished their previous epoch, vote no.
+                ParticipantAction::Notify(ParticipantActionNotification::RequestForVote(value)) => {
+                    match step.context.children.state() {
+                        TwoPhaseCommitState::WaitingForStart => {
+                            self.children_event(step, CoordinatorEvent::Start(value))?
+                        }
+                        // The children have already been asked to vote, such as when recovering.
+                        TwoPhaseCommitState::Voting { .. }
+                        | TwoPhaseCommitState::WaitingForVote { .. } => (),
+                        _ => self.parent_event(step, ParticipantEvent::Vote(false))?,
+                    }
+                }
+
+                // The decision of the parent is passed on to the children.
+                ParticipantAction::Notify(ParticipantActionNotification::Commit(epoch, value)) => {
+                    step.actions.push(TreeTwoPhaseCommitAction::Notify(
+                        TwoPhaseCommitActionNotification::Commit(epoch, value),
+                    ));
+
+                    if matches!(
+                        step.context.children.state(),
+                        TwoPhaseCommitState::WaitingForVote { .. }
+                    ) {
+                        self.children_event(step, CoordinatorEvent::Vote(true))?;
+                    }
+                }
+                ParticipantAction::Notify(ParticipantActionNotification::Abort(epoch, value)) => {
+                    step.actions.push(TreeTwoPhaseCommitAction::Notify(
+                        TwoPhaseCommitActionNotification::Abort(epoch, value),
+                    ));
+
+                    if matches!(
+                        step.context.children.state(),
+                        TwoPhaseCommitState::Voting { .. }
+                            | TwoPhaseCommitState::WaitingForVote { .. }
+                    ) {
+                        let actions = self
+                            .coordinator
+                            .abort((*step.context.children).clone().try_into()?)?;
+                        self.children_actions(step, actions)?;
+                    }
+                }
+
+                ParticipantAction::Notify(ParticipantActionNotification::MessageDropped(
+                    message,
+                )) => step.actions.push(TreeTwoPhaseCommitAction::Notify(
+                    TwoPhaseCommitActionNotification::MessageDropped(message),
+                )),
+
+                // The children keep waiting for the decision as well, so the node is blocked as
+                // a whole.
+                ParticipantAction::Notify(ParticipantActionNotification::Blocked(epoch)) => {
+                    step.actions.push(TreeTwoPhaseCommitAction::Notify(
+                        TwoPhaseCommitActionNotification::ParticipantBlocked(epoch),
+                    ))
+                }
+
+                ParticipantAction::Notify(
+                    ParticipantActionNotification::RequestForBatchVote(..)
+                    | ParticipantActionNotification::BatchDecision(..),
+                ) => return Err(Self::batch_error()),
+            }
+        }
+
+        Ok(())
+    }
+
+    // Process an event with the coordinator context of the node.
+    fn children_event(
+        &self,
+        step: &mut Step<P, V, TS::Time>,
+        event: CoordinatorEvent<P, V>,
+    ) -> Result<(), AlgorithmError> {
+        let actions = self
+            .coordinator
+            .event(event, (*step.context.children).clone().try_into()?)?;
+        self.children_actions(step, actions)
+    }
+
+    // Process the actions of the coordinator context of the node.
+    fn children_actions(
+        &self,
+        step: &mut Step<P, V, TS::Time>,
+        actions: Vec<CoordinatorAction<P, V, TS::Time>>,
+    ) -> Result<(), AlgorithmError> {
+        for action in actions {
+            match action {
+                CoordinatorAction::Update { context, alarm } => {
+                    *step.context.children = context.into();
+                    step.context.children_alarm = alarm;
+                    step.push_update();
+                }
+
+                CoordinatorAction::SendMessage(process, message) => step
+                    .actions
+                    .push(TreeTwoPhaseCommitAction::SendMessage(process, message)),
+
+                // The children have finished the epoch, so the held back decision ack can be sent
+                // to the parent.
+                CoordinatorAction::Notify(CoordinatorActionNotification::RequestForStart(_)) => {
+                    if let Some(epoch) = step.context.pending_decision_ack.take() {
+                        step.push_update();
+                        step.actions.push(TreeTwoPhaseCommitAction::SendMessage(
+                            step.context.parent.coordinator().clone(),
+                            TwoPhaseCommitMessage::DecisionAck(epoch),
+                        ));
+                    }
+                }
+
+                // All children have voted yes, so request the node's own vote.
+                CoordinatorAction::Notify(CoordinatorActionNotification::RequestForVote(..)) => {
+                    if let (TwoPhaseCommitState::WaitingForVote { .. }, Some(value)) = (
+                        step.context.parent.state(),
+                        step.context.parent.value().clone(),
+                    ) {
+                        step.actions.push(TreeTwoPhaseCommitAction::Notify(
+                            TwoPhaseCommitActionNotification::ParticipantRequestForVote(value),
+                        ));
+                    }
+                }
+
+                // The application is notified of the decision of the parent instead.
+                CoordinatorAction::Notify(
+                    CoordinatorActionNotification::Commit(..)
+                    | CoordinatorActionNotification::VoteValues(..),
+                ) => (),
+
+                // A child has voted no or not voted in time, so vote no to the parent.
+                CoordinatorAction::Notify(CoordinatorActionNotification::Abort(..)) => {
+                    if matches!(
+                        step.context.parent.state(),
+                        TwoPhaseCommitState::WaitingForVote { .. }
+                    ) {
+                        self.parent_event(step, ParticipantEvent::Vote(false))?;
+                    }
+                }
+
+                CoordinatorAction::Notify(CoordinatorActionNotification::MessageDropped(
+                    message,
+                )) => step.actions.push(TreeTwoPhaseCommitAction::Notify(
+                    TwoPhaseCommitActionNotification::MessageDropped(message),
+                )),
+
+                CoordinatorAction::Notify(
+                    CoordinatorActionNotification::RequestForBatchVote(..)
+                    | CoordinatorActionNotification::BatchDecision(..),
+                ) => return Err(Self::batch_error()),
+            }
+        }
+
+        Ok(())
+    }
+}
+
+impl<P, V, TS> Algorithm for TreeTwoPhaseCommitAlgorithm<P, V, TS>
+where
+    P: Process,
+    V: Value,
+    TS: TimeSource,
+{
+    type Event = TwoPhaseCommitEvent<P, V>;
+    type Action = TreeTwoPhaseCommitAction<P, V, TS::Time>;
+    type Context = TreeTwoPhaseCommitContext<P, V, TS::Time>;
+
+    fn event(
+        &self,
+        event: Self::Event,
+        context: Self::Context,
+    ) -> Result<Vec<Self::Action>, AlgorithmError> {
+        let mut step = Step {
+            context,
+            actions: Vec::new(),
+        };
+
+        match event {
+            // The alarm is the earliest alarm of the two contexts, so pass it on to each context
+            // whose alarm is due. The alarm is always set again, as the alarm of the other context
+            // may still be pending.
+            TwoPhaseCommitEvent::Alarm() => {
+                let now = self.time_source.now();
+
+                if matches!(step.context.parent_alarm, Some(alarm) if alarm <= now) {
+                    self.parent_event(&mut step, ParticipantEvent::Alarm())?;
+                }
+
+                if !matches!(
+                    step.context.children.state(),
+                    TwoPhaseCommitState::WaitingForVote { .. }
+                ) && matches!(step.context.children_alarm, Some(alarm) if alarm <= now)
+                {
+                    self.children_event(&mut step, CoordinatorEvent::Alarm())?;
+                }
+
+                step.push_update();
+            }
+
+            TwoPhaseCommitEvent::Recover() => {
+                self.children_event(&mut step, CoordinatorEvent::Recover())?;
+                self.parent_event(&mut step, ParticipantEvent::Recover())?;
+            }
+
+            // Messages which can only be handled by a coordinator are from the children, and
+            // a DecisionRequest is from a child if the sender is one of the children.
+            TwoPhaseCommitEvent::Deliver(process, message) => {
+                let from_child = match &message {
+                    TwoPhaseCommitMessage::VoteResponse(..)
+                    | TwoPhaseCommitMessage::VoteResponseWithValue(..)
+                    | TwoPhaseCommitMessage::ReadOnlyVoteResponse(..)
+                    | TwoPhaseCommitMessage::DecisionAck(..)
+                    | TwoPhaseCommitMessage::NewTermAck(..)
+                    | TwoPhaseCommitMessage::BatchVoteResponse(..) => true,
+                    TwoPhaseCommitMessage::DecisionRequest(..) => step
+                        .context
+                        .children
+                        .participants()
+                        .map(|children| children.iter().any(|child| child.process == process))
+                        .unwrap_or(false),
+                    TwoPhaseCommitMessage::VoteRequest(..)
+                    | TwoPhaseCommitMessage::Commit(..)
+                    | TwoPhaseCommitMessage::Abort(..)
+                    | TwoPhaseCommitMessage::NewTerm(..)
+                    | TwoPhaseCommitMessage::Outcome(..)
+                    | TwoPhaseCommitMessage::BatchVoteRequest(..)
+                    | TwoPhaseCommitMessage::BatchCommit(..)
+                    | TwoPhaseCommitMessage::BatchOutcome(..) => false,
+                };
+
+                if from_child {
+                    self.children_event(
+                        &mut step,
+                        CoordinatorEvent::Deliver(process, message.try_into()?),
+                    )?;
+                } else {
+                    self.parent_event(
+                        &mut step,
+                        ParticipantEvent::Deliver(process, message.try_into()?),
+                    )?;
+                }
+            }
+
+            TwoPhaseCommitEvent::Vote(vote) => {
+                self.parent_event(&mut step, ParticipantEvent::Vote(vote))?
+            }
+            TwoPhaseCommitEvent::VoteWithValue(value) => {
+                self.parent_event(&mut step, ParticipantEvent::VoteWithValue(value))?
+            }
+            // Cancelling the epoch of the children also votes no to the parent. Once the node has
+            // voted yes to the parent, the parent may commit, so the children can no longer be
+            // aborted.
+            TwoPhaseCommitEvent::Cancel() => {
+                if !matches!(
+                    step.context.parent.state(),
+                    TwoPhaseCommitState::WaitingForVote { .. }
+                ) {
+                    return Err(AlgorithmError::InvalidState(
+                        InvalidStateError::with_message(
+                            "Cancel event when the node is not waiting to vote".into(),
+                        ),
+                    ));
+                }
+                self.children_event(&mut step, CoordinatorEvent::Cancel())?
+            }
+            TwoPhaseCommitEvent::Reconfigure(processes) => {
+                self.children_event(&mut step, CoordinatorEvent::Reconfigure(processes))?
+            }
+
+            // The epochs of the children are started by the parent, the items of a batch are not
+            // passed on to the children, and a read-only node would not learn the decision it
+            // must pass on to its children.
+            TwoPhaseCommitEvent::Start(_)
+            | TwoPhaseCommitEvent::StartWithValues(_)
+            | TwoPhaseCommitEvent::StartBatch(_)
+            | TwoPhaseCommitEvent::BatchVote(_)
+            | TwoPhaseCommitEvent::TakeOver(_)
+            | TwoPhaseCommitEvent::ReadOnlyVote() => {
+                return Err(AlgorithmError::InvalidState(
+                    InvalidStateError::with_message(
+                        "event can not be handled by a tree node".into(),
+                    ),
+                ))
+            }
+        }
+
+        Ok(step.actions)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::time::SystemTime;
+
+    use crate::two_phase_commit::test_support::{
+        Network, Simulated, SimulatedAction, TestTimeSource,
+    };
+
+    use crate::two_phase_commit::{
+        Participant, TwoPhaseCommitAction, TwoPhaseCommitAlgorithm, TwoPhaseCommitContext,
+        TwoPhaseCommitContextBuilder,
+    };
+
+    type Message = TwoPhaseCommitMessage<String, u32>;
+    type Notification = TwoPhaseCommitActionNotification<String, u32>;
+
+    // The context of a process in a tree. The inner node "n" runs the tree algorithm, and every
+    // other process runs the two-phase commit algorithm.
+    #[derive(Clone)]
+    enum TreeProcess {
+        Node(TreeTwoPhaseCommitContext<String, u32, SystemTime>),
+        Process(TwoPhaseCommitContext<String, u32, SystemTime>),
+    }
+
+    // Runs the algorithm of each process in a tree.
+    struct TreeAlgorithms {
+        algorithm: TwoPhaseCommitAlgorithm<String, u32, TestTimeSource>,
+        tree_algorithm: TreeTwoPhaseCommitAlgorithm<String, u32, TestTimeSource>,
+    }
+
+    impl Algorithm for TreeAlgorithms {
+        type Event = TwoPhaseCommitEvent<String, u32>;
+        type Action = SimulatedAction<TreeProcess, Message, Notification>;
+        type Context = TreeProcess;
+
+        fn event(
+            &self,
+            event: Self::Event,
+            context: Self::Context,
+        ) -> Result<Vec<Self::Action>, AlgorithmError> {
+            let actions = match context {
+                TreeProcess::Node(context) => self
+                    .tree_algorithm
+                    .event(event, context)?
+                    .into_iter()
+                    .map(|action| match action {
+                        TreeTwoPhaseCommitAction::Update { context, alarm } => {
+                            SimulatedAction::Update(TreeProcess::Node(context), alarm)
+                        }
+                        TreeTwoPhaseCommitAction::SendMessage(to, message) => {
+                            SimulatedAction::SendMessage(to, message)
+                        }
+                        TreeTwoPhaseCommitAction::Notify(notification) => {
+                            SimulatedAction::Notify(notification)
+                        }
+                    })
+                    .collect(),
+                TreeProcess::Process(context) => self
+                    .algorithm
+                    .event(event, context)?
+                    .into_iter()
+                    .map(|action| match action {
+                        TwoPhaseCommitAction::Update { context, alarm } => {
+                            SimulatedAction::Update(TreeProcess::Process(context), alarm)
+                        }
+                        TwoPhaseCommitAction::SendMessage(to, message) => {
+                            SimulatedAction::SendMessage(to, message)
+                        }
+                        TwoPhaseCommitAction::Notify(notification) => {
+                            SimulatedAction::Notify(notification)
+                        }
+                    })
+                    .collect(),
+            };
+            Ok(actions)
+        }
+    }
+
+    impl Simulated for TreeAlgorithms {
+        type Message = Message;
+        type Notification = Notification;
+
+        fn deliver_event(from: String, message: Self::Message) -> Self::Event {
+            TwoPhaseCommitEvent::Deliver(from, message)
+        }
+
+        fn alarm_event() -> Self::Event {
+            TwoPhaseCommitEvent::Alarm()
+        }
+
+        fn simulated_action(
+            action: Self::Action,
+        ) -> SimulatedAction<Self::Context, Self::Message, Self::Notification> {
+            action
+        }
+    }
+
+    // A tree with the root "c", the inner node "n" and the leaves "a" and "b".
+    type Tree = Network<TreeAlgorithms>;
+
+    fn tree() -> Tree {
+        let coordinator = |process: &str, epoch, participants: &[&str]| {
+            TwoPhaseCommitContextBuilder::new()
+                .with_coordinator(process.to_string())
+                .with_epoch(epoch)
+                .with_state(TwoPhaseCommitState::WaitingForStart)
+                .with_this_process(process.to_string())
+                .with_participants(
+                    participants
+                        .iter()
+                        .map(|p| Participant::new(p.to_string()))
+                        .collect(),
+                )
+                .build()
+                .unwrap()
+        };
+        let participant = |process: &str, coordinator: &str, participants: &[&str]| {
+            TwoPhaseCommitContextBuilder::new()
+                .with_coordinator(coordinator.to_string())
+                .with_epoch(0)
+                .with_state(TwoPhaseCommitState::WaitingForVoteRequest)
+                .with_this_process(process.to_string())
+                .with_participant_processes(participants.iter().map(|p| p.to_string()).collect())
+                .build()
+                .unwrap()
+        };
+
+        let node = TreeTwoPhaseCommitContext::new(
+            participant("n", "c", &["n"]),
+            coordinator("n", 1, &["a", "b"]),
+        )
+        .unwrap();
+
+        let mut contexts = HashMap::new();
+        contexts.insert(
+            "c".to_string(),
+            TreeProcess::Process(coordinator("c", 1, &["n"])),
+        );
+        contexts.insert("n".to_string(), TreeProcess::Node(node));
+        contexts.insert(
+            "a".to_string(),
+            TreeProcess::Process(participant("a", "n", &["a", "b"])),
+        );
+        contexts.insert(
+            "b".to_string(),
+            TreeProcess::Process(participant("b", "n", &["a", "b"])),
+        );
+
+        let time = TestTimeSource::new();
+        let algorithms = TreeAlgorithms {
+            algorithm: TwoPhaseCommitAlgorithm::new(time.clone()),
+            tree_algorithm: TreeTwoPhaseCommitAlgorithm::new(time.clone()),
+        };
+        Network::new(algorithms, &time, contexts)
+    }
+
+    impl Tree {
+        // Returns the context of the inner node "n".
+        fn node(&self) -> &TreeTwoPhaseCommitContext<String, u32, SystemTime> {
+            match &self.contexts["n"] {
+                TreeProcess::Node(context) => context,
+                TreeProcess::Process(_) => panic!("process n is not an inner node"),
+            }
+        }
+
+        // Returns the context of a process other than the inner node.
+        fn context(&self, process: &str) -> &TwoPhaseCommitContext<String, u32, SystemTime> {
+            match &self.contexts[process] {
+                TreeProcess::Process(context) => context,
+                TreeProcess::Node(_) => panic!("process {} is an inner node", process),
+            }
+        }
+    }
+
+    /// Test that an inner node passes the VoteRequest on to its children, only votes once all of
+    /// its children have voted yes, and holds back its decision ack until the children have
+    /// acknowledged the decision.
+    #[test]
+    fn tree_commit() {
+        let mut tree = tree();
+
+        tree.event("c", TwoPhaseCommitEvent::Start(7));
+        let delivered = tree.deliver_all(&[]);
+        assert_eq!(
+            delivered,
+            vec![
+                (
+                    "c".into(),
+                    "n".into(),
+                    Message::VoteRequest(0, 1, 7, vec!["n".into()], vec![])
+                ),
+                (
+                    "n".into(),
+                    "a".into(),
+                    Message::VoteRequest(0, 1, 7, vec!["a".into(), "b".into()], vec![])
+                ),
+                (
+                    "n".into(),
+                    "b".into(),
+                    Message::VoteRequest(0, 1, 7, vec!["a".into(), "b".into()], vec![])
+                ),
+            ]
+        );
+
+        tree.event("a", TwoPhaseCommitEvent::Vote(true));
+        tree.event("b", TwoPhaseCommitEvent::Vote(true));
+        let delivered = tree.deliver_all(&[]);
+        assert!(delivered.iter().all(|(_, to, _)| to == "n"));
+        assert!(tree.notified("n", Notification::ParticipantRequestForVote(7)));
+
+        tree.event("n", TwoPhaseCommitEvent::Vote(true));
+        tree.deliver_all(&[]);
+        tree.event("c", TwoPhaseCommitEvent::Vote(true));
+        let delivered = tree.deliver_all(&[]);
+
+        assert!(tree.notified("n", Notification::Commit(1, Some(7))));
+        assert!(tree.notified("a", Notification::Commit(1, Some(7))));
+        assert!(tree.notified("b", Notification::Commit(1, Some(7))));
+
+        // The ack of the node is only sent once both children have acked.
+        let acks = delivered
+            .iter()
+            .filter(|(_, _, message)| matches!(message, Message::DecisionAck(_)))
+            .map(|(from, to, _)| (from.as_str(), to.as_str()))
+            .collect::<Vec<_>>();
+        assert_eq!(acks, vec![("a", "n"), ("b", "n"), ("n", "c")]);
+
+        assert_eq!(*tree.node().pending_decision_ack(), None);
+        assert_eq!(*tree.node().parent().epoch(), 1);
+        assert_eq!(*tree.node().children().epoch(), 2);
+        assert_eq!(*tree.context("c").epoch(), 2);
+    }
+
+    /// Test that a no vote from a child is sent up the tree as a no vote of the inner node.
+    #[test]
+    fn tree_abort() {
+        let mut tree = tree();
+
+        tree.event("c", TwoPhaseCommitEvent::Start(7));
+        tree.deliver_all(&[]);
+        tree.event("a", TwoPhaseCommitEvent::Vote(true));
+        tree.event("b", TwoPhaseCommitEvent::Vote(false));
+        tree.deliver_all(&[]);
+
+        assert!(!tree.notified("n", Notification::ParticipantRequestForVote(7)));
+        assert!(tree.notified("n", Notification::Abort(1, Some(7))));
+        assert!(tree.notified("a", Notification::Abort(1, Some(7))));
+        assert!(tree.notified("c", Notification::Abort(1, Some(7))));
+    }
+
+    /// Test that an inner node can not be cancelled once it has voted yes to its parent, so the
+    /// children are never aborted while the parent commits.
+    #[test]
+    fn tree_cancel_after_vote() {
+        let mut tree = tree();
+
+        tree.event("c", TwoPhaseCommitEvent::Start(7));
+        tree.deliver_all(&[]);
+        tree.event("a", TwoPhaseCommitEvent::Vote(true));
+        tree.event("b", TwoPhaseCommitEvent::Vote(true));
+        tree.deliver_all(&[]);
+        tree.event("n", TwoPhaseCommitEvent::Vote(true));
+
+        assert!(tree
+            .algorithm
+            .tree_algorithm
+            .event(TwoPhaseCommitEvent::Cancel(), tree.node().clone())
+            .is_err());
+
+        tree.deliver_all(&[]);
+        tree.event("c", TwoPhaseCommitEvent::Vote(true));
+        tree.deliver_all(&[]);
+
+        for process in ["c", "n", "a", "b"] {
+            assert!(!tree
+                .notifications
+                .iter()
+                .any(|(notified, notification)| notified == process
+                    && matches!(notification, Notification::Abort(..))));
+        }
+        assert!(tree.notified("n", Notification::Commit(1, Some(7))));
+        assert!(tree.notified("a", Notification::Commit(1, Some(7))));
+        assert!(tree.notified("b", Notification::Commit(1, Some(7))));
+    }
+}
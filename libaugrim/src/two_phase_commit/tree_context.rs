@@ -0,0 +1,119 @@
+// Copyright 2023 Bitwise IO, Inc.
+//
+// Licensed under the Apache License, Version 2.0 (the "License");
+// you may not use this file except in compliance with the License.
+// You may obtain a copy of the License at
+//
+//     http://www.apache.org/licenses/LICENSE-2.0
+//
+// Unless required by applicable law or agreed to in writing, software
+// distributed under the License is distributed on an "AS IS" BASIS,
+// WITHOUT WARRANTIES OR CONDITIONS OF ANY KIND, either express or implied.
+// See the License for the specific language governing permissions and
+// limitations under the License.
+
+use crate::algorithm::Value;
+use crate::error::InvalidStateError;
+use crate::process::Process;
+use crate::time::Time;
+
+use super::Epoch;
+use super::TwoPhaseCommitContext;
+use super::TwoPhaseCommitState;
+
+/// The context of an inner node of a two-phase commit tree.
+///
+/// An inner node is a participant to its parent and the coordinator of its children. The context
+/// holds a participant context for the epochs of the parent, and a coordinator context for the
+/// epochs of the children.
+#[derive(Clone, Debug, PartialEq)]
+pub struct TreeTwoPhaseCommitContext<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    pub(super) parent: TwoPhaseCommitContext<P, V, T>,
+    // The coordinator context is boxed to keep the size of the context, and of the Update action
+    // which holds it, close to that of the other actions.
+    pub(super) children: Box<TwoPhaseCommitContext<P, V, T>>,
+    pub(super) parent_alarm: Option<T>,
+    pub(super) children_alarm: Option<T>,
+    pub(super) pending_decision_ack: Option<Epoch>,
+}
+
+impl<P, V, T> TreeTwoPhaseCommitContext<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    /// Create the context of an inner node from a participant context, whose coordinator is the
+    /// parent of the node, and a coordinator context, whose participants are the children of the
+    /// node.
+    pub fn new(
+        parent: TwoPhaseCommitContext<P, V, T>,
+        children: TwoPhaseCommitContext<P, V, T>,
+    ) -> Result<Self, InvalidStateError> {
+        if parent.this_process() != children.this_process() {
+            return Err(InvalidStateError::with_message(
+                "parent and children contexts must be for the same process".into(),
+            ));
+        }
+
+        if parent.participant_processes().is_none() {
+            return Err(InvalidStateError::with_message(
+                "parent context must be a participant context".into(),
+            ));
+        }
+
+        match children.participants() {
+            Some(participants) if !participants.is_empty() => (),
+            _ => {
+                return Err(InvalidStateError::with_message(
+                    "children context must be a coordinator context with at least one participant"
+                        .into(),
+                ))
+            }
+        }
+
+        Ok(Self {
+            parent,
+            children: Box::new(children),
+            parent_alarm: None,
+            children_alarm: None,
+            pending_decision_ack: None,
+        })
+    }
+
+    /// The participant context of the node, for the epochs of its parent.
+    pub fn parent(&self) -> &TwoPhaseCommitContext<P, V, T> {
+        &self.parent
+    }
+
+    /// The coordinator context of the node, for the epochs of its children.
+    pub fn children(&self) -> &TwoPhaseCommitContext<P, V, T> {
+        &self.children
+    }
+
+    /// The epoch of the parent whose decision ack is held back until the children have
+    /// acknowledged the decision.
+    pub fn pending_decision_ack(&self) -> &Option<Epoch> {
+        &self.pending_decision_ack
+    }
+
+    // Returns the earliest alarm of the two contexts. The alarm of the children context is not
+    // used while waiting for the node's own vote, as that vote is made by the parent context.
+    pub(super) fn alarm(&self) -> Option<T> {
+        let children_alarm = match self.children.state() {
+            TwoPhaseCommitState::WaitingForVote { .. } => None,
+            _ => self.children_alarm,
+        };
+
+        match (self.parent_alarm, children_alarm) {
+            (Some(parent), Some(children)) if children < parent => Some(children),
+            (Some(parent), _) => Some(parent),
+            (None, children) => children,
+        }
+    }
+}
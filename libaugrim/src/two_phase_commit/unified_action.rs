@@ -16,6 +16,7 @@ use crate::algorithm::Value;
 use crate::process::Process;
 use crate::time::Time;
 
+use super::Epoch;
 use super::TwoPhaseCommitContext;
 use super::TwoPhaseCommitMessage;
 
@@ -27,22 +28,46 @@ where
     T: Time,
 {
     Update {
-        context: TwoPhaseCommitContext<P, T>,
+        context: TwoPhaseCommitContext<P, V, T>,
         alarm: Option<T>,
     },
-    SendMessage(P, TwoPhaseCommitMessage<V>),
-    Notify(TwoPhaseCommitActionNotification<V>),
+    SendMessage(P, TwoPhaseCommitMessage<P, V>),
+    Notify(TwoPhaseCommitActionNotification<P, V>),
 }
 
 #[derive(Debug, Eq, PartialEq)]
-pub enum TwoPhaseCommitActionNotification<V>
+pub enum TwoPhaseCommitActionNotification<P, V>
 where
+    P: Process,
     V: Value,
 {
-    Abort(),
-    Commit(),
+    /// The epoch was aborted. Includes the value of the epoch, if it is known.
+    Abort(Epoch, Option<V>),
+    /// The epoch was committed. Includes the value of the epoch, if it is known.
+    Commit(Epoch, Option<V>),
+    /// The coordinator committed the epoch, and participants attached the given values to their
+    /// yes votes. This precedes the Commit notification, and is only sent if any values were
+    /// attached.
+    CoordinatorVoteValues(Epoch, Vec<(P, V)>),
     MessageDropped(String),
-    RequestForStart(),
-    CoordinatorRequestForVote(),
+    /// The coordinator is ready to start the given epoch.
+    RequestForStart(Epoch),
+    /// All participants voted yes, and the coordinator's vote is requested. Includes the value of
+    /// the epoch, if it is known, and the values which participants attached to their yes votes.
+    CoordinatorRequestForVote(Epoch, Option<V>, Vec<(P, V)>),
     ParticipantRequestForVote(V),
+    /// All participants voted on the items of a batch, and the coordinator's vote on each item is
+    /// requested. Includes the items of the batch, and whether each item was accepted by every
+    /// participant.
+    CoordinatorRequestForBatchVote(Epoch, Vec<V>, Vec<bool>),
+    /// A vote on each item of a batch is requested from the participant.
+    ParticipantRequestForBatchVote(Vec<V>),
+    /// The batch epoch was decided. Includes the items which were committed, followed by the
+    /// items which were aborted.
+    BatchDecision(Epoch, Vec<V>, Vec<V>),
+    /// The participant voted yes in the epoch, but has not learned the decision after the
+    /// configured number of DecisionRequest retries, and has stopped retrying. The participant
+    /// still accepts the decision if it is sent; a Recover event sends the DecisionRequest
+    /// messages again.
+    ParticipantBlocked(Epoch),
 }
@@ -18,10 +18,13 @@ use crate::process::Process;
 use crate::time::TimeSource;
 
 use super::CoordinatorAlgorithm;
+use super::ObserverAlgorithm;
 use super::ParticipantAlgorithm;
 use super::TwoPhaseCommitAction;
+use super::TwoPhaseCommitConfig;
 use super::TwoPhaseCommitContext;
 use super::TwoPhaseCommitEvent;
+use super::TwoPhaseCommitState;
 
 pub struct TwoPhaseCommitAlgorithm<P, V, TS>
 where
@@ -30,6 +33,7 @@ where
     TS: TimeSource,
 {
     coordinator: CoordinatorAlgorithm<P, V, TS>,
+    observer: ObserverAlgorithm<P, V, TS>,
     participant: ParticipantAlgorithm<P, V, TS>,
 }
 
@@ -39,10 +43,17 @@ where
     V: Value,
     TS: TimeSource + Clone,
 {
+    /// Create a new algorithm using the default configuration.
     pub fn new(time_source: TS) -> Self {
+        Self::with_config(time_source, TwoPhaseCommitConfig::default())
+    }
+
+    /// Create a new algorithm using the given configuration.
+    pub fn with_config(time_source: TS, config: TwoPhaseCommitConfig) -> Self {
         Self {
-            coordinator: CoordinatorAlgorithm::new(time_source.clone()),
-            participant: ParticipantAlgorithm::new(time_source),
+            coordinator: CoordinatorAlgorithm::new(time_source.clone(), config.clone()),
+            observer: ObserverAlgorithm::new(config.clone()),
+            participant: ParticipantAlgorithm::new(time_source, config),
         }
     }
 }
@@ -55,7 +66,7 @@ where
 {
     type Event = TwoPhaseCommitEvent<P, V>;
     type Action = TwoPhaseCommitAction<P, V, TS::Time>;
-    type Context = TwoPhaseCommitContext<P, TS::Time>;
+    type Context = TwoPhaseCommitContext<P, V, TS::Time>;
 
     fn event(
         &self,
@@ -66,6 +77,10 @@ where
             self.coordinator
                 .event(event.try_into()?, context.try_into()?)
                 .map(|v| v.into_iter().map(|a| a.into()).collect())
+        } else if context.state() == TwoPhaseCommitState::Observing {
+            self.observer
+                .event(event.try_into()?, context.try_into()?)
+                .map(|v| v.into_iter().map(|a| a.into()).collect())
         } else {
             self.participant
                 .event(event.try_into()?, context.try_into()?)
@@ -73,3 +88,1979 @@ where
         }
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    use std::collections::HashMap;
+    use std::time::{Duration, SystemTime};
+
+    use crate::two_phase_commit::test_support::{Network, TestTimeSource};
+    use crate::two_phase_commit::{
+        Decision, Epoch, Participant, Presumption, SeededRandomSource,
+        TwoPhaseCommitActionNotification, TwoPhaseCommitConfigBuilder,
+        TwoPhaseCommitContextBuilder, TwoPhaseCommitMessage, TwoPhaseCommitState,
+    };
+
+    type TestAlgorithm = TwoPhaseCommitAlgorithm<String, u32, TestTimeSource>;
+    type TestAction = TwoPhaseCommitAction<String, u32, SystemTime>;
+    type TestContext = TwoPhaseCommitContext<String, u32, SystemTime>;
+
+    fn algorithm(time: &TestTimeSource, presumption: Presumption) -> TestAlgorithm {
+        let config = TwoPhaseCommitConfigBuilder::new()
+            .with_presumption(presumption)
+            .build()
+            .unwrap();
+        TwoPhaseCommitAlgorithm::with_config(time.clone(), config)
+    }
+
+    fn processes(names: &[&str]) -> Vec<String> {
+        names.iter().map(|name| name.to_string()).collect()
+    }
+
+    // Returns a context for coordinator "c", waiting to start the given epoch.
+    fn coordinator_context(epoch: Epoch, participants: &[&str]) -> TestContext {
+        TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(epoch)
+            .with_state(TwoPhaseCommitState::WaitingForStart)
+            .with_this_process("c".to_string())
+            .with_participants(
+                participants
+                    .iter()
+                    .map(|p| Participant::new(p.to_string()))
+                    .collect(),
+            )
+            .build()
+            .unwrap()
+    }
+
+    fn deliver(
+        algorithm: &TestAlgorithm,
+        context: TestContext,
+        from: &str,
+        message: TwoPhaseCommitMessage<String, u32>,
+    ) -> Vec<TestAction> {
+        algorithm
+            .event(TwoPhaseCommitEvent::Deliver(from.into(), message), context)
+            .unwrap()
+    }
+
+    // Returns the context from the last Update action in the list of actions.
+    fn last_context(actions: &[TestAction]) -> TestContext {
+        actions
+            .iter()
+            .rev()
+            .find_map(|action| match action {
+                TwoPhaseCommitAction::Update { context, .. } => Some(context.clone()),
+                _ => None,
+            })
+            .expect("no update action")
+    }
+
+    // Returns the messages sent by the list of actions.
+    fn sent_messages(actions: &[TestAction]) -> Vec<(&str, TwoPhaseCommitMessage<String, u32>)> {
+        actions
+            .iter()
+            .filter_map(|action| match action {
+                TwoPhaseCommitAction::SendMessage(process, message) => {
+                    Some((process.as_str(), message.clone()))
+                }
+                _ => None,
+            })
+            .collect()
+    }
+
+    // A coordinator "c" and a set of participants, with the messages sent between them.
+    type Cluster = Network<TestAlgorithm>;
+
+    fn cluster(algorithm: TestAlgorithm, time: &TestTimeSource, participants: &[&str]) -> Cluster {
+        let mut contexts = HashMap::new();
+        contexts.insert("c".to_string(), coordinator_context(1, participants));
+        for participant in participants {
+            let context = TwoPhaseCommitContextBuilder::new()
+                .with_coordinator("c".to_string())
+                .with_epoch(0)
+                .with_state(TwoPhaseCommitState::WaitingForVoteRequest)
+                .with_this_process(participant.to_string())
+                .with_participant_processes(participants.iter().map(|p| p.to_string()).collect())
+                .build()
+                .unwrap();
+            contexts.insert(participant.to_string(), context);
+        }
+
+        Network::new(algorithm, time, contexts)
+    }
+
+    impl Cluster {
+        fn state(&self, process: &str) -> TwoPhaseCommitState<SystemTime> {
+            self.contexts[process].state()
+        }
+    }
+
+    // Adds an observer "o" to the cluster, which the coordinator sends the outcome of each epoch
+    // to.
+    fn add_observer(cluster: &mut Cluster, participants: &[&str]) {
+        let coordinator = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(1)
+            .with_state(TwoPhaseCommitState::WaitingForStart)
+            .with_this_process("c".to_string())
+            .with_participants(
+                participants
+                    .iter()
+                    .map(|p| Participant::new(p.to_string()))
+                    .collect(),
+            )
+            .with_observers(processes(&["o"]))
+            .build()
+            .unwrap();
+        let observer = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(0)
+            .with_state(TwoPhaseCommitState::Observing)
+            .with_this_process("o".to_string())
+            .build()
+            .unwrap();
+        cluster.contexts.insert("c".into(), coordinator);
+        cluster.contexts.insert("o".into(), observer);
+    }
+
+    // Adds a standby process "n" to the cluster, which may take over as coordinator.
+    fn add_standby_coordinator(cluster: &mut Cluster, participants: &[&str], observers: &[&str]) {
+        let context = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("n".to_string())
+            .with_epoch(0)
+            .with_state(TwoPhaseCommitState::WaitingForStart)
+            .with_this_process("n".to_string())
+            .with_participants(
+                participants
+                    .iter()
+                    .map(|p| Participant::new(p.to_string()))
+                    .collect(),
+            )
+            .with_observers(processes(observers))
+            .build()
+            .unwrap();
+        cluster.contexts.insert("n".into(), context);
+    }
+
+    // Returns an algorithm with a short decision timeout, so participants may be woken up for
+    // the termination protocol without a long wait.
+    fn termination_algorithm(time: &TestTimeSource) -> TestAlgorithm {
+        let config = TwoPhaseCommitConfigBuilder::new()
+            .with_decision_timeout(Duration::from_millis(1))
+            .build()
+            .unwrap();
+        TwoPhaseCommitAlgorithm::with_config(time.clone(), config)
+    }
+
+    /// Test that with presumed abort, the coordinator does not wait for decision acks after an
+    /// abort and answers a DecisionRequest for a forgotten epoch with Abort.
+    #[test]
+    fn presumed_abort_skips_acks_for_abort() {
+        let algorithm = algorithm(&TestTimeSource::new(), Presumption::Abort);
+        let context = coordinator_context(1, &["p1", "p2"]);
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Start(7), context)
+            .unwrap();
+
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p1",
+            TwoPhaseCommitMessage::VoteResponse(1, true),
+        );
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p2",
+            TwoPhaseCommitMessage::VoteResponse(1, false),
+        );
+
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("p1", TwoPhaseCommitMessage::Abort(0, 1))]
+        );
+        assert!(actions.contains(&TwoPhaseCommitAction::Notify(
+            TwoPhaseCommitActionNotification::Abort(1, Some(7))
+        )));
+        assert_eq!(
+            actions.last(),
+            Some(&TwoPhaseCommitAction::Notify(
+                TwoPhaseCommitActionNotification::RequestForStart(2)
+            ))
+        );
+
+        let context = last_context(&actions);
+        assert_eq!(*context.epoch(), 2);
+        assert_eq!(context.state(), TwoPhaseCommitState::WaitingForStart);
+        assert_eq!(*context.last_commit_epoch(), None);
+
+        let actions = deliver(
+            &algorithm,
+            context,
+            "p1",
+            TwoPhaseCommitMessage::DecisionRequest(1),
+        );
+        assert_eq!(
+            actions,
+            vec![TwoPhaseCommitAction::SendMessage(
+                "p1".into(),
+                TwoPhaseCommitMessage::Abort(0, 1)
+            )]
+        );
+    }
+
+    /// Test that with presumed abort, the coordinator does not move on to the next epoch until
+    /// every participant has acked a commit, and sends the commit again after the ack timeout.
+    #[test]
+    fn presumed_abort_resends_commit_until_acked() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Abort), &time, &["p1", "p2"]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.event("p2", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+
+        // The commit never reaches p2.
+        cluster.event("c", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&["p2"]);
+        assert!(cluster.notified("p1", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+
+        // The coordinator keeps the commit after the ack timeout, rather than leaving p2 to be
+        // answered with the presumed abort.
+        for _ in 0..2 {
+            time.advance(Duration::from_secs(60));
+            cluster.alarm("c");
+            assert!(matches!(
+                cluster.state("c"),
+                TwoPhaseCommitState::WaitingForDecisionAck { .. }
+            ));
+            assert!(cluster.deliver_all(&["p2"]).is_empty());
+        }
+
+        time.advance(Duration::from_secs(60));
+        cluster.alarm("c");
+        assert_eq!(
+            cluster.deliver_all(&[]),
+            vec![
+                ("c".into(), "p2".into(), TwoPhaseCommitMessage::Commit(0, 1)),
+                (
+                    "p2".into(),
+                    "c".into(),
+                    TwoPhaseCommitMessage::DecisionAck(1)
+                ),
+            ]
+        );
+        assert!(cluster.notified("p2", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+        assert_eq!(cluster.state("c"), TwoPhaseCommitState::WaitingForStart);
+        assert_eq!(*cluster.contexts["c"].epoch(), 2);
+    }
+
+    /// Test that with presumed commit, the coordinator records the epoch before sending any
+    /// VoteRequest, advances without waiting for decision acks after a commit and presumes commit
+    /// for an epoch older than the last commit epoch.
+    #[test]
+    fn presumed_commit_skips_acks_for_commit() {
+        let algorithm = algorithm(&TestTimeSource::new(), Presumption::Commit);
+        let context = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(5)
+            .with_last_commit_epoch(3)
+            .with_state(TwoPhaseCommitState::WaitingForStart)
+            .with_this_process("c".to_string())
+            .with_participants(vec![Participant::new("p1".to_string())])
+            .build()
+            .unwrap();
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Start(7), context.clone())
+            .unwrap();
+        assert!(matches!(
+            actions.as_slice(),
+            [
+                TwoPhaseCommitAction::Update { .. },
+                TwoPhaseCommitAction::SendMessage(
+                    _,
+                    TwoPhaseCommitMessage::VoteRequest(0, 5, 7, ..)
+                ),
+            ]
+        ));
+        let voting_context = last_context(&actions);
+
+        let actions = deliver(
+            &algorithm,
+            context,
+            "p1",
+            TwoPhaseCommitMessage::DecisionRequest(2),
+        );
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("p1", TwoPhaseCommitMessage::Commit(0, 2))]
+        );
+
+        let actions = deliver(
+            &algorithm,
+            voting_context,
+            "p1",
+            TwoPhaseCommitMessage::VoteResponse(5, true),
+        );
+        let context = last_context(&actions);
+        assert!(matches!(
+            context.state(),
+            TwoPhaseCommitState::WaitingForVote { .. }
+        ));
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Vote(true), context)
+            .unwrap();
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("p1", TwoPhaseCommitMessage::Commit(0, 5))]
+        );
+
+        let context = last_context(&actions);
+        assert_eq!(*context.epoch(), 6);
+        assert_eq!(context.state(), TwoPhaseCommitState::WaitingForStart);
+        assert_eq!(*context.last_commit_epoch(), Some(5));
+    }
+
+    /// Test that with presumed commit, the coordinator keeps the ack timeout alarm while acks for
+    /// an abort are missing, and sends the abort again to a participant whose ack was lost.
+    #[test]
+    fn presumed_commit_resends_abort_until_acked() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Commit), &time, &["p1", "p2"]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.event("p2", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+        cluster.event("c", TwoPhaseCommitEvent::Vote(false));
+
+        // Both participants abort, but the ack from p1 is lost.
+        cluster.deliver();
+        cluster.deliver();
+        assert_eq!(
+            cluster.messages.pop_front(),
+            Some((
+                "p1".into(),
+                "c".into(),
+                TwoPhaseCommitMessage::DecisionAck(1)
+            ))
+        );
+        cluster.deliver_all(&[]);
+        assert!(matches!(
+            cluster.state("c"),
+            TwoPhaseCommitState::WaitingForDecisionAck { .. }
+        ));
+
+        time.advance(Duration::from_secs(60));
+        cluster.alarm("c");
+        assert_eq!(
+            cluster.deliver_all(&[]),
+            vec![
+                ("c".into(), "p1".into(), TwoPhaseCommitMessage::Abort(0, 1)),
+                (
+                    "p1".into(),
+                    "c".into(),
+                    TwoPhaseCommitMessage::DecisionAck(1)
+                ),
+            ]
+        );
+        assert_eq!(cluster.state("c"), TwoPhaseCommitState::WaitingForStart);
+        assert_eq!(*cluster.contexts["c"].epoch(), 2);
+    }
+
+    /// Test that with presumed commit, an abort is sent to every participant and kept until all
+    /// of them have acked it, including a participant whose yes vote was lost and one which never
+    /// received the VoteRequest.
+    #[test]
+    fn presumed_commit_sends_abort_to_all_participants() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(
+            algorithm(&time, Presumption::Commit),
+            &time,
+            &["p1", "p2", "p3"],
+        );
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&["p3"]);
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.event("p2", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&["p2"]);
+
+        // The vote of p2 is lost, so the coordinator aborts after the vote timeout.
+        time.advance(Duration::from_secs(60));
+        cluster.alarm("c");
+        let delivered = cluster.deliver_all(&[]);
+        for participant in ["p1", "p2", "p3"] {
+            assert!(delivered.contains(&(
+                "c".into(),
+                participant.into(),
+                TwoPhaseCommitMessage::Abort(0, 1)
+            )));
+            assert!(delivered.contains(&(
+                participant.into(),
+                "c".into(),
+                TwoPhaseCommitMessage::DecisionAck(1)
+            )));
+        }
+        assert!(cluster.notified("p2", TwoPhaseCommitActionNotification::Abort(1, Some(7))));
+        assert_eq!(cluster.state("c"), TwoPhaseCommitState::WaitingForStart);
+        assert_eq!(*cluster.contexts["c"].epoch(), 2);
+    }
+
+    /// Test that a read-only participant is not sent the decision and the coordinator does not
+    /// wait for its decision ack.
+    #[test]
+    fn read_only_participant_skips_decision() {
+        let algorithm = algorithm(&TestTimeSource::new(), Presumption::Nothing);
+        let context = coordinator_context(1, &["p1", "p2"]);
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Start(7), context)
+            .unwrap();
+
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p2",
+            TwoPhaseCommitMessage::ReadOnlyVoteResponse(1),
+        );
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p1",
+            TwoPhaseCommitMessage::VoteResponse(1, true),
+        );
+        let context = last_context(&actions);
+        assert!(matches!(
+            context.state(),
+            TwoPhaseCommitState::WaitingForVote { .. }
+        ));
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Vote(true), context)
+            .unwrap();
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("p1", TwoPhaseCommitMessage::Commit(0, 1))]
+        );
+
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p1",
+            TwoPhaseCommitMessage::DecisionAck(1),
+        );
+        let context = last_context(&actions);
+        assert_eq!(*context.epoch(), 2);
+        assert_eq!(context.state(), TwoPhaseCommitState::WaitingForStart);
+    }
+
+    /// Test that the coordinator aborts as soon as the first NO vote arrives, before any other
+    /// participant has voted, and that late votes for the aborted epoch are answered correctly:
+    /// a late YES vote is sent Abort and a late NO vote is dropped.
+    #[test]
+    fn abort_on_first_no_vote() {
+        let algorithm = algorithm(&TestTimeSource::new(), Presumption::Nothing);
+        let context = coordinator_context(1, &["p1", "p2", "p3"]);
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Start(7), context)
+            .unwrap();
+
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p2",
+            TwoPhaseCommitMessage::VoteResponse(1, false),
+        );
+        assert!(sent_messages(&actions).is_empty());
+        assert!(actions.contains(&TwoPhaseCommitAction::Notify(
+            TwoPhaseCommitActionNotification::Abort(1, Some(7))
+        )));
+        let context = last_context(&actions);
+        assert_eq!(*context.epoch(), 2);
+        assert_eq!(context.state(), TwoPhaseCommitState::WaitingForStart);
+
+        let actions = deliver(
+            &algorithm,
+            context.clone(),
+            "p1",
+            TwoPhaseCommitMessage::VoteResponse(1, true),
+        );
+        assert_eq!(
+            actions,
+            vec![TwoPhaseCommitAction::SendMessage(
+                "p1".into(),
+                TwoPhaseCommitMessage::Abort(0, 1)
+            )]
+        );
+
+        let actions = deliver(
+            &algorithm,
+            context,
+            "p3",
+            TwoPhaseCommitMessage::VoteResponse(1, false),
+        );
+        assert!(sent_messages(&actions).is_empty());
+    }
+
+    /// Test that the coordinator aborts on a NO vote which arrives after a YES vote, sending Abort
+    /// only to the YES voter, and that a YES vote arriving afterwards, while the coordinator is
+    /// still waiting for decision acks, is sent Abort and recorded.
+    #[test]
+    fn abort_on_no_vote_after_yes_vote() {
+        let algorithm = algorithm(&TestTimeSource::new(), Presumption::Nothing);
+        let context = coordinator_context(1, &["p1", "p2", "p3"]);
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Start(7), context)
+            .unwrap();
+
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p1",
+            TwoPhaseCommitMessage::VoteResponse(1, true),
+        );
+        assert!(sent_messages(&actions).is_empty());
+
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p2",
+            TwoPhaseCommitMessage::VoteResponse(1, false),
+        );
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("p1", TwoPhaseCommitMessage::Abort(0, 1))]
+        );
+        let context = last_context(&actions);
+        assert!(matches!(
+            context.state(),
+            TwoPhaseCommitState::WaitingForDecisionAck { .. }
+        ));
+
+        let actions = deliver(
+            &algorithm,
+            context,
+            "p3",
+            TwoPhaseCommitMessage::VoteResponse(1, true),
+        );
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("p3", TwoPhaseCommitMessage::Abort(0, 1))]
+        );
+        let context = last_context(&actions);
+        assert_eq!(*context.epoch(), 1);
+        assert_eq!(
+            context.participants().unwrap()[2].vote,
+            Some(true),
+            "late vote was not recorded"
+        );
+    }
+
+    /// Test that a participant whose application does not vote within the local vote timeout
+    /// aborts on its own and sends a NO vote to the coordinator.
+    #[test]
+    fn participant_votes_no_after_local_vote_timeout() {
+        let time = TestTimeSource::new();
+        let algorithm: TestAlgorithm = TwoPhaseCommitAlgorithm::new(time.clone());
+        let context = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(0)
+            .with_state(TwoPhaseCommitState::WaitingForVoteRequest)
+            .with_this_process("p1".to_string())
+            .with_participant_processes(vec!["p1".to_string(), "p2".to_string()])
+            .build()
+            .unwrap();
+
+        let actions = deliver(
+            &algorithm,
+            context,
+            "c",
+            TwoPhaseCommitMessage::VoteRequest(0, 1, 7, processes(&["p1", "p2"]), vec![]),
+        );
+        let alarm = actions.iter().find_map(|action| match action {
+            TwoPhaseCommitAction::Update {
+                alarm: Some(alarm), ..
+            } => Some(*alarm),
+            _ => None,
+        });
+        assert!(alarm.is_some(), "local vote timeout alarm was not set");
+
+        // An early alarm is ignored.
+        let context = last_context(&actions);
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Alarm(), context)
+            .unwrap();
+        assert!(actions.is_empty());
+
+        let context = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(1)
+            .with_state(TwoPhaseCommitState::WaitingForVote {
+                local_vote_timeout_start: time.now(),
+            })
+            .with_this_process("p1".to_string())
+            .with_participant_processes(vec!["p1".to_string(), "p2".to_string()])
+            .with_value(7)
+            .build()
+            .unwrap();
+        time.advance(Duration::from_secs(60));
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Alarm(), context)
+            .unwrap();
+        assert!(actions.contains(&TwoPhaseCommitAction::Notify(
+            TwoPhaseCommitActionNotification::Abort(1, Some(7))
+        )));
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("c", TwoPhaseCommitMessage::VoteResponse(1, false))]
+        );
+        assert_eq!(
+            last_context(&actions).state(),
+            TwoPhaseCommitState::WaitingForVoteRequest
+        );
+    }
+
+    /// Test that a coordinator whose application does not vote within the coordinator vote
+    /// timeout, after all participants voted yes, decides to abort.
+    #[test]
+    fn coordinator_aborts_after_coordinator_vote_timeout() {
+        let time = TestTimeSource::new();
+        let config = TwoPhaseCommitConfigBuilder::new()
+            .with_coordinator_vote_timeout(Duration::from_secs(1))
+            .build()
+            .unwrap();
+        let algorithm: TestAlgorithm = TwoPhaseCommitAlgorithm::with_config(time.clone(), config);
+        let context = coordinator_context(1, &["p1"]);
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Start(7), context)
+            .unwrap();
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p1",
+            TwoPhaseCommitMessage::VoteResponse(1, true),
+        );
+        assert!(matches!(
+            actions.last(),
+            Some(TwoPhaseCommitAction::Notify(
+                TwoPhaseCommitActionNotification::CoordinatorRequestForVote(..)
+            ))
+        ));
+        assert!(matches!(
+            &actions[actions.len() - 2],
+            TwoPhaseCommitAction::Update { alarm: Some(alarm), .. }
+                if *alarm == time.now() + Duration::from_secs(1)
+        ));
+
+        let context = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(1)
+            .with_state(TwoPhaseCommitState::WaitingForVote {
+                local_vote_timeout_start: time.now(),
+            })
+            .with_this_process("c".to_string())
+            .with_participants(vec![Participant {
+                process: "p1".to_string(),
+                vote: Some(true),
+                read_only: false,
+                decision_ack: false,
+                vote_value: None,
+                batch_votes: None,
+                new_term_ack: None,
+            }])
+            .build()
+            .unwrap();
+        time.advance(Duration::from_secs(2));
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Alarm(), context)
+            .unwrap();
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("p1", TwoPhaseCommitMessage::Abort(0, 1))]
+        );
+        assert!(actions.contains(&TwoPhaseCommitAction::Notify(
+            TwoPhaseCommitActionNotification::Abort(1, None)
+        )));
+    }
+
+    /// Test that a Start event is rejected unless the coordinator is waiting for one.
+    #[test]
+    fn start_outside_waiting_for_start_is_rejected() {
+        let algorithm = algorithm(&TestTimeSource::new(), Presumption::Nothing);
+        let context = coordinator_context(1, &["p1"]);
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Start(7), context)
+            .unwrap();
+        let context = last_context(&actions);
+
+        assert!(matches!(
+            algorithm.event(TwoPhaseCommitEvent::Start(8), context),
+            Err(AlgorithmError::InvalidState(_))
+        ));
+    }
+
+    /// Test that a StartWithValues event sends each participant its own value, and that it is
+    /// rejected unless there is exactly one value for each participant, in the order of the
+    /// participants.
+    #[test]
+    fn start_with_values_sends_each_participant_its_value() {
+        let algorithm = algorithm(&TestTimeSource::new(), Presumption::Nothing);
+        let context = coordinator_context(1, &["p1", "p2"]);
+
+        assert!(matches!(
+            algorithm.event(
+                TwoPhaseCommitEvent::StartWithValues(vec![("p1".into(), 7), ("p3".into(), 8)]),
+                context.clone()
+            ),
+            Err(AlgorithmError::InvalidState(_))
+        ));
+        assert!(matches!(
+            algorithm.event(
+                TwoPhaseCommitEvent::StartWithValues(vec![("p2".into(), 8), ("p1".into(), 7)]),
+                context.clone()
+            ),
+            Err(AlgorithmError::InvalidState(_))
+        ));
+
+        let actions = algorithm
+            .event(
+                TwoPhaseCommitEvent::StartWithValues(vec![("p1".into(), 7), ("p2".into(), 8)]),
+                context,
+            )
+            .unwrap();
+        assert_eq!(
+            sent_messages(&actions),
+            vec![
+                (
+                    "p1",
+                    TwoPhaseCommitMessage::VoteRequest(0, 1, 7, processes(&["p1", "p2"]), vec![])
+                ),
+                (
+                    "p2",
+                    TwoPhaseCommitMessage::VoteRequest(0, 1, 8, processes(&["p1", "p2"]), vec![])
+                ),
+            ]
+        );
+        assert!(matches!(
+            last_context(&actions).state(),
+            TwoPhaseCommitState::Voting { .. }
+        ));
+    }
+
+    /// Test that a value attached to a participant's yes vote is passed to the coordinator's
+    /// RequestForVote and Commit notifications.
+    #[test]
+    fn vote_with_value_reaches_coordinator_notifications() {
+        let algorithm = algorithm(&TestTimeSource::new(), Presumption::Nothing);
+
+        let participant_context = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(0)
+            .with_state(TwoPhaseCommitState::WaitingForVoteRequest)
+            .with_this_process("p1".to_string())
+            .with_participant_processes(vec!["p1".to_string(), "p2".to_string()])
+            .build()
+            .unwrap();
+        let actions = deliver(
+            &algorithm,
+            participant_context,
+            "c",
+            TwoPhaseCommitMessage::VoteRequest(0, 1, 7, processes(&["p1", "p2"]), vec![]),
+        );
+        let actions = algorithm
+            .event(
+                TwoPhaseCommitEvent::VoteWithValue(42),
+                last_context(&actions),
+            )
+            .unwrap();
+        let vote_response = sent_messages(&actions);
+        assert_eq!(
+            vote_response,
+            vec![("c", TwoPhaseCommitMessage::VoteResponseWithValue(1, 42))]
+        );
+
+        let context = coordinator_context(1, &["p1", "p2"]);
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Start(7), context)
+            .unwrap();
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p1",
+            vote_response[0].1.clone(),
+        );
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p2",
+            TwoPhaseCommitMessage::VoteResponse(1, true),
+        );
+        assert_eq!(
+            actions.last(),
+            Some(&TwoPhaseCommitAction::Notify(
+                TwoPhaseCommitActionNotification::CoordinatorRequestForVote(
+                    1,
+                    Some(7),
+                    vec![("p1".into(), 42)]
+                )
+            ))
+        );
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Vote(true), last_context(&actions))
+            .unwrap();
+        let notifications = actions
+            .into_iter()
+            .filter_map(|action| match action {
+                TwoPhaseCommitAction::Notify(notification) => Some(notification),
+                _ => None,
+            })
+            .collect::<Vec<_>>();
+        assert_eq!(
+            notifications,
+            vec![
+                TwoPhaseCommitActionNotification::CoordinatorVoteValues(1, vec![("p1".into(), 42)]),
+                TwoPhaseCommitActionNotification::Commit(1, Some(7)),
+            ]
+        );
+    }
+
+    /// Test that a participant includes the epoch and the value from the VoteRequest in its
+    /// decision notification, and clears the value once the epoch is decided.
+    #[test]
+    fn participant_decision_includes_epoch_and_value() {
+        let algorithm = algorithm(&TestTimeSource::new(), Presumption::Nothing);
+        let context = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(0)
+            .with_state(TwoPhaseCommitState::WaitingForVoteRequest)
+            .with_this_process("p1".to_string())
+            .with_participant_processes(vec!["p1".to_string(), "p2".to_string()])
+            .build()
+            .unwrap();
+
+        let actions = deliver(
+            &algorithm,
+            context,
+            "c",
+            TwoPhaseCommitMessage::VoteRequest(0, 1, 7, processes(&["p1", "p2"]), vec![]),
+        );
+        let context = last_context(&actions);
+        assert_eq!(*context.value(), Some(7));
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Vote(true), context)
+            .unwrap();
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "c",
+            TwoPhaseCommitMessage::Commit(0, 1),
+        );
+        assert!(actions.contains(&TwoPhaseCommitAction::Notify(
+            TwoPhaseCommitActionNotification::Commit(1, Some(7))
+        )));
+        assert_eq!(*last_context(&actions).value(), None);
+    }
+
+    /// Test that the decision history keeps the decisions of the most recent epochs only, and is
+    /// used by a participant to answer a DecisionRequest for an epoch before the last commit.
+    #[test]
+    fn decision_history_answers_older_epochs() {
+        let config = TwoPhaseCommitConfigBuilder::new()
+            .with_decision_history_size(2)
+            .build()
+            .unwrap();
+        let algorithm: TestAlgorithm =
+            TwoPhaseCommitAlgorithm::with_config(TestTimeSource::new(), config);
+
+        let mut context = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(0)
+            .with_state(TwoPhaseCommitState::WaitingForVoteRequest)
+            .with_this_process("p1".to_string())
+            .with_participant_processes(vec!["p1".to_string(), "p2".to_string()])
+            .build()
+            .unwrap();
+
+        // Commit epoch 1, then abort epochs 2 and 3.
+        for (epoch, vote) in [(1, true), (2, false), (3, false)] {
+            let actions = deliver(
+                &algorithm,
+                context,
+                "c",
+                TwoPhaseCommitMessage::VoteRequest(0, epoch, 7, processes(&["p1", "p2"]), vec![]),
+            );
+            let actions = algorithm
+                .event(TwoPhaseCommitEvent::Vote(vote), last_context(&actions))
+                .unwrap();
+            context = last_context(&actions);
+            if vote {
+                let actions = deliver(
+                    &algorithm,
+                    context,
+                    "c",
+                    TwoPhaseCommitMessage::Commit(0, epoch),
+                );
+                context = last_context(&actions);
+            }
+        }
+
+        // Epoch 1 has been pruned from the history.
+        assert_eq!(
+            *context.decision_history(),
+            vec![(2, Decision::Abort), (3, Decision::Abort)]
+        );
+
+        // Now commit epoch 4; epoch 3 is before the last commit epoch but is answered from the
+        // decision history.
+        let actions = deliver(
+            &algorithm,
+            context,
+            "c",
+            TwoPhaseCommitMessage::VoteRequest(0, 4, 7, processes(&["p1", "p2"]), vec![]),
+        );
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Vote(true), last_context(&actions))
+            .unwrap();
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "c",
+            TwoPhaseCommitMessage::Commit(0, 4),
+        );
+        let context = last_context(&actions);
+
+        let actions = deliver(
+            &algorithm,
+            context.clone(),
+            "p2",
+            TwoPhaseCommitMessage::DecisionRequest(3),
+        );
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("p2", TwoPhaseCommitMessage::Abort(0, 3))]
+        );
+
+        let actions = deliver(
+            &algorithm,
+            context,
+            "p2",
+            TwoPhaseCommitMessage::DecisionRequest(1),
+        );
+        assert!(sent_messages(&actions).is_empty());
+    }
+
+    /// Test that a participant which voted yes and missed the commit decision, while the
+    /// coordinator is down, learns the decision from another participant.
+    #[test]
+    fn termination_protocol_commit_from_peer() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(termination_algorithm(&time), &time, &["p1", "p2"]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.event("p2", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+
+        // The coordinator commits, but the decision is lost on its way to p1.
+        cluster.event("c", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&["p1"]);
+        assert!(cluster.notified("p2", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+        assert!(matches!(
+            cluster.state("p1"),
+            TwoPhaseCommitState::Voted { .. }
+        ));
+
+        // With the coordinator down, p1 times out and asks p2 for the decision.
+        time.advance(Duration::from_millis(5));
+        cluster.alarm("p1");
+        cluster.deliver_all(&["c"]);
+
+        assert!(cluster.notified("p1", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+        assert_eq!(
+            cluster.state("p1"),
+            TwoPhaseCommitState::WaitingForVoteRequest
+        );
+        assert_eq!(*cluster.contexts["p1"].last_commit_epoch(), Some(1));
+    }
+
+    /// Test that a participant which has not yet voted unilaterally aborts when asked for the
+    /// decision by a blocked participant, which then learns the abort decision.
+    #[test]
+    fn termination_protocol_abort_from_peer_waiting_for_vote() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(termination_algorithm(&time), &time, &["p1", "p2"]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+
+        // p1 votes yes, but the coordinator goes down before receiving the vote, and p2 has not
+        // yet voted.
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&["c"]);
+
+        time.advance(Duration::from_millis(5));
+        cluster.alarm("p1");
+        cluster.deliver_all(&["c"]);
+
+        assert!(cluster.notified("p2", TwoPhaseCommitActionNotification::Abort(1, Some(7))));
+        assert!(cluster.notified("p1", TwoPhaseCommitActionNotification::Abort(1, Some(7))));
+        assert_eq!(
+            cluster.state("p1"),
+            TwoPhaseCommitState::WaitingForVoteRequest
+        );
+        assert_eq!(
+            cluster.state("p2"),
+            TwoPhaseCommitState::WaitingForVoteRequest
+        );
+    }
+
+    /// Test that the coordinator answers a DecisionRequest from a participant which missed the
+    /// decision, and drops a DecisionRequest from a process which is not a participant.
+    #[test]
+    fn termination_protocol_commit_from_coordinator() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(termination_algorithm(&time), &time, &["p1", "p2"]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.event("p2", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+
+        // The coordinator commits, but the decision is lost on its way to p1.
+        cluster.event("c", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&["p1"]);
+
+        // A process which is not a participant is only answered from the decision history.
+        cluster.messages.push_back((
+            "x".into(),
+            "c".into(),
+            TwoPhaseCommitMessage::DecisionRequest(2),
+        ));
+        cluster.deliver_all(&[]);
+        assert!(cluster.notified(
+            "c",
+            TwoPhaseCommitActionNotification::MessageDropped(
+                "sender process is not a participant".into()
+            )
+        ));
+
+        // With p2 down, only the coordinator can answer p1.
+        time.advance(Duration::from_millis(5));
+        cluster.alarm("p1");
+        cluster.deliver_all(&["p2"]);
+        assert!(cluster.notified("p1", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+    }
+
+    /// Test that a participant which has not yet received the VoteRequest unilaterally aborts
+    /// when asked for the decision, and drops the VoteRequest if it arrives later.
+    #[test]
+    fn termination_protocol_abort_from_peer_without_vote_request() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(termination_algorithm(&time), &time, &["p1", "p2"]);
+
+        // The VoteRequest to p2 is delayed.
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        let delayed = cluster
+            .messages
+            .iter()
+            .position(|(_, to, _)| to == "p2")
+            .and_then(|index| cluster.messages.remove(index))
+            .unwrap();
+        cluster.deliver_all(&[]);
+
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+
+        time.advance(Duration::from_millis(5));
+        cluster.alarm("p1");
+        cluster.deliver_all(&[]);
+
+        assert!(cluster.notified("p1", TwoPhaseCommitActionNotification::Abort(1, Some(7))));
+        assert!(cluster.notified("p2", TwoPhaseCommitActionNotification::Abort(1, None)));
+
+        // The coordinator also received the NO vote from p2 and aborted the epoch.
+        assert!(cluster.notified("c", TwoPhaseCommitActionNotification::Abort(1, Some(7))));
+
+        cluster.messages.push_back(delayed);
+        cluster.deliver_all(&[]);
+        assert_eq!(
+            cluster.state("p2"),
+            TwoPhaseCommitState::WaitingForVoteRequest
+        );
+        assert_eq!(*cluster.contexts["p2"].epoch(), 1);
+    }
+
+    /// Test that a coordinator recovered in the Voting state aborts the epoch, since votes
+    /// received before it went down may have been lost.
+    #[test]
+    fn coordinator_recover_in_voting_aborts() {
+        let algorithm = algorithm(&TestTimeSource::new(), Presumption::Nothing);
+
+        let actions = algorithm
+            .event(
+                TwoPhaseCommitEvent::Start(7),
+                coordinator_context(1, &["p1", "p2"]),
+            )
+            .unwrap();
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p1",
+            TwoPhaseCommitMessage::VoteResponse(1, true),
+        );
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Recover(), last_context(&actions))
+            .unwrap();
+
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("p1", TwoPhaseCommitMessage::Abort(0, 1))]
+        );
+        assert!(actions.contains(&TwoPhaseCommitAction::Notify(
+            TwoPhaseCommitActionNotification::Abort(1, Some(7))
+        )));
+    }
+
+    /// Test that a coordinator recovered after persisting a commit decision sends the decision
+    /// again to each participant which has not acked it, and waits for the remaining acks.
+    #[test]
+    fn coordinator_recover_resends_decision() {
+        let algorithm = algorithm(&TestTimeSource::new(), Presumption::Nothing);
+
+        let mut actions = algorithm
+            .event(
+                TwoPhaseCommitEvent::Start(7),
+                coordinator_context(1, &["p1", "p2"]),
+            )
+            .unwrap();
+        for participant in ["p1", "p2"] {
+            actions = deliver(
+                &algorithm,
+                last_context(&actions),
+                participant,
+                TwoPhaseCommitMessage::VoteResponse(1, true),
+            );
+        }
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Vote(true), last_context(&actions))
+            .unwrap();
+
+        // Go down after the Commit state is persisted, but before anything else is run.
+        let context = match &actions[0] {
+            TwoPhaseCommitAction::Update { context, .. } => context.clone(),
+            action => panic!("unexpected action: {action:?}"),
+        };
+        assert_eq!(context.state(), TwoPhaseCommitState::Commit);
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Recover(), context)
+            .unwrap();
+        assert_eq!(
+            sent_messages(&actions),
+            vec![
+                ("p1", TwoPhaseCommitMessage::Commit(0, 1)),
+                ("p2", TwoPhaseCommitMessage::Commit(0, 1)),
+            ]
+        );
+        assert!(actions.contains(&TwoPhaseCommitAction::Notify(
+            TwoPhaseCommitActionNotification::Commit(1, Some(7))
+        )));
+        assert!(matches!(
+            last_context(&actions).state(),
+            TwoPhaseCommitState::WaitingForDecisionAck { .. }
+        ));
+
+        // Go down again after p1 has acked.
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p1",
+            TwoPhaseCommitMessage::DecisionAck(1),
+        );
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Recover(), last_context(&actions))
+            .unwrap();
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("p2", TwoPhaseCommitMessage::Commit(0, 1))]
+        );
+        assert!(matches!(
+            actions.last(),
+            Some(TwoPhaseCommitAction::Update { alarm: Some(_), .. })
+        ));
+    }
+
+    /// Test that a participant recovered while waiting for the application's vote notifies the
+    /// RequestForVote again, and one recovered after voting starts the termination protocol
+    /// without waiting for the decision timeout.
+    #[test]
+    fn participant_recover() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Nothing), &time, &["p1", "p2"]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+
+        cluster.notifications.clear();
+        cluster.event("p1", TwoPhaseCommitEvent::Recover());
+        assert_eq!(
+            cluster.notifications,
+            vec![(
+                "p1".to_string(),
+                TwoPhaseCommitActionNotification::ParticipantRequestForVote(7)
+            )]
+        );
+
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&["c"]);
+
+        cluster.event("p1", TwoPhaseCommitEvent::Recover());
+        let requests: Vec<_> = cluster
+            .messages
+            .iter()
+            .map(|(from, to, message)| (from.as_str(), to.as_str(), message.clone()))
+            .collect();
+        assert_eq!(
+            requests,
+            vec![
+                ("p1", "p2", TwoPhaseCommitMessage::DecisionRequest(1)),
+                ("p1", "c", TwoPhaseCommitMessage::DecisionRequest(1)),
+            ]
+        );
+        assert!(matches!(
+            cluster.state("p1"),
+            TwoPhaseCommitState::Voted { vote: true, .. }
+        ));
+    }
+
+    /// Test that a Reconfigure event is only accepted between epochs, and that the new
+    /// participants are sent to every participant with the next VoteRequest.
+    #[test]
+    fn reconfigure_between_epochs() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Nothing), &time, &["p1", "p2"]);
+
+        // Add p3, whose context is created as it joins, and remove p2.
+        let p3 = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(0)
+            .with_state(TwoPhaseCommitState::WaitingForVoteRequest)
+            .with_this_process("p3".to_string())
+            .with_participant_processes(vec![])
+            .build()
+            .unwrap();
+        cluster.contexts.insert("p3".into(), p3);
+        cluster.event(
+            "c",
+            TwoPhaseCommitEvent::Reconfigure(processes(&["p1", "p3"])),
+        );
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        let vote_requests: Vec<_> = cluster
+            .messages
+            .iter()
+            .map(|(_, to, message)| (to.as_str(), message.clone()))
+            .collect();
+        assert_eq!(
+            vote_requests,
+            vec![
+                (
+                    "p1",
+                    TwoPhaseCommitMessage::VoteRequest(0, 1, 7, processes(&["p1", "p3"]), vec![])
+                ),
+                (
+                    "p3",
+                    TwoPhaseCommitMessage::VoteRequest(0, 1, 7, processes(&["p1", "p3"]), vec![])
+                ),
+            ]
+        );
+        cluster.deliver_all(&[]);
+        assert_eq!(
+            cluster.contexts["p1"].participant_processes(),
+            Some(&processes(&["p1", "p3"]))
+        );
+
+        // The participants can not change while voting.
+        assert!(cluster
+            .algorithm
+            .event(
+                TwoPhaseCommitEvent::Reconfigure(processes(&["p1"])),
+                cluster.contexts["c"].clone(),
+            )
+            .is_err());
+
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.event("p3", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+        cluster.event("c", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+        assert!(cluster.notified("p3", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+        assert!(matches!(
+            cluster.state("c"),
+            TwoPhaseCommitState::WaitingForStart
+        ));
+    }
+
+    /// Test that a participant removed by a Reconfigure event while waiting for the decision is
+    /// answered from the coordinator's decision history, and that a participant which joined
+    /// later does not infer the decision of an epoch before it joined.
+    #[test]
+    fn reconfigure_decision_requests() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Commit), &time, &["p1", "p2"]);
+
+        // Epoch 1 commits, but the Commit to p2 is lost.
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.event("p2", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+        cluster.event("c", TwoPhaseCommitEvent::Vote(true));
+        cluster.messages.retain(|(_, to, _)| to != "p2");
+        cluster.deliver_all(&[]);
+        assert!(cluster.notified("p1", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+
+        // Add p3 and remove p2, then abort epoch 2.
+        let p3 = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(0)
+            .with_state(TwoPhaseCommitState::WaitingForVoteRequest)
+            .with_this_process("p3".to_string())
+            .with_participant_processes(vec![])
+            .build()
+            .unwrap();
+        cluster.contexts.insert("p3".into(), p3);
+        cluster.event(
+            "c",
+            TwoPhaseCommitEvent::Reconfigure(processes(&["p1", "p3"])),
+        );
+        cluster.event("c", TwoPhaseCommitEvent::Start(8));
+        cluster.deliver_all(&[]);
+        cluster.event("p3", TwoPhaseCommitEvent::Vote(false));
+        cluster.deliver_all(&[]);
+        assert!(cluster.notified("p3", TwoPhaseCommitActionNotification::Abort(2, Some(8))));
+        assert_eq!(cluster.contexts["p3"].join_epoch(), Some(2));
+
+        // p2 is no longer a participant, but the coordinator still knows the decision.
+        let actions = deliver(
+            &cluster.algorithm,
+            cluster.contexts["c"].clone(),
+            "p2",
+            TwoPhaseCommitMessage::DecisionRequest(1),
+        );
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("p2", TwoPhaseCommitMessage::Commit(0, 1))]
+        );
+
+        // p3 joined in epoch 2, so it can not infer that epoch 1 was aborted.
+        let actions = deliver(
+            &cluster.algorithm,
+            cluster.contexts["p3"].clone(),
+            "p1",
+            TwoPhaseCommitMessage::DecisionRequest(1),
+        );
+        assert!(sent_messages(&actions).is_empty());
+    }
+
+    /// Test that a new coordinator aborts an in-flight epoch in which a participant had not yet
+    /// voted, and that the deposed coordinator's messages are fenced off afterwards.
+    #[test]
+    fn take_over_aborts_undecided_epoch() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Nothing), &time, &["p1", "p2"]);
+        add_standby_coordinator(&mut cluster, &["p1", "p2"], &[]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+
+        // The coordinator fails, and "n" takes over.
+        cluster.event("n", TwoPhaseCommitEvent::TakeOver(1));
+        cluster.deliver_all(&["c"]);
+
+        assert!(cluster.notified("p1", TwoPhaseCommitActionNotification::Abort(1, Some(7))));
+        assert!(cluster.notified("p2", TwoPhaseCommitActionNotification::Abort(1, Some(7))));
+        assert!(cluster.notified("n", TwoPhaseCommitActionNotification::Abort(1, None)));
+        assert!(cluster.notified("n", TwoPhaseCommitActionNotification::RequestForStart(2)));
+        assert_eq!(*cluster.contexts["p1"].coordinator(), "n");
+        assert_eq!(*cluster.contexts["p1"].term(), 1);
+
+        // A VoteRequest from the deposed coordinator is dropped.
+        cluster.messages.push_back((
+            "c".into(),
+            "p1".into(),
+            TwoPhaseCommitMessage::VoteRequest(0, 2, 9, processes(&["p1", "p2"]), vec![]),
+        ));
+        cluster.deliver_all(&[]);
+        assert_eq!(
+            cluster.state("p1"),
+            TwoPhaseCommitState::WaitingForVoteRequest
+        );
+
+        // The new coordinator starts the next epoch.
+        cluster.event("n", TwoPhaseCommitEvent::Start(8));
+        cluster.deliver_all(&[]);
+        assert!(cluster.notified(
+            "p1",
+            TwoPhaseCommitActionNotification::ParticipantRequestForVote(8)
+        ));
+    }
+
+    /// Test that a new coordinator sends NewTerm to the observers, which then accept its outcomes.
+    #[test]
+    fn take_over_reaches_observers() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Nothing), &time, &["p1", "p2"]);
+        add_observer(&mut cluster, &["p1", "p2"]);
+        add_standby_coordinator(&mut cluster, &["p1", "p2"], &["o"]);
+
+        cluster.event("n", TwoPhaseCommitEvent::TakeOver(1));
+        cluster.deliver_all(&["c"]);
+        assert_eq!(*cluster.contexts["o"].coordinator(), "n");
+        assert_eq!(*cluster.contexts["o"].term(), 1);
+
+        cluster.event("n", TwoPhaseCommitEvent::Start(8));
+        cluster.deliver_all(&[]);
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.event("p2", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+        cluster.event("n", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+        assert!(cluster.notified("o", TwoPhaseCommitActionNotification::Commit(1, Some(8))));
+    }
+
+    /// Test that a participant drops a message from its own coordinator which was sent in an
+    /// earlier term, after that coordinator has taken over again.
+    #[test]
+    fn stale_term_message_is_dropped() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Nothing), &time, &["p1", "p2"]);
+        add_standby_coordinator(&mut cluster, &["p1", "p2"], &[]);
+
+        cluster.event("n", TwoPhaseCommitEvent::TakeOver(1));
+        cluster.deliver_all(&["c"]);
+        cluster.event("c", TwoPhaseCommitEvent::TakeOver(2));
+        cluster.deliver_all(&["n"]);
+        assert_eq!(*cluster.contexts["p1"].coordinator(), "c");
+        assert_eq!(*cluster.contexts["p1"].term(), 2);
+
+        // A VoteRequest which "c" sent in term 0 is delayed until after the takeover.
+        cluster.messages.push_back((
+            "c".into(),
+            "p1".into(),
+            TwoPhaseCommitMessage::VoteRequest(0, 2, 9, processes(&["p1", "p2"]), vec![]),
+        ));
+        cluster.deliver_all(&[]);
+        assert!(cluster.notified(
+            "p1",
+            TwoPhaseCommitActionNotification::MessageDropped(
+                "term 0 is below the current term 2".into()
+            )
+        ));
+        assert_eq!(
+            cluster.state("p1"),
+            TwoPhaseCommitState::WaitingForVoteRequest
+        );
+    }
+
+    /// Test that a participant accepts a decision which its coordinator sent in an earlier term.
+    #[test]
+    fn earlier_term_decision_is_accepted() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Nothing), &time, &["p1", "p2"]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.event("p2", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+
+        // The Commit to p2 is delayed until after "c" has moved to term 2.
+        cluster.event("c", TwoPhaseCommitEvent::Vote(true));
+        let delayed = cluster
+            .messages
+            .iter()
+            .position(|(_, to, _)| to == "p2")
+            .and_then(|index| cluster.messages.remove(index))
+            .unwrap();
+        cluster.deliver_all(&[]);
+        cluster
+            .messages
+            .push_back(("c".into(), "p2".into(), TwoPhaseCommitMessage::NewTerm(2)));
+        cluster.deliver();
+        assert_eq!(*cluster.contexts["p2"].term(), 2);
+
+        cluster.messages.push_back(delayed);
+        cluster.deliver_all(&[]);
+        assert!(cluster.notified("p2", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+    }
+
+    /// Test that a new coordinator commits an in-flight epoch which a participant has already
+    /// committed, and sends the decision to the participants which were uncertain.
+    #[test]
+    fn take_over_recovers_commit() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Nothing), &time, &["p1", "p2"]);
+        add_standby_coordinator(&mut cluster, &["p1", "p2"], &[]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.event("p2", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+
+        // The coordinator commits, but fails before the decision reaches p2.
+        cluster.event("c", TwoPhaseCommitEvent::Vote(true));
+        cluster.messages.retain(|(_, to, _)| to != "p2");
+        cluster.deliver_all(&[]);
+        assert!(cluster.notified("p1", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+
+        cluster.event("n", TwoPhaseCommitEvent::TakeOver(1));
+        cluster.deliver_all(&["c"]);
+
+        assert!(cluster.notified("p2", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+        assert!(cluster.notified("n", TwoPhaseCommitActionNotification::Commit(1, None)));
+        assert_eq!(*cluster.contexts["n"].epoch(), 2);
+    }
+
+    /// Test that a new coordinator can not decide an epoch in which every participant voted yes
+    /// without learning the decision, and that the previous coordinator can finish the epoch by
+    /// taking over again with a greater term.
+    #[test]
+    fn take_over_blocks_until_decision_is_known() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Nothing), &time, &["p1", "p2"]);
+        add_standby_coordinator(&mut cluster, &["p1", "p2"], &[]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.event("p2", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+
+        // The coordinator commits, but fails before the decision reaches any participant.
+        cluster.event("c", TwoPhaseCommitEvent::Vote(true));
+        cluster.messages.clear();
+
+        cluster.event("n", TwoPhaseCommitEvent::TakeOver(1));
+        cluster.deliver_all(&["c"]);
+        assert!(matches!(
+            cluster.state("n"),
+            TwoPhaseCommitState::WaitingForNewTermAck { .. }
+        ));
+
+        // The previous coordinator is restarted, and takes over again.
+        cluster.event("c", TwoPhaseCommitEvent::TakeOver(2));
+        cluster.deliver_all(&["n"]);
+
+        assert!(cluster.notified("p1", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+        assert!(cluster.notified("p2", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+        assert!(matches!(
+            cluster.state("c"),
+            TwoPhaseCommitState::WaitingForStart
+        ));
+    }
+
+    // Returns an algorithm in decentralized mode with a short vote timeout, so the coordinator
+    // may be woken up to request missing votes without a long wait.
+    fn decentralized_algorithm(time: &TestTimeSource) -> TestAlgorithm {
+        let config = TwoPhaseCommitConfigBuilder::new()
+            .with_decentralized(true)
+            .with_vote_timeout(Duration::from_millis(1))
+            .build()
+            .unwrap();
+        TwoPhaseCommitAlgorithm::with_config(time.clone(), config)
+    }
+
+    /// Test that in decentralized mode, participants commit once they have the votes of all other
+    /// participants, and the coordinator neither sends the decision nor waits for acks.
+    #[test]
+    fn decentralized_commit() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(decentralized_algorithm(&time), &time, &["p1", "p2", "p3"]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+        for participant in ["p1", "p2", "p3"] {
+            cluster.event(participant, TwoPhaseCommitEvent::Vote(true));
+        }
+
+        // Each participant sends its vote to the coordinator and both other participants.
+        assert_eq!(cluster.messages.len(), 9);
+        assert!(cluster
+            .messages
+            .iter()
+            .all(|(_, _, message)| *message == TwoPhaseCommitMessage::VoteResponse(1, true)));
+        cluster.deliver_all(&[]);
+
+        for participant in ["p1", "p2", "p3"] {
+            assert!(cluster.notified(
+                participant,
+                TwoPhaseCommitActionNotification::Commit(1, Some(7))
+            ));
+        }
+        assert!(cluster.notified("c", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+        assert!(!cluster
+            .notifications
+            .iter()
+            .any(|(_, notification)| matches!(
+                notification,
+                TwoPhaseCommitActionNotification::MessageDropped(_)
+            )));
+        assert_eq!(cluster.state("c"), TwoPhaseCommitState::WaitingForStart);
+        assert_eq!(*cluster.contexts["c"].epoch(), 2);
+    }
+
+    /// Test that in decentralized mode, the coordinator requests a missing vote on its vote
+    /// timeout rather than aborting, and that a participant which has not voted answers with a no
+    /// vote, which aborts the epoch everywhere.
+    #[test]
+    fn decentralized_requests_missing_vote() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(decentralized_algorithm(&time), &time, &["p1", "p2", "p3"]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        cluster.deliver_all(&[]);
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.event("p2", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+
+        time.advance(Duration::from_millis(5));
+        cluster.alarm("c");
+        assert_eq!(
+            cluster.messages,
+            vec![(
+                "c".to_string(),
+                "p3".to_string(),
+                TwoPhaseCommitMessage::DecisionRequest(1)
+            )]
+        );
+        cluster.deliver_all(&[]);
+
+        for process in ["c", "p1", "p2", "p3"] {
+            assert!(cluster.notified(process, TwoPhaseCommitActionNotification::Abort(1, Some(7))));
+        }
+    }
+
+    /// Test that an observer is sent the value and decision of an epoch once the coordinator has
+    /// decided, without taking part in voting, and that a participant which missed the decision
+    /// learns it from the observer.
+    #[test]
+    fn observer_follows_decisions() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(termination_algorithm(&time), &time, &["p1"]);
+        add_observer(&mut cluster, &["p1"]);
+
+        cluster.event("c", TwoPhaseCommitEvent::Start(7));
+        assert!(cluster.messages.iter().all(|(_, to, _)| to != "o"));
+        cluster.deliver_all(&[]);
+        assert_eq!(cluster.contexts["p1"].observers(), Some(&processes(&["o"])));
+        cluster.event("p1", TwoPhaseCommitEvent::Vote(true));
+        cluster.deliver_all(&[]);
+
+        // The coordinator commits, but the decision is lost on its way to p1.
+        cluster.event("c", TwoPhaseCommitEvent::Vote(true));
+        assert!(cluster.messages.contains(&(
+            "c".into(),
+            "o".into(),
+            TwoPhaseCommitMessage::Outcome(1, Some(7), Decision::Commit)
+        )));
+        cluster.deliver_all(&["p1"]);
+
+        assert!(cluster.notified("o", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+        assert_eq!(*cluster.contexts["o"].epoch(), 1);
+
+        // With the coordinator down, p1 times out and asks the observer for the decision.
+        time.advance(Duration::from_millis(5));
+        cluster.alarm("p1");
+        cluster.deliver_all(&["c"]);
+
+        assert!(cluster.notified("p1", TwoPhaseCommitActionNotification::Commit(1, Some(7))));
+        assert_eq!(*cluster.contexts["p1"].last_commit_epoch(), Some(1));
+    }
+
+    /// Test that a Cancel event aborts the epoch in flight without waiting for the vote timeout,
+    /// and is rejected once the epoch has been decided.
+    #[test]
+    fn cancel_aborts_epoch() {
+        let algorithm = algorithm(&TestTimeSource::new(), Presumption::Nothing);
+        let context = coordinator_context(1, &["p1", "p2"]);
+
+        assert!(algorithm
+            .event(TwoPhaseCommitEvent::Cancel(), context.clone())
+            .is_err());
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Start(7), context)
+            .unwrap();
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "p1",
+            TwoPhaseCommitMessage::VoteResponse(1, true),
+        );
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Cancel(), last_context(&actions))
+            .unwrap();
+
+        assert_eq!(
+            sent_messages(&actions),
+            vec![("p1", TwoPhaseCommitMessage::Abort(0, 1))]
+        );
+        assert!(actions.contains(&TwoPhaseCommitAction::Notify(
+            TwoPhaseCommitActionNotification::Abort(1, Some(7))
+        )));
+        assert_eq!(
+            last_context(&actions).decision_history(),
+            &vec![(1, Decision::Abort)]
+        );
+    }
+
+    /// Test that in a batch epoch, only the items accepted by every participant and by the
+    /// coordinator are committed, and the rest of the batch is aborted. The observer is sent the
+    /// decision of each item.
+    #[test]
+    fn batch_commits_accepted_items() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Nothing), &time, &["p1", "p2"]);
+        add_observer(&mut cluster, &["p1", "p2"]);
+
+        cluster.event("c", TwoPhaseCommitEvent::StartBatch(vec![1, 2, 3]));
+        cluster.deliver_all(&[]);
+        assert!(cluster.notified(
+            "p1",
+            TwoPhaseCommitActionNotification::ParticipantRequestForBatchVote(vec![1, 2, 3])
+        ));
+
+        cluster.event(
+            "p1",
+            TwoPhaseCommitEvent::BatchVote(vec![true, true, false]),
+        );
+        cluster.event(
+            "p2",
+            TwoPhaseCommitEvent::BatchVote(vec![true, false, true]),
+        );
+        cluster.deliver_all(&[]);
+        assert!(cluster.notified(
+            "c",
+            TwoPhaseCommitActionNotification::CoordinatorRequestForBatchVote(
+                1,
+                vec![1, 2, 3],
+                vec![true, false, false]
+            )
+        ));
+
+        cluster.event("c", TwoPhaseCommitEvent::BatchVote(vec![true, true, true]));
+        cluster.deliver_all(&[]);
+        for process in ["c", "p1", "p2", "o"] {
+            assert!(cluster.notified(
+                process,
+                TwoPhaseCommitActionNotification::BatchDecision(1, vec![1], vec![2, 3])
+            ));
+        }
+        assert_eq!(cluster.state("c"), TwoPhaseCommitState::WaitingForStart);
+        for process in ["p1", "o"] {
+            assert_eq!(
+                cluster.contexts[process].batch_history(),
+                &vec![(1, vec![true, false, false])]
+            );
+        }
+
+        // The observer answers a DecisionRequest with the decision of each item.
+        let actions = deliver(
+            &cluster.algorithm,
+            cluster.contexts["o"].clone(),
+            "p1",
+            TwoPhaseCommitMessage::DecisionRequest(1),
+        );
+        assert_eq!(
+            sent_messages(&actions),
+            vec![(
+                "p1",
+                TwoPhaseCommitMessage::BatchCommit(0, 1, vec![true, false, false])
+            )]
+        );
+    }
+
+    /// Test that a participant which is sent the Abort of a batch epoch is notified that every
+    /// item of the batch is aborted.
+    #[test]
+    fn batch_abort_aborts_every_item() {
+        let time = TestTimeSource::new();
+        let mut cluster = cluster(algorithm(&time, Presumption::Nothing), &time, &["p1", "p2"]);
+
+        cluster.event("c", TwoPhaseCommitEvent::StartBatch(vec![1, 2, 3]));
+        cluster.deliver_all(&[]);
+
+        // No item is accepted by p2, so the coordinator aborts the epoch.
+        cluster.event("p1", TwoPhaseCommitEvent::BatchVote(vec![true, true, true]));
+        cluster.event(
+            "p2",
+            TwoPhaseCommitEvent::BatchVote(vec![false, false, false]),
+        );
+        cluster.deliver_all(&[]);
+
+        for process in ["c", "p1", "p2"] {
+            assert!(cluster.notified(
+                process,
+                TwoPhaseCommitActionNotification::BatchDecision(1, vec![], vec![1, 2, 3])
+            ));
+        }
+        assert_eq!(
+            cluster.state("p1"),
+            TwoPhaseCommitState::WaitingForVoteRequest
+        );
+        assert!(cluster.contexts["p1"].batch().is_empty());
+    }
+
+    /// Test that participants which share a random source wait different times before their next
+    /// round of DecisionRequest messages.
+    #[test]
+    fn decision_request_jitter_differs_between_participants() {
+        let config = TwoPhaseCommitConfigBuilder::new()
+            .with_decision_timeout(Duration::from_millis(2))
+            .with_decision_request_random_source(SeededRandomSource::new(1))
+            .build()
+            .unwrap();
+        let time = TestTimeSource::new();
+        let algorithm: TestAlgorithm = TwoPhaseCommitAlgorithm::with_config(time.clone(), config);
+        let decision_timeout_start = time.now();
+
+        let alarm = |process: &str| {
+            let context = TwoPhaseCommitContextBuilder::new()
+                .with_coordinator("c".to_string())
+                .with_epoch(1)
+                .with_state(TwoPhaseCommitState::Voted {
+                    vote: true,
+                    decision_timeout_start,
+                })
+                .with_this_process(process.to_string())
+                .with_participant_processes(processes(&["p1", "p2"]))
+                .build()
+                .unwrap();
+            let actions = algorithm
+                .event(TwoPhaseCommitEvent::Alarm(), context)
+                .unwrap();
+            match actions.last() {
+                Some(TwoPhaseCommitAction::Update {
+                    alarm: Some(alarm), ..
+                }) => *alarm,
+                _ => panic!("no alarm"),
+            }
+        };
+
+        time.advance(Duration::from_millis(3));
+        assert_ne!(alarm("p1"), alarm("p2"));
+    }
+
+    /// Test that a participant waits longer after each round of DecisionRequest messages, up to
+    /// the maximum, and notifies that it is blocked once the retry limit has been reached.
+    #[test]
+    fn decision_requests_back_off_until_blocked() {
+        let config = |seed| {
+            TwoPhaseCommitConfigBuilder::new()
+                .with_decision_timeout(Duration::from_millis(2))
+                .with_decision_request_max_timeout(Duration::from_millis(6))
+                .with_decision_request_limit(3)
+                .with_decision_request_random_source(SeededRandomSource::new(seed))
+                .build()
+                .unwrap()
+        };
+        let time = TestTimeSource::new();
+        let algorithm: TestAlgorithm =
+            TwoPhaseCommitAlgorithm::with_config(time.clone(), config(1));
+        // A separate algorithm whose random source has the same seed.
+        let replay: TestAlgorithm = TwoPhaseCommitAlgorithm::with_config(time.clone(), config(1));
+
+        let mut context = TwoPhaseCommitContextBuilder::new()
+            .with_coordinator("c".to_string())
+            .with_epoch(1)
+            .with_state(TwoPhaseCommitState::Voted {
+                vote: true,
+                decision_timeout_start: time.now(),
+            })
+            .with_this_process("p1".to_string())
+            .with_participant_processes(processes(&["p1", "p2"]))
+            .build()
+            .unwrap();
+
+        // The wait doubles after each round, up to the maximum, and is shortened by up to half.
+        time.advance(Duration::from_secs(60));
+        for max_wait in [4, 6, 6] {
+            let now = time.now();
+            let actions = algorithm
+                .event(TwoPhaseCommitEvent::Alarm(), context.clone())
+                .unwrap();
+
+            // The same seed gives the same wait.
+            assert_eq!(
+                replay.event(TwoPhaseCommitEvent::Alarm(), context).unwrap(),
+                actions
+            );
+
+            assert_eq!(
+                sent_messages(&actions),
+                vec![
+                    ("p2", TwoPhaseCommitMessage::DecisionRequest(1)),
+                    ("c", TwoPhaseCommitMessage::DecisionRequest(1)),
+                ]
+            );
+            let alarm = match actions.last() {
+                Some(TwoPhaseCommitAction::Update {
+                    alarm: Some(alarm), ..
+                }) => *alarm,
+                _ => panic!("no alarm"),
+            };
+            assert!(alarm >= now + Duration::from_millis(max_wait / 2));
+            assert!(alarm <= now + Duration::from_millis(max_wait));
+
+            context = last_context(&actions);
+            time.advance(Duration::from_millis(max_wait + 1));
+        }
+
+        let actions = algorithm
+            .event(TwoPhaseCommitEvent::Alarm(), context)
+            .unwrap();
+        assert!(sent_messages(&actions).is_empty());
+        assert!(matches!(
+            &actions[0],
+            TwoPhaseCommitAction::Update { alarm: None, .. }
+        ));
+        assert!(actions.contains(&TwoPhaseCommitAction::Notify(
+            TwoPhaseCommitActionNotification::ParticipantBlocked(1)
+        )));
+
+        // The decision is still accepted once it arrives.
+        let actions = deliver(
+            &algorithm,
+            last_context(&actions),
+            "c",
+            TwoPhaseCommitMessage::Commit(0, 1),
+        );
+        assert!(actions.contains(&TwoPhaseCommitAction::Notify(
+            TwoPhaseCommitActionNotification::Commit(1, None)
+        )));
+    }
+}
@@ -14,41 +14,147 @@
 
 use std::marker::PhantomData;
 
+use crate::algorithm::Value;
 use crate::error::InvalidStateError;
 use crate::process::Process;
 use crate::time::Time;
 
+use super::Decision;
+use super::DecisionHistory;
 use super::Epoch;
+use super::ObserverContext;
+use super::Term;
+use super::TwoPhaseCommitMessage;
 use super::TwoPhaseCommitRoleContext;
 use super::TwoPhaseCommitState;
 use super::{CoordinatorContext, CoordinatorState, Participant};
 use super::{ParticipantContext, ParticipantState};
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub struct TwoPhaseCommitContext<P, T, R = TwoPhaseCommitRoleContext<P, T>>
+pub struct TwoPhaseCommitContext<P, V, T, R = TwoPhaseCommitRoleContext<P, V, T>>
 where
     P: Process,
+    V: Value,
     T: Time,
     R: Clone,
 {
+    pub(super) batch: Vec<V>,
+    pub(super) batch_history: DecisionHistory<Epoch, Vec<bool>>,
     pub(super) coordinator: P,
+    pub(super) decision_history: DecisionHistory<Epoch, Decision>,
     pub(super) epoch: Epoch,
     pub(super) last_commit_epoch: Option<Epoch>,
     pub(super) role_context: R,
+    pub(super) term: Term,
     pub(super) this_process: P,
     pub(super) time_phantom: PhantomData<T>,
+    pub(super) value: Option<V>,
 }
 
-impl<P, T, R> TwoPhaseCommitContext<P, T, R>
+impl<P, V, T, R> TwoPhaseCommitContext<P, V, T, R>
 where
     P: Process,
+    V: Value,
     T: Time,
     R: Clone,
 {
+    /// The items being decided in the current epoch, if it was started with a batch of values.
+    ///
+    /// Like the value, this is cleared once the epoch has been decided.
+    pub fn batch(&self) -> &Vec<V> {
+        &self.batch
+    }
+
+    pub(super) fn set_batch(&mut self, batch: Vec<V>) {
+        self.batch = batch
+    }
+
+    /// The items which were committed in recently committed batch epochs, ordered from the oldest
+    /// epoch to the newest. The history is pruned to the same size as the decision history.
+    pub fn batch_history(&self) -> &Vec<(Epoch, Vec<bool>)> {
+        self.batch_history.decisions()
+    }
+
+    // Returns the items which were committed in the given epoch, if it is in the batch history.
+    pub(super) fn batch_decision(&self, epoch: Epoch) -> Option<&Vec<bool>> {
+        self.batch_history.get(epoch)
+    }
+
+    // Adds the committed items of a batch epoch to the batch history, then prunes the history to
+    // at most `history_size` epochs by removing the oldest epochs.
+    pub(super) fn record_batch_decision(
+        &mut self,
+        epoch: Epoch,
+        committed: Vec<bool>,
+        history_size: usize,
+    ) {
+        self.batch_history.record(epoch, committed, history_size);
+    }
+
+    // Takes the items of the current batch, split into the items which were committed in the
+    // given epoch and the items which were aborted. Every item was aborted if the epoch is not in
+    // the batch history.
+    pub(super) fn take_batch_decision(&mut self, epoch: Epoch) -> (Vec<V>, Vec<V>) {
+        let committed = self.batch_decision(epoch).cloned().unwrap_or_default();
+        let mut committed_items = Vec::new();
+        let mut aborted_items = Vec::new();
+
+        for (index, item) in std::mem::take(&mut self.batch).into_iter().enumerate() {
+            if committed.get(index) == Some(&true) {
+                committed_items.push(item);
+            } else {
+                aborted_items.push(item);
+            }
+        }
+
+        (committed_items, aborted_items)
+    }
+
+    // Returns the Commit message for the given epoch, with the current term. For a batch epoch,
+    // this is a BatchCommit which includes the items which were committed.
+    pub(super) fn commit_message(&self, epoch: Epoch) -> TwoPhaseCommitMessage<P, V> {
+        match self.batch_decision(epoch) {
+            Some(committed) => {
+                TwoPhaseCommitMessage::BatchCommit(self.term, epoch, committed.clone())
+            }
+            None => TwoPhaseCommitMessage::Commit(self.term, epoch),
+        }
+    }
+
+    // Returns the Abort message for the given epoch, with the current term.
+    pub(super) fn abort_message(&self, epoch: Epoch) -> TwoPhaseCommitMessage<P, V> {
+        TwoPhaseCommitMessage::Abort(self.term, epoch)
+    }
+
     pub fn coordinator(&self) -> &P {
         &self.coordinator
     }
 
+    pub(super) fn set_coordinator(&mut self, coordinator: P) {
+        self.coordinator = coordinator;
+    }
+
+    /// The decisions of recently decided epochs, ordered from the oldest epoch to the newest.
+    pub fn decision_history(&self) -> &Vec<(Epoch, Decision)> {
+        self.decision_history.decisions()
+    }
+
+    // Returns the decision for the given epoch, if it is in the decision history.
+    pub(super) fn decision(&self, epoch: Epoch) -> Option<Decision> {
+        self.decision_history.get(epoch).copied()
+    }
+
+    // Adds a decision to the decision history, then prunes the history to at most
+    // `history_size` decisions by removing the decisions of the oldest epochs.
+    pub(super) fn record_decision(
+        &mut self,
+        epoch: Epoch,
+        decision: Decision,
+        history_size: usize,
+    ) {
+        self.decision_history.record(epoch, decision, history_size);
+    }
+
     pub fn epoch(&self) -> &Epoch {
         &self.epoch
     }
@@ -65,17 +171,43 @@ where
         self.last_commit_epoch = epoch
     }
 
+    /// The term of the coordinator, which increases each time a coordinator takes over.
+    pub fn term(&self) -> &Term {
+        &self.term
+    }
+
+    pub(super) fn set_term(&mut self, term: Term) {
+        self.term = term;
+    }
+
     pub fn this_process(&self) -> &P {
         &self.this_process
     }
+
+    /// The value being decided in the current epoch, if it is known.
+    ///
+    /// This is set when the epoch is started, and cleared once the epoch has been decided. For a
+    /// coordinator which was started with a value for each participant, this is `None`.
+    pub fn value(&self) -> &Option<V> {
+        &self.value
+    }
+
+    pub(super) fn set_value(&mut self, value: Option<V>) {
+        self.value = value
+    }
+
+    pub(super) fn take_value(&mut self) -> Option<V> {
+        self.value.take()
+    }
 }
 
-impl<P, T> TwoPhaseCommitContext<P, T, TwoPhaseCommitRoleContext<P, T>>
+impl<P, V, T> TwoPhaseCommitContext<P, V, T, TwoPhaseCommitRoleContext<P, V, T>>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
-    pub fn participants(&self) -> Option<&Vec<Participant<P>>> {
+    pub fn participants(&self) -> Option<&Vec<Participant<P, V>>> {
         self.role_context.participants()
     }
 
@@ -83,24 +215,48 @@ where
         self.role_context.participant_processes()
     }
 
+    /// The observers which the coordinator sends the outcome of each epoch to. For a participant,
+    /// these are the observers of the current epoch, which it sends DecisionRequest messages to.
+    /// This is always `None` for an observer.
+    pub fn observers(&self) -> Option<&Vec<P>> {
+        self.role_context.observers()
+    }
+
+    /// The last epoch in which this participant voted read-only, if any. This is always `None`
+    /// for a coordinator.
+    pub fn last_read_only_epoch(&self) -> Option<Epoch> {
+        self.role_context.last_read_only_epoch()
+    }
+
+    /// The epoch in which this participant joined, which is the epoch of the first VoteRequest it
+    /// received while not among the participants. This is always `None` for a coordinator.
+    pub fn join_epoch(&self) -> Option<Epoch> {
+        self.role_context.join_epoch()
+    }
+
     pub fn state(&self) -> TwoPhaseCommitState<T> {
         self.role_context.state()
     }
 }
 
-impl<P, T> TwoPhaseCommitContext<P, T, CoordinatorContext<P, T>>
+impl<P, V, T> TwoPhaseCommitContext<P, V, T, CoordinatorContext<P, V, T>>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
-    pub(super) fn participants(&self) -> &Vec<Participant<P>> {
+    pub(super) fn participants(&self) -> &Vec<Participant<P, V>> {
         &self.role_context.participants
     }
 
-    pub(super) fn participants_mut(&mut self) -> &mut Vec<Participant<P>> {
+    pub(super) fn participants_mut(&mut self) -> &mut Vec<Participant<P, V>> {
         &mut self.role_context.participants
     }
 
+    pub(super) fn observers(&self) -> &Vec<P> {
+        &self.role_context.observers
+    }
+
     pub(super) fn state(&self) -> &CoordinatorState<T> {
         &self.role_context.state
     }
@@ -110,15 +266,60 @@ where
     }
 }
 
-impl<P, T> TwoPhaseCommitContext<P, T, ParticipantContext<P, T>>
+impl<P, V, T> TwoPhaseCommitContext<P, V, T, ParticipantContext<P, T>>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
     pub(super) fn participant_processes(&self) -> &Vec<P> {
         &self.role_context.participant_processes
     }
 
+    pub(super) fn set_participant_processes(&mut self, participant_processes: Vec<P>) {
+        self.role_context.participant_processes = participant_processes;
+    }
+
+    pub(super) fn observers(&self) -> &Vec<P> {
+        &self.role_context.observers
+    }
+
+    pub(super) fn set_observers(&mut self, observers: Vec<P>) {
+        self.role_context.observers = observers;
+    }
+
+    pub(super) fn decision_requests(&self) -> &u32 {
+        &self.role_context.decision_requests
+    }
+
+    pub(super) fn set_decision_requests(&mut self, decision_requests: u32) {
+        self.role_context.decision_requests = decision_requests;
+    }
+
+    pub(super) fn peer_votes(&self) -> &Vec<(P, Epoch, bool)> {
+        &self.role_context.peer_votes
+    }
+
+    pub(super) fn peer_votes_mut(&mut self) -> &mut Vec<(P, Epoch, bool)> {
+        &mut self.role_context.peer_votes
+    }
+
+    pub(super) fn last_read_only_epoch(&self) -> &Option<Epoch> {
+        &self.role_context.last_read_only_epoch
+    }
+
+    pub(super) fn set_last_read_only_epoch(&mut self, epoch: Option<Epoch>) {
+        self.role_context.last_read_only_epoch = epoch;
+    }
+
+    pub(super) fn join_epoch(&self) -> &Option<Epoch> {
+        &self.role_context.join_epoch
+    }
+
+    pub(super) fn set_join_epoch(&mut self, epoch: Option<Epoch>) {
+        self.role_context.join_epoch = epoch;
+    }
+
     pub(super) fn state(&self) -> &ParticipantState<T> {
         &self.role_context.state
     }
@@ -129,85 +330,163 @@ where
 }
 
 #[doc(hidden)]
-impl<P, T> TryFrom<TwoPhaseCommitContext<P, T, TwoPhaseCommitRoleContext<P, T>>>
-    for TwoPhaseCommitContext<P, T, CoordinatorContext<P, T>>
+impl<P, V, T> TryFrom<TwoPhaseCommitContext<P, V, T, TwoPhaseCommitRoleContext<P, V, T>>>
+    for TwoPhaseCommitContext<P, V, T, CoordinatorContext<P, V, T>>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
     type Error = InvalidStateError;
 
     fn try_from(
-        context: TwoPhaseCommitContext<P, T, TwoPhaseCommitRoleContext<P, T>>,
+        context: TwoPhaseCommitContext<P, V, T, TwoPhaseCommitRoleContext<P, V, T>>,
     ) -> Result<Self, Self::Error> {
         Ok(Self {
+            batch: context.batch,
+            batch_history: context.batch_history,
             coordinator: context.coordinator,
+            decision_history: context.decision_history,
             epoch: context.epoch,
             last_commit_epoch: context.last_commit_epoch,
             role_context: context.role_context.try_into()?,
+            term: context.term,
             this_process: context.this_process,
             time_phantom: PhantomData,
+            value: context.value,
         })
     }
 }
 
 #[doc(hidden)]
-impl<P, T> TryFrom<TwoPhaseCommitContext<P, T, TwoPhaseCommitRoleContext<P, T>>>
-    for TwoPhaseCommitContext<P, T, ParticipantContext<P, T>>
+impl<P, V, T> TryFrom<TwoPhaseCommitContext<P, V, T, TwoPhaseCommitRoleContext<P, V, T>>>
+    for TwoPhaseCommitContext<P, V, T, ParticipantContext<P, T>>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
     type Error = InvalidStateError;
 
     fn try_from(
-        context: TwoPhaseCommitContext<P, T, TwoPhaseCommitRoleContext<P, T>>,
+        context: TwoPhaseCommitContext<P, V, T, TwoPhaseCommitRoleContext<P, V, T>>,
     ) -> Result<Self, Self::Error> {
         Ok(Self {
+            batch: context.batch,
+            batch_history: context.batch_history,
             coordinator: context.coordinator,
+            decision_history: context.decision_history,
             epoch: context.epoch,
             last_commit_epoch: context.last_commit_epoch,
             role_context: context.role_context.try_into()?,
+            term: context.term,
             this_process: context.this_process,
             time_phantom: PhantomData,
+            value: context.value,
         })
     }
 }
 
 #[doc(hidden)]
-impl<P, T> From<TwoPhaseCommitContext<P, T, CoordinatorContext<P, T>>>
-    for TwoPhaseCommitContext<P, T, TwoPhaseCommitRoleContext<P, T>>
+impl<P, V, T> From<TwoPhaseCommitContext<P, V, T, CoordinatorContext<P, V, T>>>
+    for TwoPhaseCommitContext<P, V, T, TwoPhaseCommitRoleContext<P, V, T>>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    fn from(context: TwoPhaseCommitContext<P, V, T, CoordinatorContext<P, V, T>>) -> Self {
+        Self {
+            batch: context.batch,
+            batch_history: context.batch_history,
+            coordinator: context.coordinator,
+            decision_history: context.decision_history,
+            epoch: context.epoch,
+            last_commit_epoch: context.last_commit_epoch,
+            role_context: context.role_context.into(),
+            term: context.term,
+            this_process: context.this_process,
+            time_phantom: PhantomData,
+            value: context.value,
+        }
+    }
+}
+
+#[doc(hidden)]
+impl<P, V, T> From<TwoPhaseCommitContext<P, V, T, ParticipantContext<P, T>>>
+    for TwoPhaseCommitContext<P, V, T, TwoPhaseCommitRoleContext<P, V, T>>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
-    fn from(context: TwoPhaseCommitContext<P, T, CoordinatorContext<P, T>>) -> Self {
+    fn from(context: TwoPhaseCommitContext<P, V, T, ParticipantContext<P, T>>) -> Self {
         Self {
+            batch: context.batch,
+            batch_history: context.batch_history,
             coordinator: context.coordinator,
+            decision_history: context.decision_history,
             epoch: context.epoch,
             last_commit_epoch: context.last_commit_epoch,
             role_context: context.role_context.into(),
+            term: context.term,
             this_process: context.this_process,
             time_phantom: PhantomData,
+            value: context.value,
         }
     }
 }
 
 #[doc(hidden)]
-impl<P, T> From<TwoPhaseCommitContext<P, T, ParticipantContext<P, T>>>
-    for TwoPhaseCommitContext<P, T, TwoPhaseCommitRoleContext<P, T>>
+impl<P, V, T> TryFrom<TwoPhaseCommitContext<P, V, T, TwoPhaseCommitRoleContext<P, V, T>>>
+    for TwoPhaseCommitContext<P, V, T, ObserverContext>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    type Error = InvalidStateError;
+
+    fn try_from(
+        context: TwoPhaseCommitContext<P, V, T, TwoPhaseCommitRoleContext<P, V, T>>,
+    ) -> Result<Self, Self::Error> {
+        Ok(Self {
+            batch: context.batch,
+            batch_history: context.batch_history,
+            coordinator: context.coordinator,
+            decision_history: context.decision_history,
+            epoch: context.epoch,
+            last_commit_epoch: context.last_commit_epoch,
+            role_context: context.role_context.try_into()?,
+            term: context.term,
+            this_process: context.this_process,
+            time_phantom: PhantomData,
+            value: context.value,
+        })
+    }
+}
+
+#[doc(hidden)]
+impl<P, V, T> From<TwoPhaseCommitContext<P, V, T, ObserverContext>>
+    for TwoPhaseCommitContext<P, V, T, TwoPhaseCommitRoleContext<P, V, T>>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
-    fn from(context: TwoPhaseCommitContext<P, T, ParticipantContext<P, T>>) -> Self {
+    fn from(context: TwoPhaseCommitContext<P, V, T, ObserverContext>) -> Self {
         Self {
+            batch: context.batch,
+            batch_history: context.batch_history,
             coordinator: context.coordinator,
+            decision_history: context.decision_history,
             epoch: context.epoch,
             last_commit_epoch: context.last_commit_epoch,
             role_context: context.role_context.into(),
+            term: context.term,
             this_process: context.this_process,
             time_phantom: PhantomData,
+            value: context.value,
         }
     }
 }
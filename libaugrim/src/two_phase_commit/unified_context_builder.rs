@@ -14,66 +14,120 @@
 
 use std::marker::PhantomData;
 
+use crate::algorithm::Value;
 use crate::error::InvalidStateError;
 use crate::process::Process;
 use crate::time::Time;
 
+use super::Decision;
 use super::Epoch;
 use super::Participant;
+use super::Term;
 use super::TwoPhaseCommitContext;
 use super::TwoPhaseCommitRoleContext;
 use super::TwoPhaseCommitState;
 
 #[derive(Default)]
-pub struct TwoPhaseCommitContextBuilder<P, T>
+pub struct TwoPhaseCommitContextBuilder<P, V, T>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
+    batch: Vec<V>,
+    batch_history: Vec<(Epoch, Vec<bool>)>,
     coordinator: Option<P>,
+    decision_history: Vec<(Epoch, Decision)>,
     epoch: Option<Epoch>,
+    join_epoch: Option<Epoch>,
     last_commit_epoch: Option<Epoch>,
-    participants: Option<Vec<Participant<P>>>,
+    last_read_only_epoch: Option<Epoch>,
+    observers: Option<Vec<P>>,
+    participants: Option<Vec<Participant<P, V>>>,
     participant_processes: Option<Vec<P>>,
     state: Option<TwoPhaseCommitState<T>>,
+    term: Term,
     this_process: Option<P>,
     time_phantom: PhantomData<T>,
+    value: Option<V>,
 }
 
-impl<P, T> TwoPhaseCommitContextBuilder<P, T>
+impl<P, V, T> TwoPhaseCommitContextBuilder<P, V, T>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
     pub fn new() -> Self {
         Self {
+            batch: Vec::new(),
+            batch_history: Vec::new(),
             coordinator: None,
+            decision_history: Vec::new(),
             epoch: None,
+            join_epoch: None,
             last_commit_epoch: None,
+            last_read_only_epoch: None,
+            observers: None,
             participants: None,
             participant_processes: None,
             state: None,
+            term: 0,
             this_process: None,
             time_phantom: PhantomData,
+            value: None,
         }
     }
 
+    /// Sets the items of the current epoch, if it was started with a batch of values.
+    pub fn with_batch(mut self, batch: Vec<V>) -> Self {
+        self.batch = batch;
+        self
+    }
+
+    pub fn with_batch_history(mut self, batch_history: Vec<(Epoch, Vec<bool>)>) -> Self {
+        self.batch_history = batch_history;
+        self
+    }
+
     pub fn with_coordinator(mut self, coordinator: P) -> Self {
         self.coordinator = Some(coordinator);
         self
     }
 
+    pub fn with_decision_history(mut self, decision_history: Vec<(Epoch, Decision)>) -> Self {
+        self.decision_history = decision_history;
+        self
+    }
+
     pub fn with_epoch(mut self, epoch: Epoch) -> Self {
         self.epoch = Some(epoch);
         self
     }
 
+    pub fn with_join_epoch(mut self, join_epoch: Epoch) -> Self {
+        self.join_epoch = Some(join_epoch);
+        self
+    }
+
     pub fn with_last_commit_epoch(mut self, last_commit_epoch: Epoch) -> Self {
         self.last_commit_epoch = Some(last_commit_epoch);
         self
     }
 
-    pub fn with_participants(mut self, participants: Vec<Participant<P>>) -> Self {
+    pub fn with_last_read_only_epoch(mut self, last_read_only_epoch: Epoch) -> Self {
+        self.last_read_only_epoch = Some(last_read_only_epoch);
+        self
+    }
+
+    /// Sets the observers of a coordinator context, which are sent the outcome of each epoch, or
+    /// the observers of the current epoch of a participant context.
+    pub fn with_observers(mut self, observers: Vec<P>) -> Self {
+        self.observers = Some(observers);
+        self
+    }
+
+    pub fn with_participants(mut self, participants: Vec<Participant<P, V>>) -> Self {
         self.participants = Some(participants);
         self
     }
@@ -88,15 +142,22 @@ where
         self
     }
 
+    pub fn with_term(mut self, term: Term) -> Self {
+        self.term = term;
+        self
+    }
+
     pub fn with_this_process(mut self, this_process: P) -> Self {
         self.this_process = Some(this_process);
         self
     }
 
-    pub fn build(
-        self,
-    ) -> Result<TwoPhaseCommitContext<P, T, TwoPhaseCommitRoleContext<P, T>>, InvalidStateError>
-    {
+    pub fn with_value(mut self, value: V) -> Self {
+        self.value = Some(value);
+        self
+    }
+
+    pub fn build(self) -> Result<TwoPhaseCommitContext<P, V, T>, InvalidStateError> {
         let last_commit_epoch = self.last_commit_epoch;
 
         let coordinator = self
@@ -116,13 +177,42 @@ where
             .ok_or_else(|| InvalidStateError::with_message("missing this_process field".into()))?;
 
         let role_context = match (self.participants, self.participant_processes) {
+            (Some(_), None) if self.last_read_only_epoch.is_some() => {
+                Err(InvalidStateError::with_message(
+                    "last_read_only_epoch field requires participant_processes field".into(),
+                ))
+            }
+            (Some(_), None) if self.join_epoch.is_some() => Err(InvalidStateError::with_message(
+                "join_epoch field requires participant_processes field".into(),
+            )),
+            (None, None) if self.observers.is_some() => Err(InvalidStateError::with_message(
+                "observers field requires participants or participant_processes field".into(),
+            )),
             (Some(participants), None) => Ok(TwoPhaseCommitRoleContext::new_coordinator(
                 participants,
+                self.observers.unwrap_or_default(),
                 state,
             )?),
+            // An observer context has neither participants nor participant processes.
+            (None, None) if state == TwoPhaseCommitState::Observing => {
+                if self.last_read_only_epoch.is_some() {
+                    return Err(InvalidStateError::with_message(
+                        "last_read_only_epoch field requires participant_processes field".into(),
+                    ));
+                }
+                if self.join_epoch.is_some() {
+                    return Err(InvalidStateError::with_message(
+                        "join_epoch field requires participant_processes field".into(),
+                    ));
+                }
+                Ok(TwoPhaseCommitRoleContext::new_observer())
+            }
             (None, Some(participant_processes)) => Ok(TwoPhaseCommitRoleContext::new_participant(
                 participant_processes,
+                self.observers.unwrap_or_default(),
                 state,
+                self.last_read_only_epoch,
+                self.join_epoch,
             )?),
             (Some(_), Some(_)) => Err(InvalidStateError::with_message(
                 "participant and participant_processes fields are mutually exclusive".into(),
@@ -133,12 +223,17 @@ where
         }?;
 
         Ok(TwoPhaseCommitContext {
+            batch: self.batch,
+            batch_history: self.batch_history.into(),
             coordinator,
+            decision_history: self.decision_history.into(),
             epoch,
             last_commit_epoch,
             role_context,
+            term: self.term,
             this_process,
             time_phantom: self.time_phantom,
+            value: self.value,
         })
     }
 }
@@ -153,7 +248,7 @@ mod tests {
 
     #[test]
     fn build_coordinator_context() {
-        let unified_context = TwoPhaseCommitContextBuilder::<String, SystemTime>::new()
+        let unified_context = TwoPhaseCommitContextBuilder::<String, u32, SystemTime>::new()
             .with_coordinator("me".into())
             .with_epoch(2)
             .with_last_commit_epoch(1)
@@ -167,7 +262,7 @@ mod tests {
             .build()
             .unwrap();
 
-        let coordinator_context: TwoPhaseCommitContext<_, _, CoordinatorContext<_, _>> =
+        let coordinator_context: TwoPhaseCommitContext<_, _, _, CoordinatorContext<_, _, _>> =
             unified_context.try_into().unwrap();
 
         assert_eq!(*coordinator_context.coordinator(), "me".to_string());
@@ -179,7 +274,7 @@ mod tests {
         );
         assert_eq!(coordinator_context.participants().len(), 3);
 
-        let reunified_context: TwoPhaseCommitContext<_, _> = coordinator_context.into();
+        let reunified_context: TwoPhaseCommitContext<_, _, _> = coordinator_context.into();
 
         assert_eq!(*reunified_context.coordinator(), "me".to_string());
         assert_eq!(*reunified_context.epoch(), 2);
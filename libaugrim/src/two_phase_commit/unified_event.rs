@@ -15,6 +15,7 @@
 use crate::algorithm::Value;
 use crate::process::Process;
 
+use super::Term;
 use super::TwoPhaseCommitMessage;
 
 pub enum TwoPhaseCommitEvent<P, V>
@@ -23,7 +24,19 @@ where
     V: Value,
 {
     Alarm(),
-    Deliver(P, TwoPhaseCommitMessage<V>),
+    Recover(),
+    Deliver(P, TwoPhaseCommitMessage<P, V>),
     Start(V),
+    /// Starts the next epoch with a value for each participant, in the order of the participants
+    /// of the coordinator context. The values are a list rather than a map, as a process is only
+    /// required to be comparable for equality, not hashable or ordered.
+    StartWithValues(Vec<(P, V)>),
+    StartBatch(Vec<V>),
+    Cancel(),
+    Reconfigure(Vec<P>),
+    TakeOver(Term),
     Vote(bool),
+    VoteWithValue(V),
+    BatchVote(Vec<bool>),
+    ReadOnlyVote(),
 }
@@ -14,20 +14,38 @@
 
 use crate::algorithm::Value;
 use crate::message::Message;
+use crate::process::Process;
 
+use super::Decision;
 use super::Epoch;
+use super::Term;
 
 #[derive(Clone, Debug, Eq, PartialEq)]
-pub enum TwoPhaseCommitMessage<V>
+pub enum TwoPhaseCommitMessage<P, V>
 where
+    P: Process,
     V: Value,
 {
-    VoteRequest(Epoch, V),
+    VoteRequest(Term, Epoch, V, Vec<P>, Vec<P>),
     VoteResponse(Epoch, bool),
-    Commit(Epoch),
-    Abort(Epoch),
+    VoteResponseWithValue(Epoch, V),
+    ReadOnlyVoteResponse(Epoch),
+    Commit(Term, Epoch),
+    Abort(Term, Epoch),
     DecisionRequest(Epoch),
     DecisionAck(Epoch),
+    NewTerm(Term),
+    NewTermAck(Term, Epoch, Option<Decision>),
+    Outcome(Epoch, Option<V>, Decision),
+    BatchVoteRequest(Term, Epoch, Vec<V>, Vec<P>, Vec<P>),
+    BatchVoteResponse(Epoch, Vec<bool>),
+    BatchCommit(Term, Epoch, Vec<bool>),
+    BatchOutcome(Epoch, Vec<V>, Vec<bool>),
 }
 
-impl<V> Message for TwoPhaseCommitMessage<V> where V: Value {}
+impl<P, V> Message for TwoPhaseCommitMessage<P, V>
+where
+    P: Process,
+    V: Value,
+{
+}
@@ -12,44 +12,53 @@
 // See the License for the specific language governing permissions and
 // limitations under the License.
 
+use crate::algorithm::Value;
 use crate::error::InvalidStateError;
 use crate::process::Process;
 use crate::time::Time;
 
+use super::Epoch;
+use super::ObserverContext;
 use super::ParticipantContext;
 use super::TwoPhaseCommitState;
 use super::{CoordinatorContext, Participant};
 
 #[derive(Clone, Debug, PartialEq)]
-enum InnerContext<P, T>
+enum InnerContext<P, V, T>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
-    Coordinator(CoordinatorContext<P, T>),
+    Coordinator(CoordinatorContext<P, V, T>),
     Participant(ParticipantContext<P, T>),
+    Observer(ObserverContext),
 }
 
 #[derive(Clone, Debug, PartialEq)]
-pub struct TwoPhaseCommitRoleContext<P, T>
+pub struct TwoPhaseCommitRoleContext<P, V, T>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
-    inner: InnerContext<P, T>,
+    inner: InnerContext<P, V, T>,
 }
 
-impl<P, T> TwoPhaseCommitRoleContext<P, T>
+impl<P, V, T> TwoPhaseCommitRoleContext<P, V, T>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
     pub(super) fn new_coordinator(
-        participants: Vec<Participant<P>>,
+        participants: Vec<Participant<P, V>>,
+        observers: Vec<P>,
         state: TwoPhaseCommitState<T>,
     ) -> Result<Self, InvalidStateError> {
         Ok(Self {
             inner: InnerContext::Coordinator(CoordinatorContext {
+                observers,
                 participants,
                 state: state.try_into()?,
             }),
@@ -58,27 +67,63 @@ where
 
     pub(super) fn new_participant(
         participant_processes: Vec<P>,
+        observers: Vec<P>,
         state: TwoPhaseCommitState<T>,
+        last_read_only_epoch: Option<Epoch>,
+        join_epoch: Option<Epoch>,
     ) -> Result<Self, InvalidStateError> {
         Ok(Self {
             inner: InnerContext::Participant(ParticipantContext {
+                decision_requests: 0,
+                join_epoch,
+                last_read_only_epoch,
+                observers,
                 participant_processes,
+                peer_votes: Vec::new(),
                 state: state.try_into()?,
             }),
         })
     }
 
-    pub(super) fn participants(&self) -> Option<&Vec<Participant<P>>> {
+    pub(super) fn new_observer() -> Self {
+        Self {
+            inner: InnerContext::Observer(ObserverContext),
+        }
+    }
+
+    pub(super) fn participants(&self) -> Option<&Vec<Participant<P, V>>> {
         match &self.inner {
             InnerContext::Coordinator(c) => Some(&c.participants),
-            InnerContext::Participant(_) => None,
+            InnerContext::Participant(_) | InnerContext::Observer(_) => None,
+        }
+    }
+
+    pub(super) fn observers(&self) -> Option<&Vec<P>> {
+        match &self.inner {
+            InnerContext::Coordinator(c) => Some(&c.observers),
+            InnerContext::Participant(c) => Some(&c.observers),
+            InnerContext::Observer(_) => None,
         }
     }
 
     pub(super) fn participant_processes(&self) -> Option<&Vec<P>> {
         match &self.inner {
-            InnerContext::Coordinator(_) => None,
             InnerContext::Participant(c) => Some(&c.participant_processes),
+            InnerContext::Coordinator(_) | InnerContext::Observer(_) => None,
+        }
+    }
+
+    pub(super) fn last_read_only_epoch(&self) -> Option<Epoch> {
+        match &self.inner {
+            InnerContext::Participant(c) => c.last_read_only_epoch,
+            InnerContext::Coordinator(_) | InnerContext::Observer(_) => None,
+        }
+    }
+
+    pub(super) fn join_epoch(&self) -> Option<Epoch> {
+        match &self.inner {
+            InnerContext::Participant(c) => c.join_epoch,
+            InnerContext::Coordinator(_) | InnerContext::Observer(_) => None,
         }
     }
 
@@ -86,18 +131,20 @@ where
         match &self.inner {
             InnerContext::Coordinator(c) => c.state.clone().into(),
             InnerContext::Participant(c) => c.state.clone().into(),
+            InnerContext::Observer(_) => TwoPhaseCommitState::Observing,
         }
     }
 }
 
-impl<P, T> TryFrom<TwoPhaseCommitRoleContext<P, T>> for CoordinatorContext<P, T>
+impl<P, V, T> TryFrom<TwoPhaseCommitRoleContext<P, V, T>> for CoordinatorContext<P, V, T>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
     type Error = InvalidStateError;
 
-    fn try_from(context: TwoPhaseCommitRoleContext<P, T>) -> Result<Self, Self::Error> {
+    fn try_from(context: TwoPhaseCommitRoleContext<P, V, T>) -> Result<Self, Self::Error> {
         match context.inner {
             InnerContext::Coordinator(c) => Ok(c),
             InnerContext::Participant(_) => Err(InvalidStateError::with_message(
@@ -105,18 +152,24 @@ where
                 because inner context type is Participant"
                     .into(),
             )),
+            InnerContext::Observer(_) => Err(InvalidStateError::with_message(
+                "unable to convert TwoPhaseCommitRoleContext to CoordinatorContext \
+                because inner context type is Observer"
+                    .into(),
+            )),
         }
     }
 }
 
-impl<P, T> TryFrom<TwoPhaseCommitRoleContext<P, T>> for ParticipantContext<P, T>
+impl<P, V, T> TryFrom<TwoPhaseCommitRoleContext<P, V, T>> for ParticipantContext<P, T>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
     type Error = InvalidStateError;
 
-    fn try_from(context: TwoPhaseCommitRoleContext<P, T>) -> Result<Self, Self::Error> {
+    fn try_from(context: TwoPhaseCommitRoleContext<P, V, T>) -> Result<Self, Self::Error> {
         match context.inner {
             InnerContext::Participant(c) => Ok(c),
             InnerContext::Coordinator(_) => Err(InvalidStateError::with_message(
@@ -124,25 +177,57 @@ where
                 because inner context type is Coordinator"
                     .into(),
             )),
+            InnerContext::Observer(_) => Err(InvalidStateError::with_message(
+                "unable to convert TwoPhaseCommitRoleContext to ParticipantContext \
+                because inner context type is Observer"
+                    .into(),
+            )),
+        }
+    }
+}
+
+impl<P, V, T> TryFrom<TwoPhaseCommitRoleContext<P, V, T>> for ObserverContext
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    type Error = InvalidStateError;
+
+    fn try_from(context: TwoPhaseCommitRoleContext<P, V, T>) -> Result<Self, Self::Error> {
+        match context.inner {
+            InnerContext::Observer(c) => Ok(c),
+            InnerContext::Coordinator(_) => Err(InvalidStateError::with_message(
+                "unable to convert TwoPhaseCommitRoleContext to ObserverContext \
+                because inner context type is Coordinator"
+                    .into(),
+            )),
+            InnerContext::Participant(_) => Err(InvalidStateError::with_message(
+                "unable to convert TwoPhaseCommitRoleContext to ObserverContext \
+                because inner context type is Participant"
+                    .into(),
+            )),
         }
     }
 }
 
-impl<P, T> From<CoordinatorContext<P, T>> for TwoPhaseCommitRoleContext<P, T>
+impl<P, V, T> From<CoordinatorContext<P, V, T>> for TwoPhaseCommitRoleContext<P, V, T>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
-    fn from(context: CoordinatorContext<P, T>) -> Self {
+    fn from(context: CoordinatorContext<P, V, T>) -> Self {
         Self {
             inner: InnerContext::Coordinator(context),
         }
     }
 }
 
-impl<P, T> From<ParticipantContext<P, T>> for TwoPhaseCommitRoleContext<P, T>
+impl<P, V, T> From<ParticipantContext<P, T>> for TwoPhaseCommitRoleContext<P, V, T>
 where
     P: Process,
+    V: Value,
     T: Time,
 {
     fn from(context: ParticipantContext<P, T>) -> Self {
@@ -151,3 +236,16 @@ where
         }
     }
 }
+
+impl<P, V, T> From<ObserverContext> for TwoPhaseCommitRoleContext<P, V, T>
+where
+    P: Process,
+    V: Value,
+    T: Time,
+{
+    fn from(context: ObserverContext) -> Self {
+        Self {
+            inner: InnerContext::Observer(context),
+        }
+    }
+}
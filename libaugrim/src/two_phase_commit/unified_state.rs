@@ -31,10 +31,17 @@ pub enum TwoPhaseCommitState<T> {
     },
     WaitingForStart,
     WaitingForVoteRequest,
-    WaitingForVote,
+    WaitingForVote {
+        local_vote_timeout_start: T,
+    },
     WaitingForDecisionAck {
         ack_timeout_start: T,
     },
+    WaitingForNewTermAck {
+        ack_timeout_start: T,
+    },
+    /// The state of an observer, which follows the decisions of the coordinator without voting.
+    Observing,
 }
 
 impl<T> TryFrom<TwoPhaseCommitState<T>> for CoordinatorState<T>
@@ -51,15 +58,22 @@ where
                 Ok(CoordinatorState::Voting { vote_timeout_start })
             }
             TwoPhaseCommitState::WaitingForStart => Ok(CoordinatorState::WaitingForStart),
-            TwoPhaseCommitState::WaitingForVote => Ok(CoordinatorState::WaitingForVote),
+            TwoPhaseCommitState::WaitingForVote {
+                local_vote_timeout_start,
+            } => Ok(CoordinatorState::WaitingForVote {
+                local_vote_timeout_start,
+            }),
             TwoPhaseCommitState::WaitingForDecisionAck { ack_timeout_start } => {
                 Ok(CoordinatorState::WaitingForDecisionAck { ack_timeout_start })
             }
-            TwoPhaseCommitState::WaitingForVoteRequest | TwoPhaseCommitState::Voted { .. } => {
-                Err(InvalidStateError::with_message(format!(
-                    "invalid state for coordinator: {state:?}",
-                )))
+            TwoPhaseCommitState::WaitingForNewTermAck { ack_timeout_start } => {
+                Ok(CoordinatorState::WaitingForNewTermAck { ack_timeout_start })
             }
+            TwoPhaseCommitState::WaitingForVoteRequest
+            | TwoPhaseCommitState::Voted { .. }
+            | TwoPhaseCommitState::Observing => Err(InvalidStateError::with_message(format!(
+                "invalid state for coordinator: {state:?}",
+            ))),
         }
     }
 }
@@ -84,10 +98,16 @@ where
             TwoPhaseCommitState::WaitingForVoteRequest => {
                 Ok(ParticipantState::WaitingForVoteRequest)
             }
-            TwoPhaseCommitState::WaitingForVote => Ok(ParticipantState::WaitingForVote),
+            TwoPhaseCommitState::WaitingForVote {
+                local_vote_timeout_start,
+            } => Ok(ParticipantState::WaitingForVote {
+                local_vote_timeout_start,
+            }),
             TwoPhaseCommitState::WaitingForStart
             | TwoPhaseCommitState::WaitingForDecisionAck { .. }
-            | TwoPhaseCommitState::Voting { .. } => Err(InvalidStateError::with_message(format!(
+            | TwoPhaseCommitState::WaitingForNewTermAck { .. }
+            | TwoPhaseCommitState::Voting { .. }
+            | TwoPhaseCommitState::Observing => Err(InvalidStateError::with_message(format!(
                 "invalid state for participant: {state:?}",
             ))),
         }
@@ -106,10 +126,17 @@ where
                 TwoPhaseCommitState::Voting { vote_timeout_start }
             }
             CoordinatorState::WaitingForStart => TwoPhaseCommitState::WaitingForStart,
-            CoordinatorState::WaitingForVote => TwoPhaseCommitState::WaitingForVote,
+            CoordinatorState::WaitingForVote {
+                local_vote_timeout_start,
+            } => TwoPhaseCommitState::WaitingForVote {
+                local_vote_timeout_start,
+            },
             CoordinatorState::WaitingForDecisionAck { ack_timeout_start } => {
                 TwoPhaseCommitState::WaitingForDecisionAck { ack_timeout_start }
             }
+            CoordinatorState::WaitingForNewTermAck { ack_timeout_start } => {
+                TwoPhaseCommitState::WaitingForNewTermAck { ack_timeout_start }
+            }
         }
     }
 }
@@ -130,7 +157,11 @@ where
                 decision_timeout_start,
             },
             ParticipantState::WaitingForVoteRequest => TwoPhaseCommitState::WaitingForVoteRequest,
-            ParticipantState::WaitingForVote => TwoPhaseCommitState::WaitingForVote,
+            ParticipantState::WaitingForVote {
+                local_vote_timeout_start,
+            } => TwoPhaseCommitState::WaitingForVote {
+                local_vote_timeout_start,
+            },
         }
     }
 }